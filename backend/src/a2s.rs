@@ -0,0 +1,393 @@
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+const SIMPLE_HEADER: i32 = -1;
+const SPLIT_HEADER: i32 = -2;
+const A2S_INFO: u8 = 0x54;
+const A2S_PLAYER: u8 = 0x55;
+const S2A_INFO: u8 = 0x49;
+const S2A_PLAYER: u8 = 0x44;
+const S2C_CHALLENGE: u8 = 0x41;
+const MAX_CHALLENGE_ROUNDS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct A2sEndpoint {
+    pub host: String,
+    pub port: u16,
+}
+
+impl A2sEndpoint {
+    pub fn from_server_config(config: &serde_json::Value) -> Result<Self, String> {
+        let a2s = config
+            .get("a2s")
+            .filter(|value| value.is_object())
+            .ok_or_else(|| "server config has no a2s block".to_string())?;
+        let port = a2s
+            .get("port")
+            .and_then(serde_json::Value::as_u64)
+            .and_then(|value| u16::try_from(value).ok())
+            .ok_or_else(|| "a2s.port is missing or invalid".to_string())?;
+        let address = a2s
+            .get("address")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("");
+        Ok(Self {
+            host: crate::rcon::local_host_for(address),
+            port,
+        })
+    }
+
+    pub fn socket_address(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
+pub struct A2sInfo {
+    pub name: String,
+    pub map: String,
+    pub folder: String,
+    pub game: String,
+    pub players: u8,
+    pub max_players: u8,
+    pub bots: u8,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct A2sPlayer {
+    pub name: String,
+    pub score: i32,
+    pub duration_secs: f32,
+}
+
+pub async fn query_info(endpoint: &A2sEndpoint, timeout: Duration) -> Result<A2sInfo, String> {
+    let socket = connect(endpoint).await?;
+    let payload = request(&socket, timeout, |challenge| {
+        let mut packet = SIMPLE_HEADER.to_le_bytes().to_vec();
+        packet.push(A2S_INFO);
+        packet.extend_from_slice(b"Source Engine Query\0");
+        if let Some(challenge) = challenge {
+            packet.extend_from_slice(&challenge);
+        }
+        packet
+    })
+    .await?;
+    parse_info(&payload)
+}
+
+pub async fn query_players(endpoint: &A2sEndpoint, timeout: Duration) -> Result<Vec<A2sPlayer>, String> {
+    let socket = connect(endpoint).await?;
+    let payload = request(&socket, timeout, |challenge| {
+        let mut packet = SIMPLE_HEADER.to_le_bytes().to_vec();
+        packet.push(A2S_PLAYER);
+        packet.extend_from_slice(&challenge.unwrap_or([0xFF; 4]));
+        packet
+    })
+    .await?;
+    parse_players(&payload)
+}
+
+async fn connect(endpoint: &A2sEndpoint) -> Result<UdpSocket, String> {
+    let address = tokio::net::lookup_host(endpoint.socket_address())
+        .await
+        .map_err(|err| format!("invalid A2S address {}: {err}", endpoint.socket_address()))?
+        .next()
+        .ok_or_else(|| format!("invalid A2S address {}", endpoint.socket_address()))?;
+    let bind_address = if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+    let socket = UdpSocket::bind(bind_address)
+        .await
+        .map_err(|err| format!("failed to open A2S socket: {err}"))?;
+    socket
+        .connect(address)
+        .await
+        .map_err(|err| format!("failed to connect to A2S at {address}: {err}"))?;
+    Ok(socket)
+}
+
+async fn request(
+    socket: &UdpSocket,
+    timeout: Duration,
+    build: impl Fn(Option<[u8; 4]>) -> Vec<u8>,
+) -> Result<Vec<u8>, String> {
+    let mut challenge = None;
+    for _ in 0..MAX_CHALLENGE_ROUNDS {
+        socket
+            .send(&build(challenge))
+            .await
+            .map_err(|err| format!("A2S send failed: {err}"))?;
+        let payload = receive(socket, timeout).await?;
+        match payload.as_slice() {
+            [S2C_CHALLENGE, a, b, c, d, ..] => challenge = Some([*a, *b, *c, *d]),
+            [] => return Err("empty A2S response".to_string()),
+            _ => return Ok(payload),
+        }
+    }
+    Err("A2S server kept answering with challenges".to_string())
+}
+
+async fn receive(socket: &UdpSocket, timeout: Duration) -> Result<Vec<u8>, String> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut buffer = vec![0u8; 65_535];
+    let mut parts: Vec<Option<Vec<u8>>> = Vec::new();
+    loop {
+        let len = tokio::time::timeout_at(deadline, socket.recv(&mut buffer))
+            .await
+            .map_err(|_| "A2S query timed out".to_string())?
+            .map_err(|err| format!("A2S receive failed: {err}"))?;
+        let packet = &buffer[..len];
+        if packet.len() < 4 {
+            continue;
+        }
+        match i32::from_le_bytes([packet[0], packet[1], packet[2], packet[3]]) {
+            SIMPLE_HEADER => return Ok(packet[4..].to_vec()),
+            SPLIT_HEADER if packet.len() >= 12 => {
+                let id = u32::from_le_bytes([packet[4], packet[5], packet[6], packet[7]]);
+                if id & 0x8000_0000 != 0 {
+                    return Err("compressed A2S responses are not supported".to_string());
+                }
+                let total = usize::from(packet[8]);
+                let number = usize::from(packet[9]);
+                if total == 0 || number >= total {
+                    continue;
+                }
+                if parts.len() != total {
+                    parts = vec![None; total];
+                }
+                parts[number] = Some(packet[12..].to_vec());
+                if parts.iter().all(Option::is_some) {
+                    let joined: Vec<u8> = parts.into_iter().flatten().flatten().collect();
+                    return joined
+                        .get(4..)
+                        .map(<[u8]>::to_vec)
+                        .ok_or_else(|| "malformed split A2S response".to_string());
+                }
+            }
+            _ => continue,
+        }
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let end = self.position + count;
+        let slice = self
+            .data
+            .get(self.position..end)
+            .ok_or_else(|| "truncated A2S response".to_string())?;
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        let bytes = self.bytes(4)?;
+        Ok(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        let bytes = self.bytes(4)?;
+        Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let rest = &self.data[self.position..];
+        let end = rest
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or_else(|| "unterminated string in A2S response".to_string())?;
+        self.position += end + 1;
+        Ok(String::from_utf8_lossy(&rest[..end]).into_owned())
+    }
+}
+
+fn parse_info(payload: &[u8]) -> Result<A2sInfo, String> {
+    let mut reader = Reader::new(payload);
+    if reader.u8()? != S2A_INFO {
+        return Err("unexpected A2S_INFO response type".to_string());
+    }
+    let _protocol = reader.u8()?;
+    let name = reader.string()?;
+    let map = reader.string()?;
+    let folder = reader.string()?;
+    let game = reader.string()?;
+    let _app_id = reader.bytes(2)?;
+    let players = reader.u8()?;
+    let max_players = reader.u8()?;
+    let bots = reader.u8()?;
+    let _server_type_environment_visibility_vac = reader.bytes(4)?;
+    let version = reader.string().unwrap_or_default();
+    Ok(A2sInfo {
+        name,
+        map,
+        folder,
+        game,
+        players,
+        max_players,
+        bots,
+        version,
+    })
+}
+
+fn parse_players(payload: &[u8]) -> Result<Vec<A2sPlayer>, String> {
+    let mut reader = Reader::new(payload);
+    if reader.u8()? != S2A_PLAYER {
+        return Err("unexpected A2S_PLAYER response type".to_string());
+    }
+    let count = reader.u8()?;
+    let mut players = Vec::with_capacity(usize::from(count));
+    for _ in 0..count {
+        let _index = reader.u8()?;
+        players.push(A2sPlayer {
+            name: reader.string()?,
+            score: reader.i32()?,
+            duration_secs: reader.f32()?,
+        });
+    }
+    Ok(players)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    const CHALLENGE: [u8; 4] = [0x11, 0x22, 0x33, 0x44];
+
+    fn info_payload() -> Vec<u8> {
+        let mut payload = vec![S2A_INFO, 17];
+        for value in ["ARSSM Test", "Everon", "reforger", "Arma Reforger"] {
+            payload.extend_from_slice(value.as_bytes());
+            payload.push(0);
+        }
+        payload.extend_from_slice(&[0, 0, 3, 32, 0, b'd', b'l', 0, 1]);
+        payload.extend_from_slice(b"1.2.0.76\0");
+        payload
+    }
+
+    fn players_payload() -> Vec<u8> {
+        let mut payload = vec![S2A_PLAYER, 2];
+        for (index, name, score, duration) in [(0u8, "Alice", 12i32, 61.5f32), (1, "Bob", 3, 5.0)] {
+            payload.push(index);
+            payload.extend_from_slice(name.as_bytes());
+            payload.push(0);
+            payload.extend_from_slice(&score.to_le_bytes());
+            payload.extend_from_slice(&duration.to_le_bytes());
+        }
+        payload
+    }
+
+    fn simple(payload: &[u8]) -> Vec<u8> {
+        let mut packet = SIMPLE_HEADER.to_le_bytes().to_vec();
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    /// Answers like a server that requires a challenge and splits player lists.
+    async fn start_fake_server() -> A2sEndpoint {
+        let socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await.expect("bind fake server"));
+        let port = socket.local_addr().expect("local addr").port();
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; 2048];
+            loop {
+                let Ok((len, peer)) = socket.recv_from(&mut buffer).await else {
+                    return;
+                };
+                let request = buffer[..len].to_vec();
+                let challenged = request.ends_with(&CHALLENGE);
+                let replies = match request.get(4) {
+                    Some(&A2S_INFO) if challenged => vec![simple(&info_payload())],
+                    Some(&A2S_PLAYER) if challenged => {
+                        let whole = simple(&players_payload());
+                        let (first, second) = whole.split_at(whole.len() / 2);
+                        [(1u8, second), (0u8, first)]
+                            .iter()
+                            .map(|(number, part)| {
+                                let mut packet = SPLIT_HEADER.to_le_bytes().to_vec();
+                                packet.extend_from_slice(&7u32.to_le_bytes());
+                                packet.extend_from_slice(&[2, *number]);
+                                packet.extend_from_slice(&1248u16.to_le_bytes());
+                                packet.extend_from_slice(part);
+                                packet
+                            })
+                            .collect()
+                    }
+                    Some(_) => {
+                        let mut payload = vec![S2C_CHALLENGE];
+                        payload.extend_from_slice(&CHALLENGE);
+                        vec![simple(&payload)]
+                    }
+                    None => Vec::new(),
+                };
+                for reply in replies {
+                    let _ = socket.send_to(&reply, peer).await;
+                }
+            }
+        });
+        A2sEndpoint {
+            host: "127.0.0.1".to_string(),
+            port,
+        }
+    }
+
+    #[tokio::test]
+    async fn info_query_answers_challenge() {
+        let endpoint = start_fake_server().await;
+        let info = query_info(&endpoint, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(info.name, "ARSSM Test");
+        assert_eq!(info.map, "Everon");
+        assert_eq!(info.players, 3);
+        assert_eq!(info.max_players, 32);
+        assert_eq!(info.version, "1.2.0.76");
+    }
+
+    #[tokio::test]
+    async fn player_query_reassembles_split_packets() {
+        let endpoint = start_fake_server().await;
+        let players = query_players(&endpoint, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[0].name, "Alice");
+        assert_eq!(players[0].score, 12);
+        assert_eq!(players[1].name, "Bob");
+    }
+
+    #[tokio::test]
+    async fn silent_endpoint_times_out() {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = A2sEndpoint {
+            host: "127.0.0.1".to_string(),
+            port: socket.local_addr().unwrap().port(),
+        };
+        let err = query_info(&endpoint, Duration::from_millis(50)).await.unwrap_err();
+        assert!(err.contains("timed out"));
+    }
+
+    #[test]
+    fn truncated_info_is_rejected() {
+        let payload = info_payload();
+        assert!(parse_info(&payload[..10]).is_err());
+    }
+
+    #[test]
+    fn endpoint_is_read_from_server_config() {
+        let config = serde_json::json!({ "a2s": { "address": "0.0.0.0", "port": 17777 } });
+        let endpoint = A2sEndpoint::from_server_config(&config).unwrap();
+        assert_eq!(endpoint.socket_address(), "127.0.0.1:17777");
+    }
+}
@@ -7,6 +7,25 @@ pub fn baseline_config() -> &'static str {
     BASELINE_CONFIG
 }
 
+/// One entry of `game.mods`. `name` and `version` are left out when unknown; without
+/// a version the server downloads the latest one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigMod {
+    pub mod_id: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+}
+
+impl ConfigMod {
+    pub fn new(mod_id: impl Into<String>) -> Self {
+        Self {
+            mod_id: mod_id.into(),
+            name: None,
+            version: None,
+        }
+    }
+}
+
 pub fn generate_server_config(
     scenario_id: &str,
     mod_ids: &[String],
@@ -15,7 +34,8 @@ pub fn generate_server_config(
     let mut root: Value = serde_json::from_str(BASELINE_CONFIG)
         .map_err(|err| format!("failed to parse baseline config: {err}"))?;
 
-    apply_game_overrides(&mut root, scenario_id, mod_ids, display_name)?;
+    let mods: Vec<ConfigMod> = mod_ids.iter().map(ConfigMod::new).collect();
+    apply_game_overrides(&mut root, scenario_id, &mods, display_name)?;
 
     Ok(root)
 }
@@ -23,7 +43,7 @@ pub fn generate_server_config(
 pub fn apply_game_overrides(
     config: &mut Value,
     scenario_id: &str,
-    mod_ids: &[String],
+    mods: &[ConfigMod],
     display_name: Option<&str>,
 ) -> Result<(), String> {
     let game = config
@@ -40,11 +60,17 @@ pub fn apply_game_overrides(
         game.insert("name".to_string(), Value::String(name.to_string()));
     }
 
-    let mods = dedupe_mod_ids(mod_ids)
+    let mods = dedupe_mods(mods)
         .into_iter()
-        .map(|id| {
+        .map(|entry| {
             let mut mod_entry = serde_json::Map::new();
-            mod_entry.insert("modId".to_string(), Value::String(id));
+            mod_entry.insert("modId".to_string(), Value::String(entry.mod_id.clone()));
+            if let Some(name) = entry.name.as_deref().filter(|name| !name.trim().is_empty()) {
+                mod_entry.insert("name".to_string(), Value::String(name.to_string()));
+            }
+            if let Some(version) = entry.version.as_deref().filter(|version| !version.trim().is_empty()) {
+                mod_entry.insert("version".to_string(), Value::String(version.to_string()));
+            }
             Value::Object(mod_entry)
         })
         .collect::<Vec<_>>();
@@ -53,12 +79,12 @@ pub fn apply_game_overrides(
     Ok(())
 }
 
-fn dedupe_mod_ids(mod_ids: &[String]) -> Vec<String> {
+fn dedupe_mods(mods: &[ConfigMod]) -> Vec<&ConfigMod> {
     let mut seen = HashSet::new();
     let mut result = Vec::new();
-    for id in mod_ids {
-        if seen.insert(id.clone()) {
-            result.push(id.clone());
+    for entry in mods {
+        if seen.insert(entry.mod_id.as_str()) {
+            result.push(entry);
         }
     }
     result
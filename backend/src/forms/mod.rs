@@ -1,7 +1,9 @@
+use backend::models::RestartPolicy;
 use serde::{Deserialize, Serialize};
 use serde::de::{self, Deserializer};
+use std::collections::{BTreeMap, HashMap};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, schemars::JsonSchema)]
 pub struct AppConfig {
     pub steamcmd_dir: String,
     pub reforger_server_exe: String,
@@ -27,6 +29,89 @@ pub struct SettingsForm {
     pub reforger_server_exe: String,
     pub reforger_server_work_dir: String,
     pub profile_dir_base: String,
+    pub dependency_change_action: Option<String>,
+    pub stop_grace_period_secs: Option<String>,
+    pub steam_branch: Option<String>,
+    pub login_max_failures: Option<String>,
+    pub login_window_secs: Option<String>,
+    pub login_lockout_secs: Option<String>,
+    pub login_base_delay_ms: Option<String>,
+    pub login_max_delay_ms: Option<String>,
+    pub web_bind_address: Option<String>,
+    pub web_port: Option<String>,
+    pub web_tls_cert_path: Option<String>,
+    pub web_tls_key_path: Option<String>,
+    pub web_plain_http: Option<String>,
+}
+
+#[derive(Deserialize, Default, schemars::JsonSchema)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub target: Option<String>,
+    /// `YYYY-MM-DD`, inclusive.
+    pub since: Option<String>,
+    /// `YYYY-MM-DD`, inclusive.
+    pub until: Option<String>,
+    /// `jsonl` (default) or `csv` for exports.
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct WorkshopCacheForm {
+    pub ttl_secs: String,
+    pub offline: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct WorkshopCachePurgeForm {
+    /// `expired` or `all`.
+    pub scope: String,
+}
+
+#[derive(Deserialize, Default, schemars::JsonSchema)]
+pub struct WorkshopCachePurgeQuery {
+    /// Remove only pages older than the TTL instead of the whole cache.
+    #[serde(default)]
+    pub expired_only: bool,
+}
+
+#[derive(Deserialize)]
+pub struct LoginQuery {
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LoginForm {
+    pub username: String,
+    pub password: String,
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UserCreateForm {
+    pub username: String,
+    pub password: String,
+    pub role: String,
+}
+
+#[derive(Deserialize)]
+pub struct UserRoleForm {
+    pub role: String,
+}
+
+#[derive(Deserialize)]
+pub struct UserPasswordForm {
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub struct TokenCreateForm {
+    pub name: String,
+    pub scope_read: Option<String>,
+    pub scope_run_control: Option<String>,
+    pub scope_config_write: Option<String>,
+    pub expires_days: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -69,6 +154,8 @@ pub struct NewProfileCreateForm {
     pub selected_scenario_id_path: Option<String>,
     pub scenario_ids: Option<String>,
     pub optional_mod_ids: Option<String>,
+    /// JSON of the resolve step's `metadata`.
+    pub mod_metadata: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -79,6 +166,9 @@ pub struct EditProfileForm {
     #[serde(default, deserialize_with = "deserialize_mod_ids")]
     pub optional_package_ids: Option<Vec<String>>,
     pub optional_mod_ids: Option<String>,
+    pub restart_policy: Option<String>,
+    pub update_before_start: Option<String>,
+    pub pin_mod_versions: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -95,6 +185,29 @@ pub struct ProfilePathsForm {
     pub reforger_server_exe_override: String,
     pub reforger_server_work_dir_override: String,
     pub profile_dir_base_override: String,
+    #[serde(default)]
+    pub steam_branch_override: String,
+}
+
+#[derive(Deserialize)]
+pub struct ModVersionPinForm {
+    pub mod_id: String,
+    /// Empty to remove the pin.
+    #[serde(default)]
+    pub version: String,
+}
+
+#[derive(Deserialize)]
+pub struct AcceptUpgradesForm {
+    /// Missing to accept every pending upgrade.
+    pub mod_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, schemars::JsonSchema)]
+pub struct AcceptUpgradesRequest {
+    /// Mods to upgrade; empty to accept every pending upgrade.
+    #[serde(default)]
+    pub mod_ids: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -102,11 +215,104 @@ pub struct WorkshopSaveForm {
     pub selected_scenario_id_path: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, schemars::JsonSchema)]
 pub struct RunStartRequest {
     pub profile_id: Option<String>,
 }
 
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct PlayerKickRequest {
+    pub profile_id: Option<String>,
+    pub player_id: u32,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct PlayerBanRequest {
+    pub profile_id: Option<String>,
+    pub player_id: u32,
+    #[serde(default)]
+    pub duration_minutes: u64,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct PlayerBroadcastRequest {
+    pub profile_id: Option<String>,
+    pub message: String,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct RconCommandRequest {
+    pub profile_id: Option<String>,
+    pub command: String,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct RunProfileQuery {
+    pub profile_id: Option<String>,
+}
+
+/// Editable part of a `ServerProfile` for `/api/v1/profiles`. Resolve results and
+/// generated paths are managed by the server and ignored when sent.
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+pub struct ProfileRequest {
+    pub display_name: String,
+    pub workshop_url: String,
+    #[serde(default)]
+    pub selected_scenario_id_path: Option<String>,
+    #[serde(default)]
+    pub optional_mod_ids: Vec<String>,
+    #[serde(default)]
+    pub optional_package_ids: Vec<String>,
+    #[serde(default)]
+    pub load_session_save: bool,
+    #[serde(default)]
+    pub restart_policy: RestartPolicy,
+    #[serde(default)]
+    pub update_before_start: bool,
+    #[serde(default)]
+    pub steamcmd_dir_override: Option<String>,
+    #[serde(default)]
+    pub reforger_server_exe_override: Option<String>,
+    #[serde(default)]
+    pub reforger_server_work_dir_override: Option<String>,
+    #[serde(default)]
+    pub profile_dir_base_override: Option<String>,
+    #[serde(default)]
+    pub steam_branch_override: Option<String>,
+    #[serde(default)]
+    pub server_json_overrides: Option<serde_json::Value>,
+    #[serde(default)]
+    pub server_json_override_enabled: HashMap<String, bool>,
+    #[serde(default)]
+    pub pin_mod_versions: bool,
+    /// Versions by mod ID (or workshop URL) to write into the generated config.
+    #[serde(default)]
+    pub mod_version_pins: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+pub struct ModCreateRequest {
+    /// Workshop ID or URL.
+    pub mod_id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+pub struct ModUpdateRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+pub struct PackageRequest {
+    pub name: String,
+    #[serde(default)]
+    pub mod_ids: Vec<String>,
+}
+
 pub fn deserialize_mod_ids<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
 where
     D: Deserializer<'de>,
@@ -1,6 +1,10 @@
+pub mod a2s;
 pub mod models;
 pub mod config_gen;
 pub mod defaults;
+pub mod rcon;
 pub mod runner;
+pub mod steamcmd;
 pub mod storage;
 pub mod workshop;
+pub mod workshop_cache;
@@ -1,10 +1,11 @@
 mod forms;
 mod routes;
 mod security;
+mod server;
 mod services;
 mod views;
 
-use tracing::info;
+use server::ListenConfig;
 
 #[tokio::main]
 async fn main() {
@@ -12,22 +13,37 @@ async fn main() {
         .with_env_filter("info")
         .init();
 
-    let state = routes::default_state().await;
-    let app = routes::build_router(state);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("reset-credentials") {
+        let (creds, password) = security::reset_credentials()
+            .await
+            .expect("failed to reset credentials");
+        println!("New credentials (shown only once):");
+        println!("  Username: {}", creds.username);
+        println!("  Password: {password}");
+        println!("Stored as a password hash at: {}", security::credentials_path().display());
+        return;
+    }
 
-    let cert_path = security::cert_path();
-    let key_path = security::key_path();
-    security::ensure_tls_cert(&cert_path, &key_path)
-        .await
-        .expect("failed to prepare TLS certificates");
-    let tls_config = axum_server::tls_rustls::RustlsConfig::from_pem_file(cert_path, key_path)
+    let settings = backend::storage::load_settings(&backend::storage::settings_path())
         .await
-        .expect("failed to load TLS certificates");
+        .expect("failed to load settings");
+    let listen = match ListenConfig::resolve(&settings.web_server, |key| std::env::var(key).ok(), &args) {
+        Ok(listen) => listen,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    };
 
-    let addr = "0.0.0.0:3000".parse().expect("invalid bind address");
-    info!("server listening on https://0.0.0.0:3000");
-    axum_server::bind_rustls(addr, tls_config)
-        .serve(app.into_make_service())
-        .await
-        .expect("server failed");
+    let mut state = routes::default_state().await;
+    if listen.is_plain_http() {
+        state.sessions.set_secure_cookies(false);
+        state.behind_proxy = true;
+    }
+    state.tls_cert_path = listen.certificate_path();
+    routes::players::spawn_ban_sync_on_start(state.clone());
+    let app = routes::build_router(state);
+
+    server::serve(app, listen).await.expect("server failed");
 }
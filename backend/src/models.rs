@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct ServerProfile {
     pub profile_id: String,
     pub display_name: String,
@@ -20,6 +21,10 @@ pub struct ServerProfile {
     #[serde(default)]
     pub load_session_save: bool,
     #[serde(default)]
+    pub restart_policy: RestartPolicy,
+    #[serde(default)]
+    pub update_before_start: bool,
+    #[serde(default)]
     pub steamcmd_dir_override: Option<String>,
     #[serde(default)]
     pub reforger_server_exe_override: Option<String>,
@@ -28,6 +33,8 @@ pub struct ServerProfile {
     #[serde(default)]
     pub profile_dir_base_override: Option<String>,
     #[serde(default)]
+    pub steam_branch_override: Option<String>,
+    #[serde(default)]
     pub server_json_overrides: serde_json::Value,
     #[serde(default)]
     pub server_json_override_enabled: std::collections::HashMap<String, bool>,
@@ -37,6 +44,125 @@ pub struct ServerProfile {
     pub last_resolved_at: Option<String>,
     #[serde(default)]
     pub last_resolve_hash: Option<String>,
+    /// Workshop details of the root mod, its dependencies and the optional mods
+    /// from the last resolve.
+    #[serde(default)]
+    pub mod_metadata: BTreeMap<String, ModMetadata>,
+    /// Pin every resolved mod to its version when it is first resolved or when
+    /// this is switched on. Later versions become pending upgrades.
+    #[serde(default)]
+    pub pin_mod_versions: bool,
+    /// Versions by mod ID that are written into the generated config.
+    #[serde(default)]
+    pub mod_version_pins: BTreeMap<String, String>,
+}
+
+/// Details shown on a mod's workshop page. Everything is optional because pages
+/// differ in what they expose.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct ModMetadata {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    /// Current version of the mod, e.g. `1.0.15`.
+    pub version: Option<String>,
+    pub size_bytes: Option<u64>,
+    /// As shown by the workshop, usually an ISO 8601 timestamp.
+    pub updated_at: Option<String>,
+    /// Game version the current mod version was built for.
+    pub game_version: Option<String>,
+}
+
+impl ModMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Why a mod is part of a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModRole {
+    Root,
+    Dependency,
+    Optional,
+}
+
+impl ModRole {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ModRole::Root => "Root",
+            ModRole::Dependency => "Dependency",
+            ModRole::Optional => "Optional",
+        }
+    }
+}
+
+/// Pinned and latest resolved version of one mod of a profile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ModVersionStatus {
+    pub mod_id: String,
+    pub role: ModRole,
+    /// Workshop details from the last resolve; `version` is the latest version.
+    pub metadata: ModMetadata,
+    pub pinned_version: Option<String>,
+    /// Pinned to a version other than the latest resolved one.
+    pub upgrade_available: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    OnCrash,
+    Always,
+}
+
+impl RestartPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Never => "never",
+            Self::OnCrash => "on_crash",
+            Self::Always => "always",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "never" => Some(Self::Never),
+            "on_crash" => Some(Self::OnCrash),
+            "always" => Some(Self::Always),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct BanRecord {
+    pub guid: String,
+    pub name: String,
+    #[serde(default)]
+    pub reason: String,
+    pub banned_at: u64,
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    #[serde(default)]
+    pub banned_by: Option<String>,
+}
+
+impl BanRecord {
+    pub fn is_active(&self, now: u64) -> bool {
+        self.expires_at.map(|expires_at| expires_at > now).unwrap_or(true)
+    }
+
+    /// Minutes left as BattlEye expects them; 0 means permanent.
+    pub fn remaining_minutes(&self, now: u64) -> u64 {
+        match self.expires_at {
+            Some(expires_at) => expires_at.saturating_sub(now).div_ceil(60).max(1),
+            None => 0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,15 +172,84 @@ pub struct ModPreset {
     pub mod_ids: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct ModEntry {
     pub mod_id: String,
     pub name: String,
+    /// Filled in whenever a resolve covers this mod.
+    #[serde(default)]
+    pub metadata: ModMetadata,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Copies resolved workshop details onto the matching mod entries. Names that
+/// were left empty or set to the bare ID are replaced by the workshop name.
+/// Returns whether anything changed.
+pub fn apply_mod_metadata(mods: &mut [ModEntry], metadata: &BTreeMap<String, ModMetadata>) -> bool {
+    let mut changed = false;
+    for entry in mods.iter_mut() {
+        let Some(resolved) = metadata.get(&entry.mod_id) else {
+            continue;
+        };
+        if entry.metadata != *resolved {
+            entry.metadata = resolved.clone();
+            changed = true;
+        }
+        if let Some(name) = resolved.name.as_deref() {
+            let name_is_placeholder = entry.name.trim().is_empty() || entry.name.trim() == entry.mod_id;
+            if name_is_placeholder && entry.name != name {
+                entry.name = name.to_string();
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct ModPackage {
     pub package_id: String,
     pub name: String,
     pub mod_ids: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(mod_id: &str, name: &str) -> ModEntry {
+        ModEntry {
+            mod_id: mod_id.to_string(),
+            name: name.to_string(),
+            metadata: ModMetadata::default(),
+        }
+    }
+
+    #[test]
+    fn apply_mod_metadata_fills_placeholder_names_only() {
+        let resolved = ModMetadata {
+            name: Some("Some Mod".to_string()),
+            version: Some("1.0.15".to_string()),
+            ..ModMetadata::default()
+        };
+        let metadata = BTreeMap::from([
+            ("5AAAC70D754245DD".to_string(), resolved.clone()),
+            ("5C9758250C8C56F1".to_string(), resolved.clone()),
+            ("60C4CE4888FF4621".to_string(), resolved.clone()),
+        ]);
+        let mut mods = vec![
+            entry("5AAAC70D754245DD", ""),
+            entry("5C9758250C8C56F1", "5C9758250C8C56F1"),
+            entry("60C4CE4888FF4621", "My name"),
+            entry("591AF5BDA9F7CE8B", ""),
+        ];
+
+        assert!(apply_mod_metadata(&mut mods, &metadata));
+        assert_eq!(mods[0].name, "Some Mod");
+        assert_eq!(mods[1].name, "Some Mod");
+        assert_eq!(mods[2].name, "My name");
+        assert_eq!(mods[2].metadata, resolved);
+        assert!(mods[3].metadata.is_empty());
+
+        assert!(!apply_mod_metadata(&mut mods, &metadata));
+    }
+}
@@ -0,0 +1,802 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::sync::{broadcast, oneshot};
+use tokio::task::AbortHandle;
+
+const PACKET_LOGIN: u8 = 0x00;
+const PACKET_COMMAND: u8 = 0x01;
+const PACKET_MESSAGE: u8 = 0x02;
+const MAX_PACKET_SIZE: usize = 65_507;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RconEndpoint {
+    pub host: String,
+    pub port: u16,
+    pub password: String,
+}
+
+impl RconEndpoint {
+    pub fn from_server_config(config: &serde_json::Value) -> Result<Self, String> {
+        let rcon = config
+            .get("rcon")
+            .filter(|value| value.is_object())
+            .ok_or_else(|| "server config has no rcon block".to_string())?;
+        let port = rcon
+            .get("port")
+            .and_then(serde_json::Value::as_u64)
+            .and_then(|value| u16::try_from(value).ok())
+            .ok_or_else(|| "rcon.port is missing or invalid".to_string())?;
+        let password = rcon
+            .get("password")
+            .and_then(serde_json::Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| "rcon.password is not set".to_string())?;
+        let address = rcon
+            .get("address")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("");
+        Ok(Self {
+            host: local_host_for(address),
+            port,
+            password: password.to_string(),
+        })
+    }
+
+    pub fn socket_address(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+}
+
+/// A wildcard bind address is reachable through loopback on this host.
+pub(crate) fn local_host_for(address: &str) -> String {
+    match address.trim() {
+        "" | "0.0.0.0" => "127.0.0.1".to_string(),
+        "::" => "::1".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RconOptions {
+    pub timeout: Duration,
+    pub attempts: u32,
+    pub keepalive_interval: Duration,
+}
+
+impl Default for RconOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(2),
+            attempts: 3,
+            // BattlEye drops clients that stay silent for more than 45 seconds.
+            keepalive_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RconClient {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    socket: Arc<UdpSocket>,
+    options: RconOptions,
+    next_sequence: Mutex<u8>,
+    pending: Mutex<HashMap<u8, PendingCommand>>,
+    last_message_sequence: Mutex<Option<u8>>,
+    messages: broadcast::Sender<String>,
+    connected: AtomicBool,
+    tasks: Mutex<Vec<AbortHandle>>,
+}
+
+struct PendingCommand {
+    parts: Vec<Option<Vec<u8>>>,
+    reply: oneshot::Sender<String>,
+}
+
+impl RconClient {
+    pub async fn connect(endpoint: &RconEndpoint, options: RconOptions) -> Result<Self, String> {
+        let address = tokio::net::lookup_host(endpoint.socket_address())
+            .await
+            .map_err(|err| format!("invalid RCON address {}: {err}", endpoint.socket_address()))?
+            .next()
+            .ok_or_else(|| format!("invalid RCON address {}", endpoint.socket_address()))?;
+        let bind_address = if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(bind_address)
+            .await
+            .map_err(|err| format!("failed to open RCON socket: {err}"))?;
+        socket
+            .connect(address)
+            .await
+            .map_err(|err| format!("failed to connect to RCON at {address}: {err}"))?;
+        login(&socket, &endpoint.password, &options).await?;
+
+        let socket = Arc::new(socket);
+        let (messages, _) = broadcast::channel(256);
+        let keepalive_interval = options.keepalive_interval;
+        let shared = Arc::new(Shared {
+            socket: Arc::clone(&socket),
+            options,
+            next_sequence: Mutex::new(0),
+            pending: Mutex::new(HashMap::new()),
+            last_message_sequence: Mutex::new(None),
+            messages,
+            connected: AtomicBool::new(true),
+            tasks: Mutex::new(Vec::new()),
+        });
+
+        let receiver = tokio::spawn(receive_loop(socket, Arc::downgrade(&shared)));
+        let keepalive = tokio::spawn(keepalive_loop(Arc::downgrade(&shared), keepalive_interval));
+        shared
+            .tasks
+            .lock()
+            .expect("rcon task lock")
+            .extend([receiver.abort_handle(), keepalive.abort_handle()]);
+        Ok(Self { shared })
+    }
+
+    pub async fn command(&self, command: &str) -> Result<String, String> {
+        if !self.is_connected() {
+            return Err("RCON connection lost".to_string());
+        }
+        self.shared.send_command(command).await
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<String> {
+        self.shared.messages.subscribe()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.shared.connected.load(Ordering::SeqCst)
+    }
+
+    pub fn close(&self) {
+        self.shared.connected.store(false, Ordering::SeqCst);
+        self.shared.abort_tasks();
+    }
+}
+
+impl Shared {
+    async fn send_command(&self, command: &str) -> Result<String, String> {
+        let sequence = {
+            let mut next = self.next_sequence.lock().expect("rcon sequence lock");
+            let sequence = *next;
+            *next = next.wrapping_add(1);
+            sequence
+        };
+        let (reply_tx, mut reply_rx) = oneshot::channel();
+        self.pending.lock().expect("rcon pending lock").insert(
+            sequence,
+            PendingCommand {
+                parts: Vec::new(),
+                reply: reply_tx,
+            },
+        );
+
+        let mut payload = vec![PACKET_COMMAND, sequence];
+        payload.extend_from_slice(command.as_bytes());
+        let packet = encode_packet(&payload);
+
+        // Unanswered packets are resent with the same sequence number, as the protocol expects.
+        let mut result = Err("RCON command timed out".to_string());
+        for _ in 0..self.options.attempts.max(1) {
+            if let Err(err) = self.socket.send(&packet).await {
+                result = Err(format!("RCON send failed: {err}"));
+                break;
+            }
+            match tokio::time::timeout(self.options.timeout, &mut reply_rx).await {
+                Ok(Ok(response)) => return Ok(response),
+                Ok(Err(_)) => break,
+                Err(_) => continue,
+            }
+        }
+
+        self.pending.lock().expect("rcon pending lock").remove(&sequence);
+        self.connected.store(false, Ordering::SeqCst);
+        result
+    }
+
+    async fn handle_packet(&self, payload: &[u8]) {
+        match payload {
+            [PACKET_COMMAND, sequence, body @ ..] => self.complete_command(*sequence, body),
+            [PACKET_MESSAGE, sequence, message @ ..] => {
+                let _ = self
+                    .socket
+                    .send(&encode_packet(&[PACKET_MESSAGE, *sequence]))
+                    .await;
+                // The server resends messages until they are acknowledged.
+                let mut last = self.last_message_sequence.lock().expect("rcon message lock");
+                if *last == Some(*sequence) {
+                    return;
+                }
+                *last = Some(*sequence);
+                let _ = self
+                    .messages
+                    .send(String::from_utf8_lossy(message).into_owned());
+            }
+            _ => {}
+        }
+    }
+
+    fn complete_command(&self, sequence: u8, body: &[u8]) {
+        let mut pending = self.pending.lock().expect("rcon pending lock");
+        let Some(entry) = pending.get_mut(&sequence) else {
+            return;
+        };
+        let response = match body {
+            [0x00, count, index, data @ ..] if *count > 0 && index < count => {
+                if entry.parts.len() != usize::from(*count) {
+                    entry.parts = vec![None; usize::from(*count)];
+                }
+                entry.parts[usize::from(*index)] = Some(data.to_vec());
+                if entry.parts.iter().any(Option::is_none) {
+                    return;
+                }
+                entry.parts.iter().flatten().flatten().copied().collect()
+            }
+            _ => body.to_vec(),
+        };
+        if let Some(entry) = pending.remove(&sequence) {
+            let _ = entry
+                .reply
+                .send(String::from_utf8_lossy(&response).into_owned());
+        }
+    }
+
+    fn abort_tasks(&self) {
+        for task in self.tasks.lock().expect("rcon task lock").drain(..) {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        self.abort_tasks();
+    }
+}
+
+async fn login(socket: &UdpSocket, password: &str, options: &RconOptions) -> Result<(), String> {
+    let mut payload = vec![PACKET_LOGIN];
+    payload.extend_from_slice(password.as_bytes());
+    let packet = encode_packet(&payload);
+    let mut buffer = vec![0u8; MAX_PACKET_SIZE];
+
+    for _ in 0..options.attempts.max(1) {
+        socket
+            .send(&packet)
+            .await
+            .map_err(|err| format!("RCON send failed: {err}"))?;
+        let deadline = tokio::time::Instant::now() + options.timeout;
+        loop {
+            let len = match tokio::time::timeout_at(deadline, socket.recv(&mut buffer)).await {
+                Err(_) => break,
+                Ok(Err(err)) => return Err(format!("RCON receive failed: {err}")),
+                Ok(Ok(len)) => len,
+            };
+            let Ok(payload) = decode_packet(&buffer[..len]) else {
+                continue;
+            };
+            if let [PACKET_LOGIN, result, ..] = payload {
+                return if *result == 0x01 {
+                    Ok(())
+                } else {
+                    Err("RCON login rejected: wrong password".to_string())
+                };
+            }
+        }
+    }
+    Err("RCON login timed out".to_string())
+}
+
+async fn receive_loop(socket: Arc<UdpSocket>, shared: Weak<Shared>) {
+    let mut buffer = vec![0u8; MAX_PACKET_SIZE];
+    loop {
+        let result = socket.recv(&mut buffer).await;
+        let Some(shared) = shared.upgrade() else {
+            return;
+        };
+        match result {
+            Ok(len) => {
+                if let Ok(payload) = decode_packet(&buffer[..len]) {
+                    shared.handle_packet(payload).await;
+                }
+            }
+            Err(err) => {
+                tracing::debug!("RCON receive failed: {err}");
+                shared.connected.store(false, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+async fn keepalive_loop(shared: Weak<Shared>, interval: Duration) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let Some(shared) = shared.upgrade() else {
+            return;
+        };
+        if let Err(err) = shared.send_command("").await {
+            tracing::warn!("RCON keepalive failed: {err}");
+            return;
+        }
+    }
+}
+
+fn encode_packet(payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(payload.len() + 1);
+    body.push(0xFF);
+    body.extend_from_slice(payload);
+    let mut packet = Vec::with_capacity(body.len() + 6);
+    packet.extend_from_slice(b"BE");
+    packet.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+    packet.extend_from_slice(&body);
+    packet
+}
+
+fn decode_packet(packet: &[u8]) -> Result<&[u8], String> {
+    if packet.len() < 8 || &packet[..2] != b"BE" || packet[6] != 0xFF {
+        return Err("malformed RCON packet".to_string());
+    }
+    let checksum = u32::from_le_bytes([packet[2], packet[3], packet[4], packet[5]]);
+    if crc32fast::hash(&packet[6..]) != checksum {
+        return Err("RCON packet checksum mismatch".to_string());
+    }
+    Ok(&packet[7..])
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
+pub struct RconPlayer {
+    pub id: u32,
+    pub name: String,
+    pub address: String,
+    pub ping: Option<u32>,
+    pub guid: String,
+    pub verified: bool,
+    pub in_lobby: bool,
+}
+
+/// Parses the table returned by the BattlEye `players` command.
+pub fn parse_players(response: &str) -> Vec<RconPlayer> {
+    let mut players = Vec::new();
+    for line in response.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(id), Some(address), Some(ping), Some(guid)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let Ok(id) = id.parse::<u32>() else {
+            continue;
+        };
+        let name = fields.collect::<Vec<_>>().join(" ");
+        let (name, in_lobby) = match name.strip_suffix("(Lobby)") {
+            Some(name) => (name.trim_end().to_string(), true),
+            None => (name, false),
+        };
+        let (guid, verified) = match guid.split_once('(') {
+            Some((guid, status)) => (guid, status.trim_end_matches(')') == "OK"),
+            None => (guid, false),
+        };
+        players.push(RconPlayer {
+            id,
+            name,
+            address: address.to_string(),
+            ping: ping.parse().ok(),
+            guid: guid.to_string(),
+            verified,
+            in_lobby,
+        });
+    }
+    players
+}
+
+fn sanitize_argument(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub fn kick_command(player_id: u32, reason: &str) -> String {
+    format!("kick {player_id} {}", sanitize_argument(reason)).trim_end().to_string()
+}
+
+pub fn ban_command(player_id: u32, minutes: u64, reason: &str) -> String {
+    format!("ban {player_id} {minutes} {}", sanitize_argument(reason)).trim_end().to_string()
+}
+
+pub fn add_ban_command(guid: &str, minutes: u64, reason: &str) -> String {
+    format!("addBan {} {minutes} {}", sanitize_argument(guid), sanitize_argument(reason))
+        .trim_end()
+        .to_string()
+}
+
+pub fn say_command(message: &str) -> String {
+    format!("say -1 {}", sanitize_argument(message))
+}
+
+/// One slot per profile, so a slow connect only holds up callers for the same profile.
+type SessionSlot = Arc<tokio::sync::Mutex<Option<RconSession>>>;
+
+#[derive(Clone)]
+pub struct RconManager {
+    sessions: Arc<std::sync::Mutex<HashMap<String, SessionSlot>>>,
+    options: RconOptions,
+}
+
+struct RconSession {
+    endpoint: RconEndpoint,
+    client: RconClient,
+}
+
+impl Default for RconManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RconManager {
+    pub fn new() -> Self {
+        Self::with_options(RconOptions::default())
+    }
+
+    pub fn with_options(options: RconOptions) -> Self {
+        Self {
+            sessions: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            options,
+        }
+    }
+
+    fn slot(&self, profile_id: &str) -> SessionSlot {
+        let mut sessions = self.sessions.lock().expect("rcon sessions lock");
+        Arc::clone(sessions.entry(profile_id.to_string()).or_default())
+    }
+
+    pub async fn client(&self, profile_id: &str, endpoint: &RconEndpoint) -> Result<RconClient, String> {
+        let slot = self.slot(profile_id);
+        let mut session = slot.lock().await;
+        if let Some(current) = session.as_ref() {
+            if current.endpoint == *endpoint && current.client.is_connected() {
+                return Ok(current.client.clone());
+            }
+        }
+        if let Some(stale) = session.take() {
+            stale.client.close();
+        }
+
+        let client = RconClient::connect(endpoint, self.options.clone()).await?;
+        *session = Some(RconSession {
+            endpoint: endpoint.clone(),
+            client: client.clone(),
+        });
+        Ok(client)
+    }
+
+    pub async fn command(
+        &self,
+        profile_id: &str,
+        endpoint: &RconEndpoint,
+        command: &str,
+    ) -> Result<String, String> {
+        self.client(profile_id, endpoint).await?.command(command).await
+    }
+
+    pub async fn disconnect(&self, profile_id: &str) {
+        let slot = self
+            .sessions
+            .lock()
+            .expect("rcon sessions lock")
+            .get(profile_id)
+            .cloned();
+        let Some(slot) = slot else {
+            return;
+        };
+        let session = slot.lock().await.take();
+        if let Some(session) = session {
+            session.client.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+
+    #[derive(Default)]
+    struct FakeState {
+        client: Option<SocketAddr>,
+        keepalives: usize,
+        acknowledged: Vec<u8>,
+    }
+
+    struct FakeServer {
+        socket: Arc<UdpSocket>,
+        state: Arc<Mutex<FakeState>>,
+        endpoint: RconEndpoint,
+    }
+
+    impl FakeServer {
+        async fn start(password: &str) -> Self {
+            let socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await.expect("bind fake server"));
+            let port = socket.local_addr().expect("local addr").port();
+            let state = Arc::new(Mutex::new(FakeState::default()));
+            tokio::spawn(run_fake_server(
+                Arc::clone(&socket),
+                password.to_string(),
+                Arc::clone(&state),
+            ));
+            Self {
+                socket,
+                state,
+                endpoint: RconEndpoint {
+                    host: "127.0.0.1".to_string(),
+                    port,
+                    password: password.to_string(),
+                },
+            }
+        }
+
+        async fn send_message(&self, sequence: u8, message: &str) {
+            let client = self.state.lock().unwrap().client.expect("client logged in");
+            let mut payload = vec![PACKET_MESSAGE, sequence];
+            payload.extend_from_slice(message.as_bytes());
+            self.socket
+                .send_to(&encode_packet(&payload), client)
+                .await
+                .expect("send message");
+        }
+    }
+
+    async fn run_fake_server(socket: Arc<UdpSocket>, password: String, state: Arc<Mutex<FakeState>>) {
+        let mut buffer = vec![0u8; MAX_PACKET_SIZE];
+        loop {
+            let Ok((len, peer)) = socket.recv_from(&mut buffer).await else {
+                return;
+            };
+            let payload = decode_packet(&buffer[..len]).expect("valid packet").to_vec();
+            let replies = match payload.as_slice() {
+                [PACKET_LOGIN, given @ ..] => {
+                    let accepted = given == password.as_bytes();
+                    if accepted {
+                        state.lock().unwrap().client = Some(peer);
+                    }
+                    vec![vec![PACKET_LOGIN, u8::from(accepted)]]
+                }
+                [PACKET_COMMAND, sequence] => {
+                    state.lock().unwrap().keepalives += 1;
+                    vec![vec![PACKET_COMMAND, *sequence]]
+                }
+                [PACKET_COMMAND, _, command @ ..] if command == b"silent" => Vec::new(),
+                [PACKET_COMMAND, sequence, command @ ..] if command == b"players" => {
+                    let parts = ["Players on server:\n", "0 Alice\n", "1 Bob"];
+                    [2u8, 0, 1]
+                        .iter()
+                        .map(|index| {
+                            let mut reply = vec![PACKET_COMMAND, *sequence, 0x00, 3, *index];
+                            reply.extend_from_slice(parts[usize::from(*index)].as_bytes());
+                            reply
+                        })
+                        .collect()
+                }
+                [PACKET_COMMAND, sequence, command @ ..] => {
+                    let mut reply = vec![PACKET_COMMAND, *sequence];
+                    reply.extend_from_slice(b"echo: ");
+                    reply.extend_from_slice(command);
+                    vec![reply]
+                }
+                [PACKET_MESSAGE, sequence] => {
+                    state.lock().unwrap().acknowledged.push(*sequence);
+                    Vec::new()
+                }
+                _ => Vec::new(),
+            };
+            for reply in replies {
+                socket
+                    .send_to(&encode_packet(&reply), peer)
+                    .await
+                    .expect("send reply");
+            }
+        }
+    }
+
+    fn test_options() -> RconOptions {
+        RconOptions {
+            timeout: Duration::from_millis(100),
+            attempts: 2,
+            keepalive_interval: Duration::from_secs(3600),
+        }
+    }
+
+    #[test]
+    fn packets_roundtrip_and_reject_bad_checksums() {
+        let packet = encode_packet(&[PACKET_COMMAND, 7, b'#']);
+        assert_eq!(&packet[..2], b"BE");
+        assert_eq!(decode_packet(&packet).unwrap(), &[PACKET_COMMAND, 7, b'#']);
+
+        let mut corrupted = packet.clone();
+        *corrupted.last_mut().unwrap() = b'!';
+        assert!(decode_packet(&corrupted).is_err());
+    }
+
+    #[test]
+    fn endpoint_is_read_from_server_config() {
+        let config = serde_json::json!({
+            "rcon": { "address": "0.0.0.0", "port": 19999, "password": "secret" }
+        });
+        let endpoint = RconEndpoint::from_server_config(&config).unwrap();
+        assert_eq!(endpoint.socket_address(), "127.0.0.1:19999");
+        assert_eq!(endpoint.password, "secret");
+
+        let config = serde_json::json!({ "rcon": { "port": 19999, "password": "" } });
+        assert!(RconEndpoint::from_server_config(&config).is_err());
+    }
+
+    #[test]
+    fn players_table_is_parsed() {
+        let response = "Players on server:\n\
+            [#] [IP Address]:[Port] [Ping] [GUID] [Name]\n\
+            --------------------------------------------------\n\
+            0   10.0.0.5:2304     47   0123456789abcdef0123456789abcdef(OK) Alice\n\
+            1   10.0.0.6:2304     -1   fedcba9876543210fedcba9876543210(?) Bob the Builder (Lobby)\n\
+            (2 players in total)";
+        let players = parse_players(response);
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[0].id, 0);
+        assert_eq!(players[0].name, "Alice");
+        assert_eq!(players[0].ping, Some(47));
+        assert_eq!(players[0].guid, "0123456789abcdef0123456789abcdef");
+        assert!(players[0].verified);
+        assert_eq!(players[1].name, "Bob the Builder");
+        assert!(players[1].in_lobby);
+        assert!(!players[1].verified);
+        assert_eq!(players[1].ping, None);
+    }
+
+    #[test]
+    fn commands_keep_arguments_on_one_line() {
+        assert_eq!(kick_command(3, "team\nkilling"), "kick 3 team killing");
+        assert_eq!(kick_command(3, ""), "kick 3");
+        assert_eq!(ban_command(3, 60, "cheating"), "ban 3 60 cheating");
+        assert_eq!(add_ban_command("abc", 0, ""), "addBan abc 0");
+        assert_eq!(say_command("Restart in 5 minutes"), "say -1 Restart in 5 minutes");
+    }
+
+    #[tokio::test]
+    async fn login_rejects_wrong_password() {
+        let server = FakeServer::start("secret").await;
+        let endpoint = RconEndpoint {
+            password: "wrong".to_string(),
+            ..server.endpoint.clone()
+        };
+        let err = RconClient::connect(&endpoint, test_options())
+            .await
+            .err()
+            .expect("login fails");
+        assert!(err.contains("wrong password"));
+    }
+
+    #[tokio::test]
+    async fn command_returns_response() {
+        let server = FakeServer::start("secret").await;
+        let client = RconClient::connect(&server.endpoint, test_options())
+            .await
+            .expect("connect");
+        assert_eq!(client.command("#status").await.unwrap(), "echo: #status");
+        assert_eq!(client.command("say -1 hi").await.unwrap(), "echo: say -1 hi");
+    }
+
+    #[tokio::test]
+    async fn multi_packet_responses_are_reassembled_in_order() {
+        let server = FakeServer::start("secret").await;
+        let client = RconClient::connect(&server.endpoint, test_options())
+            .await
+            .expect("connect");
+        assert_eq!(
+            client.command("players").await.unwrap(),
+            "Players on server:\n0 Alice\n1 Bob"
+        );
+    }
+
+    #[tokio::test]
+    async fn server_messages_are_acknowledged_and_broadcast() {
+        let server = FakeServer::start("secret").await;
+        let client = RconClient::connect(&server.endpoint, test_options())
+            .await
+            .expect("connect");
+        let mut messages = client.subscribe();
+
+        server.send_message(4, "Player #0 Alice connected").await;
+        server.send_message(4, "Player #0 Alice connected").await;
+        server.send_message(5, "Player #0 Alice disconnected").await;
+
+        let first = tokio::time::timeout(Duration::from_secs(1), messages.recv())
+            .await
+            .expect("message")
+            .unwrap();
+        let second = tokio::time::timeout(Duration::from_secs(1), messages.recv())
+            .await
+            .expect("message")
+            .unwrap();
+        assert_eq!(first, "Player #0 Alice connected");
+        assert_eq!(second, "Player #0 Alice disconnected");
+
+        for _ in 0..50 {
+            if server.state.lock().unwrap().acknowledged.len() == 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(server.state.lock().unwrap().acknowledged, vec![4, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn keepalive_packets_are_sent() {
+        let server = FakeServer::start("secret").await;
+        let options = RconOptions {
+            keepalive_interval: Duration::from_millis(30),
+            ..test_options()
+        };
+        let client = RconClient::connect(&server.endpoint, options).await.expect("connect");
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(server.state.lock().unwrap().keepalives >= 2);
+        assert!(client.is_connected());
+    }
+
+    #[tokio::test]
+    async fn unanswered_command_marks_connection_lost() {
+        let server = FakeServer::start("secret").await;
+        let client = RconClient::connect(&server.endpoint, test_options())
+            .await
+            .expect("connect");
+        assert!(client.command("silent").await.is_err());
+        assert!(!client.is_connected());
+        assert!(client.command("#status").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn manager_reconnects_lost_sessions() {
+        let server = FakeServer::start("secret").await;
+        let manager = RconManager::with_options(test_options());
+        assert!(manager.command("pve", &server.endpoint, "silent").await.is_err());
+        assert_eq!(
+            manager.command("pve", &server.endpoint, "#status").await.unwrap(),
+            "echo: #status"
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_server_does_not_block_other_profiles() {
+        let server = FakeServer::start("secret").await;
+        let silent = UdpSocket::bind("127.0.0.1:0").await.expect("bind silent server");
+        let unreachable = RconEndpoint {
+            port: silent.local_addr().expect("local addr").port(),
+            ..server.endpoint.clone()
+        };
+        let manager = RconManager::with_options(RconOptions {
+            timeout: Duration::from_secs(2),
+            ..test_options()
+        });
+
+        let stalled = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.command("stalled", &unreachable, "#status").await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let response = tokio::time::timeout(
+            Duration::from_secs(1),
+            manager.command("pve", &server.endpoint, "#status"),
+        )
+        .await
+        .expect("other profile waited for the stalled connect");
+        assert_eq!(response.unwrap(), "echo: #status");
+        assert!(stalled.await.unwrap().is_err());
+    }
+}
@@ -0,0 +1,801 @@
+use crate::forms::{
+    AcceptUpgradesRequest, ModCreateRequest, ModUpdateRequest, PackageRequest, ProfileRequest,
+};
+use crate::routes::profiles::{
+    accept_profile_upgrades, new_profile_id, profile_version_statuses, resolve_and_update_profile,
+    write_profile_config,
+};
+use crate::routes::AppState;
+use crate::security::audit::{redact, Actor};
+use crate::security::users::{AuthUser, Role};
+use crate::services::{
+    generate_config_for_profile, known_mod_metadata, normalize_optional_path, parse_mod_id_input,
+    pin_resolved_versions,
+};
+use axum::{
+    extract::{rejection::JsonRejection, Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use backend::models::{ModEntry, ModPackage, ModVersionStatus, ServerProfile};
+use backend::storage;
+use backend::workshop::WorkshopResolveResult;
+use serde_json::{json, Value};
+
+/// JSON API for provisioning scripts, nested under `/api/v1`.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/profiles", get(list_profiles).post(create_profile))
+        .route(
+            "/profiles/:profile_id",
+            get(get_profile).put(update_profile).delete(delete_profile),
+        )
+        .route("/profiles/:profile_id/resolve", post(resolve_profile))
+        .route("/profiles/:profile_id/config", get(preview_config).post(write_config))
+        .route("/profiles/:profile_id/versions", get(list_versions))
+        .route("/profiles/:profile_id/versions/accept", post(accept_versions))
+        .route("/mods", get(list_mods).post(create_mod))
+        .route("/mods/:mod_id", get(get_mod).put(update_mod).delete(delete_mod))
+        .route("/packages", get(list_packages).post(create_package))
+        .route(
+            "/packages/:package_id",
+            get(get_package).put(update_package).delete(delete_package),
+        )
+        .fallback(unknown_route)
+}
+
+/// Every error is returned as `{"error": {"status", "code", "message"}}`.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, message)
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, message)
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+
+    /// `not_found`, `bad_request`, ... derived from the status line.
+    fn code(&self) -> String {
+        self.status
+            .canonical_reason()
+            .unwrap_or("error")
+            .to_ascii_lowercase()
+            .replace([' ', '-'], "_")
+    }
+
+    fn body(&self) -> ApiErrorBody {
+        ApiErrorBody {
+            error: ApiErrorDetail {
+                status: self.status.as_u16(),
+                code: self.code(),
+                message: self.message.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ApiErrorBody {
+    pub error: ApiErrorDetail,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ApiErrorDetail {
+    pub status: u16,
+    /// The status reason in snake case, e.g. `not_found` or `conflict`.
+    pub code: String,
+    pub message: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self.body())).into_response()
+    }
+}
+
+impl From<(StatusCode, String)> for ApiError {
+    fn from((status, message): (StatusCode, String)) -> Self {
+        Self::new(status, message)
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+async fn unknown_route() -> ApiError {
+    ApiError::not_found("no such API endpoint")
+}
+
+fn created<T: serde::Serialize>(location: String, body: T) -> Response {
+    (StatusCode::CREATED, [(header::LOCATION, location)], Json(body)).into_response()
+}
+
+/// Profile IDs end up in file names, so anything but a plain identifier cannot exist.
+fn is_valid_profile_id(profile_id: &str) -> bool {
+    !profile_id.is_empty()
+        && profile_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+async fn find_profile(profile_id: &str) -> ApiResult<ServerProfile> {
+    let not_found = || ApiError::not_found(format!("profile {profile_id} not found"));
+    if !is_valid_profile_id(profile_id) {
+        return Err(not_found());
+    }
+    if tokio::fs::metadata(storage::profile_path(profile_id)).await.is_err() {
+        return Err(not_found());
+    }
+    storage::load_profile(profile_id).await.map_err(ApiError::internal)
+}
+
+/// Checks the request and copies its fields onto `profile`, leaving resolve
+/// results and the generated config path untouched.
+pub fn apply_profile_request(
+    profile: &mut ServerProfile,
+    request: ProfileRequest,
+    packages: &[ModPackage],
+) -> Result<(), String> {
+    let display_name = request.display_name.trim();
+    let workshop_url = request.workshop_url.trim();
+    if display_name.is_empty() {
+        return Err("display_name must not be empty".to_string());
+    }
+    if workshop_url.is_empty() {
+        return Err("workshop_url must not be empty".to_string());
+    }
+    let optional_mod_ids = request
+        .optional_mod_ids
+        .iter()
+        .map(|value| {
+            parse_mod_id_input(value).ok_or_else(|| format!("invalid mod ID {value}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(unknown) = request
+        .optional_package_ids
+        .iter()
+        .find(|id| !packages.iter().any(|package| &package.package_id == *id))
+    {
+        return Err(format!("unknown package {unknown}"));
+    }
+    let mod_version_pins = request
+        .mod_version_pins
+        .iter()
+        .map(|(mod_id, version)| {
+            let mod_id = parse_mod_id_input(mod_id).ok_or_else(|| format!("invalid mod ID {mod_id}"))?;
+            let version = version.trim();
+            if version.is_empty() {
+                return Err(format!("version pin for {mod_id} must not be empty"));
+            }
+            Ok((mod_id, version.to_string()))
+        })
+        .collect::<Result<_, String>>()?;
+    let overrides = match request.server_json_overrides {
+        None | Some(Value::Null) => json!({}),
+        Some(value @ Value::Object(_)) => value,
+        Some(_) => return Err("server_json_overrides must be an object".to_string()),
+    };
+    let optional_path = |value: Option<String>| value.as_deref().and_then(normalize_optional_path);
+
+    profile.display_name = display_name.to_string();
+    profile.workshop_url = workshop_url.to_string();
+    profile.selected_scenario_id_path = optional_path(request.selected_scenario_id_path);
+    profile.optional_mod_ids = optional_mod_ids;
+    profile.optional_package_ids = request.optional_package_ids;
+    profile.load_session_save = request.load_session_save;
+    profile.restart_policy = request.restart_policy;
+    profile.update_before_start = request.update_before_start;
+    profile.steamcmd_dir_override = optional_path(request.steamcmd_dir_override);
+    profile.reforger_server_exe_override = optional_path(request.reforger_server_exe_override);
+    profile.reforger_server_work_dir_override =
+        optional_path(request.reforger_server_work_dir_override);
+    profile.profile_dir_base_override = optional_path(request.profile_dir_base_override);
+    profile.steam_branch_override = optional_path(request.steam_branch_override);
+    profile.server_json_overrides = overrides;
+    profile.server_json_override_enabled = request.server_json_override_enabled;
+    profile.pin_mod_versions = request.pin_mod_versions;
+    profile.mod_version_pins = mod_version_pins;
+    pin_resolved_versions(profile);
+    Ok(())
+}
+
+fn empty_profile(profile_id: String) -> ServerProfile {
+    ServerProfile {
+        profile_id,
+        display_name: String::new(),
+        workshop_url: String::new(),
+        root_mod_id: None,
+        selected_scenario_id_path: None,
+        scenarios: Vec::new(),
+        dependency_mod_ids: Vec::new(),
+        optional_mod_ids: Vec::new(),
+        optional_package_ids: Vec::new(),
+        load_session_save: false,
+        restart_policy: Default::default(),
+        update_before_start: false,
+        steamcmd_dir_override: None,
+        reforger_server_exe_override: None,
+        reforger_server_work_dir_override: None,
+        profile_dir_base_override: None,
+        steam_branch_override: None,
+        server_json_overrides: json!({}),
+        server_json_override_enabled: Default::default(),
+        generated_config_path: None,
+        last_resolved_at: None,
+        last_resolve_hash: None,
+        mod_metadata: Default::default(),
+        pin_mod_versions: false,
+        mod_version_pins: Default::default(),
+    }
+}
+
+async fn list_profiles(user: Option<Extension<AuthUser>>) -> ApiResult<Json<Vec<ServerProfile>>> {
+    let profiles = storage::list_profiles().await.map_err(ApiError::internal)?;
+    Ok(Json(
+        profiles
+            .into_iter()
+            .map(|profile| visible_profile(profile, user.as_deref()))
+            .collect(),
+    ))
+}
+
+async fn get_profile(
+    user: Option<Extension<AuthUser>>,
+    Path(profile_id): Path<String>,
+) -> ApiResult<Json<ServerProfile>> {
+    let profile = find_profile(&profile_id).await?;
+    Ok(Json(visible_profile(profile, user.as_deref())))
+}
+
+/// Overrides may set the RCON and admin passwords, which only admins get to see.
+fn visible_profile(mut profile: ServerProfile, user: Option<&AuthUser>) -> ServerProfile {
+    if user.map(|user| user.role) != Some(Role::Admin) {
+        profile.server_json_overrides = redact(profile.server_json_overrides);
+    }
+    profile
+}
+
+async fn create_profile(
+    State(state): State<AppState>,
+    actor: Actor,
+    payload: Result<Json<ProfileRequest>, JsonRejection>,
+) -> ApiResult<Response> {
+    let Json(request) = payload?;
+    let packages = storage::load_packages().await.map_err(ApiError::internal)?;
+    let mut profile = empty_profile(new_profile_id());
+    apply_profile_request(&mut profile, request, &packages).map_err(ApiError::bad_request)?;
+
+    storage::save_profile(&profile)
+        .await
+        .map_err(ApiError::internal)?;
+    state
+        .audit
+        .record_object(&actor, "profile.create", &profile.profile_id, None, Some(&profile))
+        .await;
+    Ok(created(
+        format!("/api/v1/profiles/{}", profile.profile_id),
+        profile,
+    ))
+}
+
+async fn update_profile(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(profile_id): Path<String>,
+    payload: Result<Json<ProfileRequest>, JsonRejection>,
+) -> ApiResult<Json<ServerProfile>> {
+    let Json(request) = payload?;
+    let mut profile = find_profile(&profile_id).await?;
+    let packages = storage::load_packages().await.map_err(ApiError::internal)?;
+    let before = profile.clone();
+    apply_profile_request(&mut profile, request, &packages).map_err(ApiError::bad_request)?;
+
+    storage::save_profile(&profile)
+        .await
+        .map_err(ApiError::internal)?;
+    state
+        .audit
+        .record_change(&actor, "profile.update", &profile_id, &before, &profile)
+        .await;
+    Ok(Json(profile))
+}
+
+async fn delete_profile(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(profile_id): Path<String>,
+) -> ApiResult<StatusCode> {
+    let profile = find_profile(&profile_id).await?;
+    state
+        .run_manager
+        .remove(&profile_id)
+        .await
+        .map_err(|message| ApiError::new(StatusCode::CONFLICT, message))?;
+
+    storage::delete_profile(&profile_id)
+        .await
+        .map_err(ApiError::internal)?;
+    state
+        .audit
+        .record_object(&actor, "profile.delete", &profile_id, Some(&profile), None)
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(serde::Serialize, schemars::JsonSchema)]
+pub struct ResolveResponse {
+    pub profile: ServerProfile,
+    pub resolve: WorkshopResolveResult,
+}
+
+async fn resolve_profile(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(profile_id): Path<String>,
+) -> ApiResult<Json<ResolveResponse>> {
+    let mut profile = find_profile(&profile_id).await?;
+    let before = profile.clone();
+    let resolve = resolve_and_update_profile(&state, &mut profile)
+        .await
+        .map_err(|message| ApiError::new(StatusCode::BAD_GATEWAY, message))?;
+    state
+        .audit
+        .record_change(&actor, "profile.resolve", &profile_id, &before, &profile)
+        .await;
+    Ok(Json(ResolveResponse { profile, resolve }))
+}
+
+async fn preview_config(
+    State(state): State<AppState>,
+    Path(profile_id): Path<String>,
+) -> ApiResult<Json<Value>> {
+    let profile = find_profile(&profile_id).await?;
+    let settings = storage::load_settings(&state.settings_path)
+        .await
+        .map_err(ApiError::internal)?;
+    let packages = storage::load_packages().await.map_err(ApiError::internal)?;
+    let mods = storage::load_mods().await.map_err(ApiError::internal)?;
+    generate_config_for_profile(&profile, &settings, &packages, &mods)
+        .map(Json)
+        .map_err(|message| ApiError::new(StatusCode::CONFLICT, message))
+}
+
+#[derive(serde::Serialize, schemars::JsonSchema)]
+pub struct ConfigWriteResponse {
+    pub path: String,
+    pub config: Value,
+    /// Non-fatal resolve errors, e.g. dependencies that could not be fetched.
+    pub warnings: Vec<String>,
+}
+
+async fn write_config(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(profile_id): Path<String>,
+) -> ApiResult<Json<ConfigWriteResponse>> {
+    let mut profile = find_profile(&profile_id).await?;
+    let written = write_profile_config(&state, &mut profile).await?;
+    state
+        .audit
+        .record(
+            &actor,
+            "profile.config_write",
+            &profile_id,
+            None,
+            Some(json!({ "path": written.path.to_string_lossy() })),
+        )
+        .await;
+    Ok(Json(ConfigWriteResponse {
+        path: written.path.to_string_lossy().to_string(),
+        config: written.config,
+        warnings: written.resolve_result.errors,
+    }))
+}
+
+async fn list_versions(Path(profile_id): Path<String>) -> ApiResult<Json<Vec<ModVersionStatus>>> {
+    let profile = find_profile(&profile_id).await?;
+    profile_version_statuses(&profile)
+        .await
+        .map(Json)
+        .map_err(ApiError::internal)
+}
+
+#[derive(serde::Serialize, schemars::JsonSchema)]
+pub struct AcceptUpgradesResponse {
+    /// Mods whose pin was moved to the latest resolved version.
+    pub accepted: Vec<String>,
+    pub path: String,
+    /// Non-fatal resolve errors from rewriting the config.
+    pub warnings: Vec<String>,
+}
+
+async fn accept_versions(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(profile_id): Path<String>,
+    payload: Result<Json<AcceptUpgradesRequest>, JsonRejection>,
+) -> ApiResult<Json<AcceptUpgradesResponse>> {
+    let Json(request) = payload?;
+    let mut profile = find_profile(&profile_id).await?;
+    let (accepted, written) =
+        accept_profile_upgrades(&state, &actor, &mut profile, &request.mod_ids).await?;
+    Ok(Json(AcceptUpgradesResponse {
+        accepted,
+        path: written.path.to_string_lossy().to_string(),
+        warnings: written.resolve_result.errors,
+    }))
+}
+
+async fn list_mods() -> ApiResult<Json<Vec<ModEntry>>> {
+    storage::load_mods().await.map(Json).map_err(ApiError::internal)
+}
+
+async fn get_mod(Path(mod_id): Path<String>) -> ApiResult<Json<ModEntry>> {
+    let mods = storage::load_mods().await.map_err(ApiError::internal)?;
+    mods.into_iter()
+        .find(|entry| entry.mod_id == mod_id)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("mod {mod_id} not found")))
+}
+
+async fn create_mod(
+    State(state): State<AppState>,
+    actor: Actor,
+    payload: Result<Json<ModCreateRequest>, JsonRejection>,
+) -> ApiResult<Response> {
+    let Json(request) = payload?;
+    let mod_id = parse_mod_id_input(&request.mod_id)
+        .ok_or_else(|| ApiError::bad_request(format!("invalid mod ID {}", request.mod_id)))?;
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::bad_request("name must not be empty"));
+    }
+    let mut mods = storage::load_mods().await.map_err(ApiError::internal)?;
+    if mods.iter().any(|entry| entry.mod_id == mod_id) {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            format!("mod {mod_id} already exists"),
+        ));
+    }
+
+    let profiles = storage::list_profiles().await.unwrap_or_default();
+    let entry = ModEntry {
+        metadata: known_mod_metadata(&profiles, &mod_id),
+        mod_id,
+        name: name.to_string(),
+    };
+    mods.push(entry.clone());
+    storage::save_mods(&mods).await.map_err(ApiError::internal)?;
+    state
+        .audit
+        .record_object(&actor, "mod.create", &entry.mod_id, None, Some(&entry))
+        .await;
+    Ok(created(format!("/api/v1/mods/{}", entry.mod_id), entry))
+}
+
+async fn update_mod(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(mod_id): Path<String>,
+    payload: Result<Json<ModUpdateRequest>, JsonRejection>,
+) -> ApiResult<Json<ModEntry>> {
+    let Json(request) = payload?;
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::bad_request("name must not be empty"));
+    }
+    let mut mods = storage::load_mods().await.map_err(ApiError::internal)?;
+    let Some(entry) = mods.iter_mut().find(|entry| entry.mod_id == mod_id) else {
+        return Err(ApiError::not_found(format!("mod {mod_id} not found")));
+    };
+    let before = entry.clone();
+    entry.name = name.to_string();
+    let after = entry.clone();
+
+    storage::save_mods(&mods).await.map_err(ApiError::internal)?;
+    state
+        .audit
+        .record_change(&actor, "mod.update", &mod_id, &before, &after)
+        .await;
+    Ok(Json(after))
+}
+
+async fn delete_mod(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(mod_id): Path<String>,
+) -> ApiResult<StatusCode> {
+    let mut mods = storage::load_mods().await.map_err(ApiError::internal)?;
+    let packages = storage::load_packages().await.map_err(ApiError::internal)?;
+    let profiles = storage::list_profiles().await.map_err(ApiError::internal)?;
+    let Some(index) = mods.iter().position(|entry| entry.mod_id == mod_id) else {
+        return Err(ApiError::not_found(format!("mod {mod_id} not found")));
+    };
+    if let Some(usage) = mod_usage(&mod_id, &packages, &profiles) {
+        return Err(ApiError::new(StatusCode::CONFLICT, usage));
+    }
+
+    let entry = mods.remove(index);
+    storage::save_mods(&mods).await.map_err(ApiError::internal)?;
+    state
+        .audit
+        .record_object(&actor, "mod.delete", &mod_id, Some(&entry), None)
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Describes where a mod is still used, if anywhere, so it is not deleted.
+fn mod_usage(mod_id: &str, packages: &[ModPackage], profiles: &[ServerProfile]) -> Option<String> {
+    if let Some(package) = packages
+        .iter()
+        .find(|package| package.mod_ids.iter().any(|id| id == mod_id))
+    {
+        return Some(format!("mod {mod_id} is used in package {}", package.package_id));
+    }
+    profiles
+        .iter()
+        .find(|profile| profile.optional_mod_ids.iter().any(|id| id == mod_id))
+        .map(|profile| format!("mod {mod_id} is used by profile {}", profile.profile_id))
+}
+
+/// Validates a package request against the known mods.
+pub fn check_package_request(request: &PackageRequest, mods: &[ModEntry]) -> Result<String, String> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err("name must not be empty".to_string());
+    }
+    if let Some(unknown) = request
+        .mod_ids
+        .iter()
+        .find(|id| !mods.iter().any(|entry| &entry.mod_id == *id))
+    {
+        return Err(format!("unknown mod {unknown}"));
+    }
+    Ok(name.to_string())
+}
+
+async fn list_packages() -> ApiResult<Json<Vec<ModPackage>>> {
+    storage::load_packages()
+        .await
+        .map(Json)
+        .map_err(ApiError::internal)
+}
+
+async fn get_package(Path(package_id): Path<String>) -> ApiResult<Json<ModPackage>> {
+    let packages = storage::load_packages().await.map_err(ApiError::internal)?;
+    packages
+        .into_iter()
+        .find(|package| package.package_id == package_id)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("package {package_id} not found")))
+}
+
+async fn create_package(
+    State(state): State<AppState>,
+    actor: Actor,
+    payload: Result<Json<PackageRequest>, JsonRejection>,
+) -> ApiResult<Response> {
+    let Json(request) = payload?;
+    let mods = storage::load_mods().await.map_err(ApiError::internal)?;
+    let name = check_package_request(&request, &mods).map_err(ApiError::bad_request)?;
+    let mut packages = storage::load_packages().await.map_err(ApiError::internal)?;
+
+    let package = ModPackage {
+        package_id: crate::routes::packages::new_package_id(),
+        name,
+        mod_ids: request.mod_ids,
+    };
+    packages.push(package.clone());
+    storage::save_packages(&packages)
+        .await
+        .map_err(ApiError::internal)?;
+    state
+        .audit
+        .record_object(&actor, "package.create", &package.package_id, None, Some(&package))
+        .await;
+    Ok(created(
+        format!("/api/v1/packages/{}", package.package_id),
+        package,
+    ))
+}
+
+async fn update_package(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(package_id): Path<String>,
+    payload: Result<Json<PackageRequest>, JsonRejection>,
+) -> ApiResult<Json<ModPackage>> {
+    let Json(request) = payload?;
+    let mods = storage::load_mods().await.map_err(ApiError::internal)?;
+    let mut packages = storage::load_packages().await.map_err(ApiError::internal)?;
+    let Some(package) = packages
+        .iter_mut()
+        .find(|package| package.package_id == package_id)
+    else {
+        return Err(ApiError::not_found(format!("package {package_id} not found")));
+    };
+    let name = check_package_request(&request, &mods).map_err(ApiError::bad_request)?;
+    let before = package.clone();
+    package.name = name;
+    package.mod_ids = request.mod_ids;
+    let after = package.clone();
+
+    storage::save_packages(&packages)
+        .await
+        .map_err(ApiError::internal)?;
+    state
+        .audit
+        .record_change(&actor, "package.update", &package_id, &before, &after)
+        .await;
+    Ok(Json(after))
+}
+
+async fn delete_package(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(package_id): Path<String>,
+) -> ApiResult<StatusCode> {
+    let mut packages = storage::load_packages().await.map_err(ApiError::internal)?;
+    let Some(index) = packages
+        .iter()
+        .position(|package| package.package_id == package_id)
+    else {
+        return Err(ApiError::not_found(format!("package {package_id} not found")));
+    };
+
+    let package = packages.remove(index);
+    storage::save_packages(&packages)
+        .await
+        .map_err(ApiError::internal)?;
+    state
+        .audit
+        .record_object(&actor, "package.delete", &package_id, Some(&package), None)
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> ProfileRequest {
+        serde_json::from_value(json!({
+            "display_name": " Coop ",
+            "workshop_url": "https://reforger.armaplatform.com/workshop/59727DAE364DEADB",
+            "optional_mod_ids": ["https://reforger.armaplatform.com/workshop/5965550F24A0C152-Where", "5AAAC70D754245DD"],
+            "optional_package_ids": ["package-1"],
+            "restart_policy": "on_crash",
+            "steamcmd_dir_override": "  ",
+            "server_json_overrides": { "game": { "maxPlayers": 64 } },
+            "mod_version_pins": { "https://reforger.armaplatform.com/workshop/59727DAE364DEADB": " 1.0.1 " },
+            "scenarios": ["ignored"]
+        }))
+        .expect("request")
+    }
+
+    fn packages() -> Vec<ModPackage> {
+        vec![ModPackage {
+            package_id: "package-1".to_string(),
+            name: "Core".to_string(),
+            mod_ids: Vec::new(),
+        }]
+    }
+
+    #[test]
+    fn profile_request_updates_editable_fields_only() {
+        let mut profile = empty_profile("profile-1".to_string());
+        profile.scenarios = vec!["{ECC61978EDCC2B5A}Missions/23_Campaign.conf".to_string()];
+        apply_profile_request(&mut profile, request(), &packages()).expect("apply");
+
+        assert_eq!(profile.display_name, "Coop");
+        assert_eq!(profile.optional_mod_ids, vec!["5965550F24A0C152", "5AAAC70D754245DD"]);
+        assert_eq!(profile.restart_policy, backend::models::RestartPolicy::OnCrash);
+        assert_eq!(profile.steamcmd_dir_override, None);
+        assert_eq!(profile.server_json_overrides["game"]["maxPlayers"], 64);
+        assert_eq!(profile.mod_version_pins["59727DAE364DEADB"], "1.0.1");
+        assert_eq!(profile.scenarios.len(), 1);
+    }
+
+    #[test]
+    fn invalid_profile_requests_are_rejected() {
+        let mut profile = empty_profile("profile-1".to_string());
+        let mut unknown_package = request();
+        unknown_package.optional_package_ids = vec!["package-2".to_string()];
+        assert!(apply_profile_request(&mut profile, unknown_package, &packages()).is_err());
+
+        let mut bad_mod = request();
+        bad_mod.optional_mod_ids = vec!["not-a-mod".to_string()];
+        assert!(apply_profile_request(&mut profile, bad_mod, &packages()).is_err());
+
+        let mut bad_overrides = request();
+        bad_overrides.server_json_overrides = Some(json!([1, 2]));
+        assert!(apply_profile_request(&mut profile, bad_overrides, &packages()).is_err());
+
+        let mut empty_pin = request();
+        empty_pin.mod_version_pins.insert("5AAAC70D754245DD".to_string(), " ".to_string());
+        assert!(apply_profile_request(&mut profile, empty_pin, &packages()).is_err());
+        assert_eq!(profile.display_name, "");
+
+        assert!(!is_valid_profile_id("../settings"));
+        assert!(is_valid_profile_id("profile-1712345678"));
+    }
+
+    #[test]
+    fn mods_in_packages_or_profiles_are_in_use() {
+        let mut packages = packages();
+        packages[0].mod_ids = vec!["5AAAC70D754245DD".to_string()];
+        let mut profile = empty_profile("profile-1".to_string());
+        profile.optional_mod_ids = vec!["5965550F24A0C152".to_string()];
+        let profiles = vec![profile];
+
+        assert_eq!(
+            mod_usage("5AAAC70D754245DD", &packages, &profiles).as_deref(),
+            Some("mod 5AAAC70D754245DD is used in package package-1")
+        );
+        assert_eq!(
+            mod_usage("5965550F24A0C152", &packages, &profiles).as_deref(),
+            Some("mod 5965550F24A0C152 is used by profile profile-1")
+        );
+        assert_eq!(mod_usage("59727DAE364DEADB", &packages, &profiles), None);
+    }
+
+    #[test]
+    fn profile_secrets_are_hidden_below_admin() {
+        let mut profile = empty_profile("profile-1".to_string());
+        profile.server_json_overrides = json!({
+            "rcon": { "password": "hunter2", "port": 19999 },
+            "game": { "passwordAdmin": "admin", "maxPlayers": 64 }
+        });
+        let user = |role| AuthUser {
+            username: "someone".to_string(),
+            role,
+        };
+
+        let viewer = user(Role::Viewer);
+        for hidden in [
+            visible_profile(profile.clone(), None),
+            visible_profile(profile.clone(), Some(&viewer)),
+        ] {
+            assert_eq!(hidden.server_json_overrides["rcon"]["password"], "[redacted]");
+            assert_eq!(hidden.server_json_overrides["game"]["passwordAdmin"], "[redacted]");
+            assert_eq!(hidden.server_json_overrides["game"]["maxPlayers"], 64);
+        }
+        let shown = visible_profile(profile, Some(&user(Role::Admin)));
+        assert_eq!(shown.server_json_overrides["rcon"]["password"], "hunter2");
+    }
+
+    #[test]
+    fn errors_share_one_body_shape() {
+        let error = ApiError::not_found("profile x not found");
+        assert_eq!(
+            serde_json::to_value(error.body()).expect("body"),
+            json!({ "error": { "status": 404, "code": "not_found", "message": "profile x not found" } })
+        );
+        let error = ApiError::from((StatusCode::BAD_GATEWAY, "workshop unreachable".to_string()));
+        assert_eq!(error.body().error.code, "bad_gateway");
+    }
+}
@@ -0,0 +1,146 @@
+use crate::forms::AuditQuery;
+use crate::routes::AppState;
+use crate::security::audit::{AuditEntry, AuditFilter};
+use crate::views::audit::render_audit_page;
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+
+/// The page shows the most recent matches; exports contain all of them.
+const PAGE_LIMIT: usize = 500;
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+pub async fn audit_page(
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let (filter, error) = match audit_filter(&query) {
+        Ok(filter) => (filter, None),
+        Err(err) => (AuditFilter::default(), Some(err)),
+    };
+    let entries = state
+        .audit
+        .read(&filter, Some(PAGE_LIMIT))
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    Ok(Html(render_audit_page(&entries, &query, PAGE_LIMIT, error.as_deref())))
+}
+
+pub async fn audit_export(
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let filter = audit_filter(&query).map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+    let mut entries = state
+        .audit
+        .read(&filter, None)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    // Exports read chronologically, like the log file itself.
+    entries.reverse();
+
+    let (content_type, extension, body) = match query.format.as_deref().unwrap_or("jsonl") {
+        "jsonl" => ("application/x-ndjson", "jsonl", entries_to_jsonl(&entries)),
+        "csv" => ("text/csv; charset=utf-8", "csv", entries_to_csv(&entries)),
+        other => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("unknown export format {other}; use jsonl or csv"),
+            ))
+        }
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"arssm-audit.{extension}\""),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+fn audit_filter(query: &AuditQuery) -> Result<AuditFilter, String> {
+    let text = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    Ok(AuditFilter {
+        actor: text(&query.actor),
+        action: text(&query.action),
+        target: text(&query.target),
+        since: text(&query.since).map(|value| parse_day(&value)).transpose()?,
+        until: text(&query.until)
+            .map(|value| parse_day(&value).map(|day| day + DAY_SECS))
+            .transpose()?,
+    })
+}
+
+/// Start of a `YYYY-MM-DD` day in UTC, as epoch seconds.
+fn parse_day(value: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid date {value}; expected YYYY-MM-DD");
+    let mut parts = value.splitn(3, '-');
+    let mut next = || parts.next().and_then(|part| part.parse::<i32>().ok());
+    let (year, month, day) = match (next(), next(), next()) {
+        (Some(year), Some(month), Some(day)) => (year, month, day),
+        _ => return Err(invalid()),
+    };
+    let month = u8::try_from(month)
+        .ok()
+        .and_then(|month| time::Month::try_from(month).ok())
+        .ok_or_else(invalid)?;
+    let date = time::Date::from_calendar_date(year, month, u8::try_from(day).map_err(|_| invalid())?)
+        .map_err(|_| invalid())?;
+    u64::try_from(date.midnight().assume_utc().unix_timestamp()).map_err(|_| invalid())
+}
+
+fn entries_to_jsonl(entries: &[AuditEntry]) -> String {
+    entries
+        .iter()
+        .filter_map(|entry| serde_json::to_string(entry).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+fn entries_to_csv(entries: &[AuditEntry]) -> String {
+    let mut csv = String::from("timestamp,actor,source_ip,action,target,before,after\n");
+    for entry in entries {
+        let json = |value: &Option<serde_json::Value>| {
+            value.as_ref().map(|value| value.to_string()).unwrap_or_default()
+        };
+        let fields = [
+            entry.timestamp.to_string(),
+            entry.actor.clone(),
+            entry.source_ip.clone().unwrap_or_default(),
+            entry.action.clone(),
+            entry.target.clone(),
+            json(&entry.before),
+            json(&entry.after),
+        ];
+        let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quotes fields as RFC 4180 requires, and defuses values spreadsheets would run as formulas.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
@@ -0,0 +1,562 @@
+use crate::forms::{LoginForm, LoginQuery};
+use crate::routes::AppState;
+use crate::security::audit::ClientIp;
+use crate::security::sessions::{cookie_value, Session, CSRF_FIELD, CSRF_HEADER, SESSION_COOKIE};
+use crate::security::throttle::AttemptCheck;
+use crate::security::tokens::{ApiToken, TokenScope};
+use crate::security::users::{AuthUser, Role};
+use crate::services::current_epoch_seconds;
+use crate::views::auth::render_login_page;
+use axum::{
+    body::{Body, Bytes},
+    extract::{ConnectInfo, FromRequest, Query, State},
+    http::{header, header::SET_COOKIE, HeaderMap, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{Html, IntoResponse, Redirect, Response},
+    Extension, Form,
+};
+use base64::Engine as _;
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+pub async fn login_page(Query(query): Query<LoginQuery>) -> Html<String> {
+    Html(render_login_page(None, safe_next(query.next.as_deref())))
+}
+
+pub async fn login_action(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Form(form): Form<LoginForm>,
+) -> Response {
+    let next = safe_next(form.next.as_deref());
+    let username = form.username.trim();
+    let ip = client_ip(&state, connect_info.map(|ConnectInfo(addr)| addr), &headers);
+    let attempt = state.users.authenticate(username, &form.password);
+    let user = match throttled(&state, ip, username, attempt).await {
+        Ok(user) => user,
+        Err(AuthFailure::Invalid) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Html(render_login_page(Some("Invalid username or password."), next)),
+            )
+                .into_response();
+        }
+        Err(AuthFailure::LockedOut(retry_after)) => {
+            let message = format!(
+                "Too many failed logins. Try again in {} minute(s).",
+                retry_after.div_ceil(60)
+            );
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                Html(render_login_page(Some(&message), next)),
+            )
+                .into_response();
+            set_retry_after(&mut response, retry_after);
+            return response;
+        }
+    };
+
+    let (session, cookie) = state.sessions.issue(&user.username, current_epoch_seconds());
+    tracing::info!(user = %user.username, "login");
+    with_cookies(Redirect::to(next).into_response(), state.sessions.login_cookies(&session, &cookie))
+}
+
+pub async fn logout_action(
+    State(state): State<AppState>,
+    session: Option<Extension<Session>>,
+) -> Response {
+    if let Some(Extension(session)) = session {
+        if let Err(err) = state.sessions.revoke(&session, current_epoch_seconds()).await {
+            tracing::warn!(user = %session.username, "failed to persist logout: {err}");
+        }
+        tracing::info!(user = %session.username, "logout");
+    }
+    with_cookies(Redirect::to("/login").into_response(), state.sessions.logout_cookies())
+}
+
+enum AuthFailure {
+    Invalid,
+    LockedOut(u64),
+}
+
+/// Runs a credential check under the per-address failed-login limits: locked out
+/// addresses are refused without checking, others wait out their progressive delay.
+/// While as many checks are in flight as failures are left, further ones wait for a slot.
+async fn throttled<T>(
+    state: &AppState,
+    ip: IpAddr,
+    principal: &str,
+    attempt: impl Future<Output = Option<T>>,
+) -> Result<T, AuthFailure> {
+    loop {
+        match state.login_throttle.check(ip, current_epoch_seconds()) {
+            AttemptCheck::LockedOut(retry_after) => {
+                tracing::warn!(%ip, user = %principal, "login refused: address locked out for {retry_after}s");
+                return Err(AuthFailure::LockedOut(retry_after));
+            }
+            AttemptCheck::Busy => tokio::time::sleep(THROTTLE_RETRY_INTERVAL).await,
+            AttemptCheck::Allowed(delay) => {
+                tokio::time::sleep(delay).await;
+                break;
+            }
+        }
+    }
+
+    // Finishes the reservation as a failure if the request is dropped mid-check.
+    let reservation = Reservation { state, ip, finished: false };
+    let result = attempt.await;
+    reservation.finish(result.is_some());
+    if let Some(value) = result {
+        return Ok(value);
+    }
+    tracing::warn!(%ip, user = %principal, "failed login");
+    if let Some(lockout) = state.login_throttle.locked_for(ip, current_epoch_seconds()) {
+        tracing::warn!(%ip, "too many failed logins, address locked out for {lockout}s");
+    }
+    Err(AuthFailure::Invalid)
+}
+
+const THROTTLE_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// A credential check let through by the login throttle.
+struct Reservation<'a> {
+    state: &'a AppState,
+    ip: IpAddr,
+    finished: bool,
+}
+
+impl Reservation<'_> {
+    fn finish(mut self, success: bool) {
+        self.finished = true;
+        if success {
+            self.state.login_throttle.record_success(self.ip);
+        } else {
+            self.state.login_throttle.record_failure(self.ip, current_epoch_seconds());
+        }
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.state.login_throttle.record_failure(self.ip, current_epoch_seconds());
+        }
+    }
+}
+
+/// The TCP peer address. `X-Forwarded-For` is only trusted from a loopback peer in
+/// plain-HTTP mode, where that peer is the reverse proxy; its last entry is the
+/// address the proxy itself saw.
+fn client_ip(state: &AppState, addr: Option<SocketAddr>, headers: &HeaderMap) -> IpAddr {
+    let peer = addr
+        .map(|addr| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    if !(state.behind_proxy && peer.is_loopback()) {
+        return peer;
+    }
+    headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(peer)
+}
+
+fn set_retry_after(response: &mut Response, retry_after: u64) {
+    if let Ok(value) = HeaderValue::from_str(&retry_after.to_string()) {
+        response.headers_mut().insert(header::RETRY_AFTER, value);
+    }
+}
+
+fn with_cookies(mut response: Response, cookies: [String; 2]) -> Response {
+    for cookie in cookies {
+        if let Ok(value) = HeaderValue::from_str(&cookie) {
+            response.headers_mut().append(SET_COOKIE, value);
+        }
+    }
+    response
+}
+
+/// Only same-site relative paths are accepted as login redirect targets.
+fn safe_next(next: Option<&str>) -> &str {
+    next.filter(|value| {
+        value.starts_with('/')
+            && !value.starts_with("//")
+            && !value.starts_with("/\\")
+            && !value.starts_with("/login")
+    })
+    .unwrap_or("/")
+}
+
+/// Minimum role per route group: admins manage configuration, operators control running servers.
+pub(crate) fn required_role(method: &Method, path: &str) -> Role {
+    const ADMIN_PREFIXES: [&str; 4] = ["/settings", "/api/settings", "/api/steamcmd/update", "/api/audit"];
+    const OPERATOR_PREFIXES: [&str; 5] = [
+        "/api/run",
+        "/api/rcon",
+        "/api/players",
+        "/players",
+        "/partials/server-status-card",
+    ];
+    let matches = |prefix: &&str| path_in(path, prefix);
+
+    if ADMIN_PREFIXES.iter().any(matches) || reveals_server_secrets(path) {
+        return Role::Admin;
+    }
+    if path == "/api/rcon/messages/stream" {
+        return Role::Operator;
+    }
+    if !is_state_changing(method) || path == "/logout" {
+        return Role::Viewer;
+    }
+    if OPERATOR_PREFIXES.iter().any(matches) {
+        Role::Operator
+    } else {
+        Role::Admin
+    }
+}
+
+/// Pages and endpoints that show a profile's generated config or its overrides, which
+/// carry the RCON and admin passwords. Knowing those grants more than the operator role.
+fn reveals_server_secrets(path: &str) -> bool {
+    let segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    matches!(
+        segments.as_slice(),
+        ["", "server", _, "config-preview" | "edit"] | ["", "api", "v1", "profiles", _, "config"]
+    )
+}
+
+fn path_in(path: &str, prefix: &str) -> bool {
+    path == prefix
+        || path
+            .strip_prefix(prefix)
+            .map(|rest| rest.starts_with('/'))
+            .unwrap_or(false)
+}
+
+fn is_state_changing(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+fn is_public(path: &str) -> bool {
+    path == "/login" || path_in(path, "/web")
+}
+
+/// Browsers authenticate with the session cookie (plus a CSRF token on state-changing
+/// requests); HTTP Basic auth is only accepted on `/api` routes for scripts and tools.
+pub(crate) async fn auth_middleware(
+    State(state): State<AppState>,
+    mut request: Request<Body>,
+    next: Next<Body>,
+) -> Result<Response, StatusCode> {
+    let path = request.uri().path().to_string();
+    if is_public(&path) {
+        return Ok(next.run(request).await);
+    }
+    let ip = client_ip(
+        &state,
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| *addr),
+        request.headers(),
+    );
+    request.extensions_mut().insert(ClientIp(ip));
+
+    let session = cookie_value(request.headers(), SESSION_COOKIE)
+        .and_then(|cookie| state.sessions.verify(cookie, current_epoch_seconds()));
+    if let Some(session) = session {
+        let Some(user) = state.users.find(&session.username).await else {
+            return Ok(unauthenticated(&request));
+        };
+        let mut request = if is_state_changing(request.method()) {
+            match check_csrf(&state, &session, request).await {
+                Ok(request) => request,
+                Err(response) => return Ok(response),
+            }
+        } else {
+            request
+        };
+        request.extensions_mut().insert(session);
+        return Ok(authorize(user, request, next).await);
+    }
+
+    if path_in(&path, "/api") {
+        if let Some(secret) = bearer_token(&request) {
+            let attempt = state.tokens.authenticate(&secret, current_epoch_seconds());
+            return Ok(match throttled(&state, ip, "<api token>", attempt).await {
+                Ok(token) => authorize_token(token, request, next).await,
+                Err(AuthFailure::Invalid) => {
+                    plain_response(StatusCode::UNAUTHORIZED, "Invalid or expired API token")
+                }
+                Err(AuthFailure::LockedOut(retry_after)) => too_many_attempts(retry_after),
+            });
+        }
+        if let Some((username, password)) = basic_credentials(&request) {
+            if is_cross_site(&request) {
+                return Ok(plain_response(StatusCode::FORBIDDEN, "Cross-site API requests are not allowed"));
+            }
+            let attempt = state.users.authenticate(&username, &password);
+            match throttled(&state, ip, &username, attempt).await {
+                Ok(user) => return Ok(authorize(user, request, next).await),
+                Err(AuthFailure::LockedOut(retry_after)) => return Ok(too_many_attempts(retry_after)),
+                Err(AuthFailure::Invalid) => {}
+            }
+        }
+    }
+
+    Ok(unauthenticated(&request))
+}
+
+async fn check_csrf(
+    state: &AppState,
+    session: &Session,
+    request: Request<Body>,
+) -> Result<Request<Body>, Response> {
+    let header_token = request
+        .headers()
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    if let Some(token) = header_token {
+        return if state.sessions.verify_csrf(session, &token) {
+            Ok(request)
+        } else {
+            Err(csrf_rejected(session))
+        };
+    }
+
+    let is_form = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.starts_with("application/x-www-form-urlencoded"))
+        .unwrap_or(false);
+    if !is_form {
+        return Err(csrf_rejected(session));
+    }
+
+    // Plain form posts carry the token as a field; buffer the body to read it and hand it on.
+    let (parts, body) = request.into_parts();
+    let bytes = Bytes::from_request(Request::new(body), &())
+        .await
+        .map_err(IntoResponse::into_response)?;
+    let fields: Vec<(String, String)> = serde_urlencoded::from_bytes(&bytes).unwrap_or_default();
+    let valid = fields
+        .iter()
+        .find(|(key, _)| key == CSRF_FIELD)
+        .map(|(_, token)| state.sessions.verify_csrf(session, token))
+        .unwrap_or(false);
+    if valid {
+        Ok(Request::from_parts(parts, Body::from(bytes)))
+    } else {
+        Err(csrf_rejected(session))
+    }
+}
+
+fn csrf_rejected(session: &Session) -> Response {
+    tracing::warn!(user = %session.username, "rejected request with missing or invalid CSRF token");
+    plain_response(StatusCode::FORBIDDEN, "Missing or invalid CSRF token")
+}
+
+fn basic_credentials(request: &Request<Body>) -> Option<(String, String)> {
+    let value = request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Basic ")?;
+    let decoded = base64::engine::general_purpose::STANDARD.decode(value).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (username, password) = decoded.split_once(':').unwrap_or((decoded.as_str(), ""));
+    Some((username.to_string(), password.to_string()))
+}
+
+fn too_many_attempts(retry_after: u64) -> Response {
+    let mut response = plain_response(
+        StatusCode::TOO_MANY_REQUESTS,
+        "Too many failed logins from this address",
+    );
+    set_retry_after(&mut response, retry_after);
+    response
+}
+
+fn bearer_token(request: &Request<Body>) -> Option<String> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(|value| value.trim().to_string())
+}
+
+/// Each role's route group maps to one token scope; scopes do not imply each other.
+pub(crate) fn required_scope(role: Role) -> TokenScope {
+    match role {
+        Role::Viewer => TokenScope::Read,
+        Role::Operator => TokenScope::RunControl,
+        Role::Admin => TokenScope::ConfigWrite,
+    }
+}
+
+async fn authorize_token(token: ApiToken, mut request: Request<Body>, next: Next<Body>) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let required = required_role(&method, &path);
+    let scope = required_scope(required);
+    if !token.has_scope(scope) {
+        tracing::warn!(token = %token.name, %method, %path, "denied: token lacks scope {}", scope.as_str());
+        return plain_response(
+            StatusCode::FORBIDDEN,
+            &format!("Forbidden: {} requires the {} scope", path, scope.as_str()),
+        );
+    }
+    let actor = format!("token:{}", token.name);
+    if is_state_changing(&method) {
+        tracing::info!(user = %actor, %method, %path, "request");
+    }
+    request.extensions_mut().insert(AuthUser {
+        username: actor,
+        role: token.role(),
+    });
+    next.run(request).await
+}
+
+/// Browsers may replay cached Basic credentials, so refuse requests a browser marks as cross-site.
+fn is_cross_site(request: &Request<Body>) -> bool {
+    request
+        .headers()
+        .get("sec-fetch-site")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value == "cross-site")
+        .unwrap_or(false)
+}
+
+fn unauthenticated(request: &Request<Body>) -> Response {
+    let path = request.uri().path();
+    if path_in(path, "/api") {
+        let mut response = plain_response(StatusCode::UNAUTHORIZED, "Unauthorized");
+        response.headers_mut().insert(
+            header::WWW_AUTHENTICATE,
+            HeaderValue::from_static("Basic realm=\"ARSSM API\""),
+        );
+        return response;
+    }
+
+    let target = request
+        .uri()
+        .path_and_query()
+        .map(|value| value.as_str())
+        .unwrap_or("/");
+    let login = format!(
+        "/login?{}",
+        serde_urlencoded::to_string([("next", target)]).unwrap_or_default()
+    );
+    if request.headers().contains_key("hx-request") {
+        // htmx swaps responses in place, so ask it to navigate to the login page instead.
+        let mut response = plain_response(StatusCode::UNAUTHORIZED, "Unauthorized");
+        if let Ok(value) = HeaderValue::from_str(&login) {
+            response.headers_mut().insert("hx-redirect", value);
+        }
+        return response;
+    }
+    Redirect::to(&login).into_response()
+}
+
+async fn authorize(user: AuthUser, mut request: Request<Body>, next: Next<Body>) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let required = required_role(&method, &path);
+    if user.role < required {
+        tracing::warn!(user = %user.username, %method, %path, "denied: requires role {}", required.as_str());
+        return plain_response(
+            StatusCode::FORBIDDEN,
+            &format!("Forbidden: {} requires the {} role", path, required.as_str()),
+        );
+    }
+    if is_state_changing(&method) {
+        tracing::info!(user = %user.username, role = user.role.as_str(), %method, %path, "request");
+    }
+    request.extensions_mut().insert(user);
+    next.run(request).await
+}
+
+fn plain_response(status: StatusCode, message: &str) -> Response {
+    (status, message.to_string()).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_groups_require_expected_roles() {
+        assert_eq!(required_role(&Method::GET, "/"), Role::Viewer);
+        assert_eq!(required_role(&Method::GET, "/api/run/logs/stream"), Role::Viewer);
+        assert_eq!(required_role(&Method::GET, "/players"), Role::Viewer);
+        assert_eq!(required_role(&Method::POST, "/logout"), Role::Viewer);
+        assert_eq!(required_role(&Method::GET, "/api/audit/export"), Role::Admin);
+        assert_eq!(required_role(&Method::POST, "/api/run/start"), Role::Operator);
+        assert_eq!(required_role(&Method::POST, "/partials/server-status-card"), Role::Operator);
+        assert_eq!(required_role(&Method::POST, "/players/kick"), Role::Operator);
+        assert_eq!(required_role(&Method::POST, "/api/rcon/command"), Role::Operator);
+        assert_eq!(required_role(&Method::GET, "/api/rcon/messages/stream"), Role::Operator);
+        assert_eq!(required_role(&Method::GET, "/settings"), Role::Admin);
+        assert_eq!(required_role(&Method::GET, "/settings/users"), Role::Admin);
+        assert_eq!(required_role(&Method::GET, "/api/settings"), Role::Admin);
+        assert_eq!(required_role(&Method::POST, "/api/steamcmd/update"), Role::Admin);
+        assert_eq!(required_role(&Method::POST, "/server/pve/delete"), Role::Admin);
+        assert_eq!(required_role(&Method::POST, "/packages/mods/add"), Role::Admin);
+        assert_eq!(required_role(&Method::POST, "/playersx"), Role::Admin);
+    }
+
+    #[test]
+    fn viewers_cannot_read_server_secrets() {
+        for path in [
+            "/server/pve/config-preview",
+            "/server/pve/edit",
+            "/server/pve/edit/",
+            "/api/v1/profiles/pve/config",
+            "/api/v1/profiles/pve/config/",
+        ] {
+            assert_eq!(required_role(&Method::GET, path), Role::Admin, "{path}");
+            assert_eq!(required_scope(required_role(&Method::GET, path)), TokenScope::ConfigWrite);
+        }
+        assert_eq!(required_role(&Method::GET, "/server/pve"), Role::Viewer);
+        assert_eq!(required_role(&Method::GET, "/server/pve/workshop"), Role::Viewer);
+        assert_eq!(required_role(&Method::GET, "/api/v1/profiles/pve"), Role::Viewer);
+    }
+
+    #[test]
+    fn token_scopes_follow_route_groups() {
+        assert_eq!(required_scope(required_role(&Method::GET, "/api/run/status")), TokenScope::Read);
+        assert_eq!(required_scope(required_role(&Method::POST, "/api/run/start")), TokenScope::RunControl);
+        assert_eq!(required_scope(required_role(&Method::POST, "/api/config")), TokenScope::ConfigWrite);
+        assert_eq!(required_scope(required_role(&Method::GET, "/api/v1/profiles")), TokenScope::Read);
+        assert_eq!(
+            required_scope(required_role(&Method::DELETE, "/api/v1/mods/5965550F24A0C152")),
+            TokenScope::ConfigWrite
+        );
+        assert_eq!(required_scope(required_role(&Method::GET, "/api/settings")), TokenScope::ConfigWrite);
+    }
+
+    #[test]
+    fn only_login_and_static_assets_are_public() {
+        assert!(is_public("/login"));
+        assert!(is_public("/web/css/theme.css"));
+        assert!(!is_public("/"));
+        assert!(!is_public("/logout"));
+        assert!(!is_public("/webhooks"));
+    }
+
+    #[test]
+    fn login_redirects_stay_on_this_site() {
+        assert_eq!(safe_next(Some("/server/pve?tab=paths")), "/server/pve?tab=paths");
+        assert_eq!(safe_next(Some("//evil.example")), "/");
+        assert_eq!(safe_next(Some("https://evil.example")), "/");
+        assert_eq!(safe_next(Some("/login")), "/");
+        assert_eq!(safe_next(None), "/");
+    }
+}
@@ -1,5 +1,6 @@
 use crate::forms::AppConfig;
 use crate::routes::AppState;
+use crate::security::audit::Actor;
 use axum::{Json, extract::State, http::StatusCode};
 use std::{io, path::PathBuf};
 
@@ -25,15 +26,31 @@ pub async fn get_config(
 
 pub async fn set_config(
     State(state): State<AppState>,
+    actor: Actor,
     Json(config): Json<AppConfig>,
 ) -> Result<Json<AppConfig>, (StatusCode, String)> {
     if let Err(message) = config.validate() {
         return Err((StatusCode::BAD_REQUEST, message));
     }
 
+    let before = load_config(&state.config_path).await.ok();
     save_config(&state.config_path, &config)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    match before {
+        Some(before) => {
+            state
+                .audit
+                .record_change(&actor, "config.update", "app_config", &before, &config)
+                .await
+        }
+        None => {
+            state
+                .audit
+                .record_object(&actor, "config.update", "app_config", None, Some(&config))
+                .await
+        }
+    }
 
     Ok(Json(config))
 }
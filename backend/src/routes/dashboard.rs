@@ -1,9 +1,15 @@
+use crate::forms::RunProfileQuery;
 use crate::routes::AppState;
-use crate::services::{current_datetime, format_duration};
+use crate::security::audit::Actor;
+use crate::security::users::AuthUser;
+use crate::services::{
+    current_datetime, effective_path_value, format_duration, profile_branch, work_dir_branches,
+};
 use crate::views::dashboard::{render_dashboard_page, render_server_status_card};
 use crate::views::layout::template_env;
-use axum::{Form, extract::State, http::StatusCode, response::Html};
-use backend::storage::{list_profiles, load_packages, load_settings};
+use axum::{Extension, Form, extract::{Query, State}, http::StatusCode, response::Html};
+use backend::steamcmd::{branch_conflicts, read_installed_build};
+use backend::storage::{AppSettings, list_profiles, load_packages, load_profile, load_settings};
 use minijinja::context;
 use serde::Deserialize;
 use sysinfo::{Pid, System};
@@ -26,47 +32,91 @@ pub async fn dashboard_page(
     } else {
         "Not configured"
     };
+    let warnings: Vec<String> = branch_conflicts(&work_dir_branches(&profiles, &settings))
+        .iter()
+        .map(|conflict| conflict.describe())
+        .collect();
 
     Ok(Html(render_dashboard_page(
-        profiles.len(),
+        &profiles,
         packages.len(),
         settings_status,
+        &warnings,
     )))
 }
 
 pub async fn header_status_partial(
     State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(query): Query<RunProfileQuery>,
 ) -> Result<Html<String>, (StatusCode, String)> {
-    let status = state.run_manager.status().await;
+    let settings = load_settings(&state.settings_path)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let profile_id = query
+        .profile_id
+        .filter(|value| !value.trim().is_empty())
+        .or(settings.active_profile_id);
+    let status = match profile_id.as_deref() {
+        Some(profile_id) => Some(state.run_manager.status(profile_id).await),
+        None => None,
+    };
+    let running_count = state.run_manager.running_profile_ids().await.len();
+
     let datetime = current_datetime();
+    let running = status.as_ref().map(|status| status.running).unwrap_or(false);
     let uptime = status
-        .started_at
+        .as_ref()
+        .and_then(|status| status.started_at)
         .map(|secs| format_duration(secs))
         .unwrap_or_else(|| "n/a".to_string());
-    let run_status = if status.running {
-        format!("running ({})", status.profile_id.unwrap_or_else(|| "unknown".to_string()))
-    } else {
-        "stopped".to_string()
+    let run_status = match status.as_ref() {
+        Some(status) if status.running => {
+            let name = crate::routes::run::active_profile_name(Some(&status.profile_id))
+                .await
+                .unwrap_or_else(|| status.profile_id.clone());
+            format!("running ({name})")
+        }
+        _ => "stopped".to_string(),
     };
-    let status_class = if status.running {
+    let status_class = if running {
         "status-pill status-pill--running"
     } else {
         "status-pill status-pill--stopped"
     };
 
-    let (cpu, ram) = if let Some(pid) = status.pid {
+    let (cpu, ram) = if let Some(pid) = status.as_ref().and_then(|status| status.pid) {
         process_metrics(&state.system, pid).await
     } else {
         (None, None)
     };
 
+    let server_info = status.as_ref().and_then(|status| status.server_info.clone());
+    let joinable = server_info.is_some();
+    let (server_name, map, players) = match server_info {
+        Some(info) => (
+            info.name,
+            info.map,
+            format!("{}/{}", info.players, info.max_players),
+        ),
+        None => (String::new(), String::new(), String::new()),
+    };
+
     let context = context! {
         datetime => datetime,
+        running => running,
+        joinable => joinable,
+        server_name => server_name,
+        map => map,
+        players => players,
         run_status => run_status,
         status_class => status_class,
+        running_count => running_count,
         uptime => uptime,
         cpu => cpu.unwrap_or_else(|| "n/a".to_string()),
         ram => ram.unwrap_or_else(|| "n/a".to_string()),
+        username => user.username,
+        role => user.role.as_str(),
     };
 
     let html = template_env()
@@ -97,68 +147,117 @@ async fn process_metrics(
 
 pub async fn server_status_card(
     State(state): State<AppState>,
+    Query(query): Query<RunProfileQuery>,
 ) -> Result<Html<String>, (StatusCode, String)> {
-    let status = state.run_manager.status().await;
+    let profile_id = crate::routes::run::requested_profile_id(&state, query.profile_id).await?;
+    let status = state.run_manager.status(&profile_id).await;
+    let profile_name = crate::routes::run::active_profile_name(Some(&profile_id)).await;
     let settings = load_settings(&state.settings_path)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
-    let active_name = crate::routes::run::active_profile_name(settings.active_profile_id.as_deref()).await;
+    let build = build_summary(&settings, &profile_id).await;
     Ok(Html(render_server_status_card(
         &status,
-        active_name.as_deref(),
+        profile_name.as_deref(),
+        build.as_deref(),
         None,
     )))
 }
 
+/// Describes the installed server build the profile launches, flagging a branch mismatch.
+async fn build_summary(settings: &AppSettings, profile_id: &str) -> Option<String> {
+    let profile = load_profile(profile_id).await.ok()?;
+    let work_dir = effective_path_value(
+        &profile.reforger_server_work_dir_override,
+        &settings.reforger_server_work_dir,
+    );
+    let wanted = profile_branch(&profile, settings);
+    let summary = match read_installed_build(&work_dir).await {
+        Ok(Some(build)) => {
+            let installed = build.branch.as_deref().unwrap_or("public");
+            if build.branch == wanted {
+                format!("{} ({installed})", build.build_id)
+            } else {
+                format!(
+                    "{} ({installed}) · profile wants {}, run a SteamCMD update",
+                    build.build_id,
+                    wanted.as_deref().unwrap_or("public")
+                )
+            }
+        }
+        Ok(None) => "not installed".to_string(),
+        Err(err) => err,
+    };
+    Some(summary)
+}
+
 #[derive(Deserialize)]
 pub(crate) struct ServerActionForm {
     action: String,
+    profile_id: Option<String>,
 }
 
 pub async fn server_status_action(
     State(state): State<AppState>,
+    actor: Actor,
     Form(form): Form<ServerActionForm>,
 ) -> Result<Html<String>, (StatusCode, String)> {
-    let mut message: Option<String> = None;
     let action = form.action.trim();
     let settings = load_settings(&state.settings_path)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
-    let active_id = settings.active_profile_id.clone();
+    let profile_id = crate::routes::run::requested_profile_id(&state, form.profile_id.clone())
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                "No active profile configured.".to_string(),
+            )
+        })?;
 
-    match action {
-        "start" => {
-            if let Some(profile_id) = active_id.clone() {
-                if let Err(err) = crate::routes::run::start_profile(&state, &settings, &profile_id).await {
-                    message = Some(err);
-                }
-            } else {
-                message = Some("No active profile configured.".to_string());
-            }
-        }
-        "stop" => {
-            let _ = state.run_manager.stop().await;
-        }
+    let (message, performed) = match action {
+        "start" => match crate::routes::run::start_profile(&state, &settings, &profile_id).await {
+            Ok(report) => (report.notice(), true),
+            Err(err) => (Some(err), false),
+        },
+        "stop" => match crate::routes::run::stop_profile(&state, &settings, &profile_id).await {
+            Ok(outcome) => (Some(outcome.describe().to_string()), true),
+            Err(err) => (Some(err), false),
+        },
         "restart" => {
-            let _ = state.run_manager.stop().await;
-            if let Some(profile_id) = active_id.clone() {
-                if let Err(err) = crate::routes::run::start_profile(&state, &settings, &profile_id).await {
-                    message = Some(err);
+            let stopped = crate::routes::run::stop_profile(&state, &settings, &profile_id)
+                .await
+                .ok();
+            match crate::routes::run::start_profile(&state, &settings, &profile_id).await {
+                Ok(report) => {
+                    let parts: Vec<String> = stopped
+                        .map(|outcome| outcome.describe().to_string())
+                        .into_iter()
+                        .chain(report.notice())
+                        .collect();
+                    ((!parts.is_empty()).then(|| parts.join(" ")), true)
                 }
-            } else {
-                message = Some("No active profile configured.".to_string());
+                Err(err) => (Some(err), false),
             }
         }
-        _ => {
-            message = Some("Unknown action.".to_string());
-        }
+        _ => (Some("Unknown action.".to_string()), false),
+    };
+    // As in the JSON API, only actions that were carried out are audited.
+    if performed {
+        let result = serde_json::json!({ "message": message });
+        state
+            .audit
+            .record(&actor, &format!("run.{action}"), &profile_id, None, Some(result))
+            .await;
     }
 
-    let status = state.run_manager.status().await;
-    let active_name = crate::routes::run::active_profile_name(active_id.as_deref()).await;
+    let status = state.run_manager.status(&profile_id).await;
+    let profile_name = crate::routes::run::active_profile_name(Some(&profile_id)).await;
+    let build = build_summary(&settings, &profile_id).await;
     Ok(Html(render_server_status_card(
         &status,
-        active_name.as_deref(),
+        profile_name.as_deref(),
+        build.as_deref(),
         message.as_deref(),
     )))
 }
@@ -1,15 +1,29 @@
+pub mod api_v1;
+pub mod audit;
+pub mod auth;
 pub mod config;
 pub mod dashboard;
 pub mod health;
+pub mod openapi;
 pub mod packages;
+pub mod players;
 pub mod profiles;
+pub mod rcon;
 pub mod run;
 pub mod settings;
+pub mod tokens;
+pub mod users;
 pub mod workshop;
 
+use crate::security::audit::{audit_log_path, AuditLog};
+use crate::security::sessions::{
+    session_key_path, session_state_path, SessionManager, DEFAULT_SESSION_TTL_SECS,
+};
+use crate::security::throttle::LoginThrottle;
+use crate::security::tokens::{tokens_path, TokenStore};
+use crate::security::users::{users_path, UserStore};
 use axum::{Router, routing::get};
-use base64::Engine as _;
-use backend::{runner::RunManager, storage::settings_path, workshop::{ReqwestFetcher, WorkshopResolver}};
+use backend::{rcon::RconManager, runner::RunManager, steamcmd::SteamcmdManager, storage::{load_settings, settings_path, workshop_cache_dir}, workshop::{RateLimitedFetcher, ReqwestFetcher, WorkshopResolver, DEFAULT_REQUESTS_PER_SECOND}, workshop_cache::{CachingFetcher, WorkshopCache}};
 use std::path::PathBuf;
 use tower_http::services::ServeDir;
 use tokio::sync::Mutex;
@@ -20,24 +34,49 @@ use sysinfo::System;
 pub struct AppState {
     pub config_path: PathBuf,
     pub workshop_resolver: WorkshopResolver,
+    /// Pages fetched by `workshop_resolver`, inspected and purged from the settings.
+    pub workshop_cache: WorkshopCache,
     pub settings_path: PathBuf,
     pub run_manager: RunManager,
+    pub rcon: RconManager,
+    pub steamcmd: SteamcmdManager,
     pub system: Arc<Mutex<System>>,
-    pub auth: crate::security::Credentials,
+    pub users: UserStore,
+    pub sessions: SessionManager,
+    pub tokens: TokenStore,
+    pub login_throttle: LoginThrottle,
+    pub audit: AuditLog,
+    /// Set in plain-HTTP mode, where loopback peers are a reverse proxy that
+    /// reports the client address in `X-Forwarded-For`.
+    pub behind_proxy: bool,
+    /// The certificate being served, shown with its fingerprint in the settings.
+    pub tls_cert_path: Option<PathBuf>,
 }
 
 pub fn build_router(state: AppState) -> Router {
     let web_dir = web_dir();
     Router::new()
+        .route(openapi::OPENAPI_PATH, get(openapi::openapi_json))
+        .nest("/api/v1", api_v1::router())
         .route("/api/config", get(config::get_config).post(config::set_config))
         .route("/api/workshop/resolve", axum::routing::post(workshop::resolve_workshop))
+        .route("/api/workshop/cache", get(workshop::workshop_cache_status).delete(workshop::purge_workshop_cache_api))
         .route("/api/settings", get(settings::get_settings_api).post(settings::save_settings_api))
         .route("/api/steamcmd/update", axum::routing::post(settings::steamcmd_update))
+        .route("/api/steamcmd/status", get(settings::steamcmd_status))
+        .route("/api/steamcmd/stream", get(settings::steamcmd_stream))
         .route("/api/run/status", get(run::run_status))
+        .route("/api/run/instances", get(run::run_instances))
         .route("/api/run/start", axum::routing::post(run::run_start))
         .route("/api/run/stop", axum::routing::post(run::run_stop))
         .route("/api/run/logs/tail", get(run::run_logs_tail))
         .route("/api/run/logs/stream", get(run::run_logs_stream))
+        .route("/api/players", get(players::players_api))
+        .route("/api/players/kick", axum::routing::post(players::kick_player_api))
+        .route("/api/players/ban", axum::routing::post(players::ban_player_api))
+        .route("/api/players/broadcast", axum::routing::post(players::broadcast_api))
+        .route("/api/rcon/command", axum::routing::post(rcon::rcon_command))
+        .route("/api/rcon/messages/stream", get(rcon::rcon_messages_stream))
         .route("/server", get(profiles::profiles_page))
         .route("/server/:profile_id", get(profiles::profile_detail))
         .route("/server/:profile_id/activate", axum::routing::post(profiles::activate_profile))
@@ -49,6 +88,10 @@ pub fn build_router(state: AppState) -> Router {
         .route("/server/new", get(profiles::new_profile_page))
         .route("/server/new/resolve", axum::routing::post(profiles::new_profile_resolve))
         .route("/server/new/create", axum::routing::post(profiles::new_profile_create))
+        .route("/server/:profile_id/versions", get(profiles::profile_versions_page))
+        .route("/server/:profile_id/versions/check", axum::routing::post(profiles::profile_versions_check))
+        .route("/server/:profile_id/versions/pin", axum::routing::post(profiles::profile_version_pin))
+        .route("/server/:profile_id/versions/accept", axum::routing::post(profiles::profile_versions_accept))
         .route("/server/:profile_id/workshop", get(profiles::profile_workshop_page))
         .route("/server/:profile_id/workshop/resolve", axum::routing::post(profiles::profile_workshop_resolve))
         .route("/server/:profile_id/workshop/save", axum::routing::post(profiles::profile_workshop_save))
@@ -65,14 +108,32 @@ pub fn build_router(state: AppState) -> Router {
         .route("/packages/packs/:package_id/edit", axum::routing::post(packages::edit_package))
         .route("/packages/packs/:package_id/delete", axum::routing::post(packages::delete_package))
         .route("/run-logs", get(run::run_logs_page))
+        .route("/players", get(players::players_page))
+        .route("/players/kick", axum::routing::post(players::kick_player_action))
+        .route("/players/ban", axum::routing::post(players::ban_player_action))
+        .route("/players/broadcast", axum::routing::post(players::broadcast_action))
         .route("/settings", get(settings::settings_page).post(settings::settings_save))
         .route("/settings/defaults", axum::routing::post(settings::settings_defaults_save))
+        .route("/settings/users", get(users::users_page))
+        .route("/settings/users/add", axum::routing::post(users::add_user_action))
+        .route("/settings/users/:username/role", axum::routing::post(users::set_role_action))
+        .route("/settings/users/:username/password", axum::routing::post(users::set_password_action))
+        .route("/settings/users/:username/delete", axum::routing::post(users::delete_user_action))
+        .route("/settings/audit", get(audit::audit_page))
+        .route("/api/audit/export", get(audit::audit_export))
+        .route("/settings/workshop-cache", get(workshop::workshop_cache_page).post(workshop::workshop_cache_settings_action))
+        .route("/settings/workshop-cache/purge", axum::routing::post(workshop::purge_workshop_cache_action))
+        .route("/settings/tokens", get(tokens::tokens_page))
+        .route("/settings/tokens/create", axum::routing::post(tokens::create_token_action))
+        .route("/settings/tokens/:token_id/revoke", axum::routing::post(tokens::revoke_token_action))
         .route("/partials/header-status", get(dashboard::header_status_partial))
         .route("/partials/server-status-card", get(dashboard::server_status_card).post(dashboard::server_status_action))
         .route("/health", get(health::health))
         .route("/", get(dashboard::dashboard_page))
         .nest_service("/web", ServeDir::new(web_dir))
-        .layer(axum::middleware::from_fn_with_state(state.clone(), auth_middleware))
+        .route("/login", get(auth::login_page).post(auth::login_action))
+        .route("/logout", axum::routing::post(auth::logout_action))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), auth::auth_middleware))
         .with_state(state)
 }
 
@@ -82,20 +143,56 @@ pub async fn default_state() -> AppState {
         .unwrap_or_else(|err| {
             panic!("failed to load credentials: {err}");
         });
-    if generated {
-        tracing::info!(
-            "Generated credentials (store securely) username={} password={}",
-            creds.username,
-            creds.password
-        );
+    if let Some(password) = generated {
+        // Printed outside tracing so the password never reaches a log sink.
+        eprintln!("Generated credentials (store securely, shown only once):");
+        eprintln!("  Username: {}", creds.username);
+        eprintln!("  Password: {password}");
+        tracing::info!("generated new credentials; the password was printed to stderr");
     }
+    let users = UserStore::load(users_path(), creds)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("failed to load users: {err}");
+        });
+    let sessions = SessionManager::load_or_create(
+        &session_key_path(),
+        &session_state_path(),
+        DEFAULT_SESSION_TTL_SECS,
+    )
+    .await
+    .unwrap_or_else(|err| {
+        panic!("failed to load sessions: {err}");
+    });
+    let tokens = TokenStore::load(tokens_path())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("failed to load API tokens: {err}");
+        });
+    let settings = load_settings(&settings_path()).await.unwrap_or_default();
+    let workshop_cache = WorkshopCache::new(workshop_cache_dir(), settings.workshop_cache);
     AppState {
         config_path: config::config_path(),
-        workshop_resolver: WorkshopResolver::new(std::sync::Arc::new(ReqwestFetcher::new())),
+        workshop_resolver: WorkshopResolver::new(Arc::new(CachingFetcher::new(
+            Arc::new(RateLimitedFetcher::new(
+                Arc::new(ReqwestFetcher::new()),
+                DEFAULT_REQUESTS_PER_SECOND,
+            )),
+            workshop_cache.clone(),
+        ))),
+        workshop_cache,
         settings_path: settings_path(),
         run_manager: RunManager::new(),
+        rcon: RconManager::new(),
+        steamcmd: SteamcmdManager::new(),
         system: Arc::new(Mutex::new(System::new())),
-        auth: creds,
+        users,
+        sessions,
+        tokens,
+        login_throttle: LoginThrottle::new(settings.login_limits),
+        audit: AuditLog::new(audit_log_path()),
+        behind_proxy: false,
+        tls_cert_path: None,
     }
 }
 
@@ -104,33 +201,3 @@ fn web_dir() -> PathBuf {
         .map(PathBuf::from)
         .unwrap_or_else(|_| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..").join("web"))
 }
-
-async fn auth_middleware(
-    axum::extract::State(state): axum::extract::State<AppState>,
-    request: axum::http::Request<axum::body::Body>,
-    next: axum::middleware::Next<axum::body::Body>,
-) -> Result<axum::response::Response, axum::http::StatusCode> {
-    let header = request.headers().get(axum::http::header::AUTHORIZATION);
-    if let Some(header) = header.and_then(|value| value.to_str().ok()) {
-        if let Some(value) = header.strip_prefix("Basic ") {
-            if let Ok(decoded) = base64::engine::general_purpose::STANDARD.decode(value) {
-                if let Ok(decoded) = String::from_utf8(decoded) {
-                    let mut parts = decoded.splitn(2, ':');
-                    let user = parts.next().unwrap_or("");
-                    let pass = parts.next().unwrap_or("");
-                    if user == state.auth.username && pass == state.auth.password {
-                        return Ok(next.run(request).await);
-                    }
-                }
-            }
-        }
-    }
-
-    let mut response = axum::response::Response::new(axum::body::Body::from("Unauthorized"));
-    *response.status_mut() = axum::http::StatusCode::UNAUTHORIZED;
-    response.headers_mut().insert(
-        axum::http::header::WWW_AUTHENTICATE,
-        axum::http::HeaderValue::from_static("Basic realm=\"ARSSM\""),
-    );
-    Ok(response.map(axum::body::boxed))
-}
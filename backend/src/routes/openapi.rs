@@ -0,0 +1,470 @@
+use crate::forms::{
+    AcceptUpgradesRequest, AppConfig, AuditQuery, ModCreateRequest, ModUpdateRequest, PackageRequest,
+    PlayerBanRequest, PlayerBroadcastRequest, PlayerKickRequest, ProfileRequest,
+    RconCommandRequest, RunProfileQuery, RunStartRequest, WorkshopCachePurgeQuery,
+};
+use crate::routes::api_v1::{
+    AcceptUpgradesResponse, ApiErrorBody, ConfigWriteResponse, ResolveResponse,
+};
+use crate::routes::players::{PlayerActionResponse, PlayersOverview};
+use crate::routes::rcon::RconCommandResponse;
+use crate::routes::run::{LogTailResponse, RunStartResponse, RunStopResponse};
+use crate::routes::settings::SteamcmdUpdateResponse;
+use crate::routes::workshop::WorkshopCachePurgeResponse;
+use crate::security::sessions::SESSION_COOKIE;
+use axum::Json;
+use backend::models::{ModEntry, ModPackage, ModVersionStatus, ServerProfile};
+use backend::runner::RunStatus;
+use backend::steamcmd::SteamcmdStatus;
+use backend::storage::AppSettings;
+use backend::workshop::{WorkshopResolveRequest, WorkshopResolveResult};
+use backend::workshop_cache::WorkshopCacheStatus;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+use std::cell::RefCell;
+
+pub const OPENAPI_PATH: &str = "/api/openapi.json";
+
+pub async fn openapi_json() -> Json<Value> {
+    Json(openapi_spec())
+}
+
+/// OpenAPI 3 description of every `/api` route, with schemas generated from the
+/// request and response types the handlers use.
+pub fn openapi_spec() -> Value {
+    let spec = Spec::new();
+
+    let config = spec.schema::<AppConfig>();
+    spec.add("get", "/api/config", Operation::new("config", "Read the legacy app config")
+        .json_response(200, "Current config", config.clone()));
+    spec.add("post", "/api/config", Operation::new("config", "Replace the legacy app config")
+        .json_body(config.clone())
+        .json_response(200, "Saved config", config));
+
+    spec.add("post", "/api/workshop/resolve", Operation::new("workshop", "Resolve a workshop item with its dependencies and scenarios")
+        .json_body(spec.schema::<WorkshopResolveRequest>())
+        .json_response(200, "Resolve result", spec.schema::<WorkshopResolveResult>()));
+    spec.add("get", "/api/workshop/cache", Operation::new("workshop", "List cached workshop pages and the cache settings")
+        .json_response(200, "Cache contents", spec.schema::<WorkshopCacheStatus>()));
+    spec.add("delete", "/api/workshop/cache", Operation::new("workshop", "Purge the workshop page cache")
+        .query::<WorkshopCachePurgeQuery>()
+        .json_response(200, "Number of removed pages", spec.schema::<WorkshopCachePurgeResponse>()));
+
+    let settings = spec.schema::<AppSettings>();
+    spec.add("get", "/api/settings", Operation::new("settings", "Read the app settings")
+        .json_response(200, "Current settings", settings.clone()));
+    spec.add("post", "/api/settings", Operation::new("settings", "Replace the app settings")
+        .json_body(settings.clone())
+        .json_response(200, "Saved settings", settings));
+
+    spec.add("post", "/api/steamcmd/update", Operation::new("steamcmd", "Start a server update via SteamCMD")
+        .query::<RunProfileQuery>()
+        .json_response(200, "Update started", spec.schema::<SteamcmdUpdateResponse>()));
+    spec.add("get", "/api/steamcmd/status", Operation::new("steamcmd", "Progress of the running or last update")
+        .json_response(200, "Update status", spec.schema::<SteamcmdStatus>()));
+    spec.add("get", "/api/steamcmd/stream", Operation::new("steamcmd", "SteamCMD output as server-sent events")
+        .event_stream());
+
+    let status = spec.schema::<RunStatus>();
+    spec.add("get", "/api/run/status", Operation::new("run", "Status of a profile's server")
+        .query::<RunProfileQuery>()
+        .json_response(200, "Run status", status.clone()));
+    spec.add("get", "/api/run/instances", Operation::new("run", "Status of every known server instance")
+        .json_response(200, "Run status per profile", json!({ "type": "array", "items": status })));
+    spec.add("post", "/api/run/start", Operation::new("run", "Start a profile's server")
+        .json_body(spec.schema::<RunStartRequest>())
+        .json_response(200, "Server started", spec.schema::<RunStartResponse>()));
+    spec.add("post", "/api/run/stop", Operation::new("run", "Stop a profile's server")
+        .query::<RunProfileQuery>()
+        .json_response(200, "Server stopped", spec.schema::<RunStopResponse>()));
+    spec.add("get", "/api/run/logs/tail", Operation::new("run", "Last lines of a profile's server log")
+        .query::<RunProfileQuery>()
+        .query_param("n", "Number of lines, 200 by default", json!({ "type": "integer", "minimum": 0 }))
+        .json_response(200, "Log lines", spec.schema::<LogTailResponse>()));
+    spec.add("get", "/api/run/logs/stream", Operation::new("run", "Server log lines as server-sent events")
+        .query::<RunProfileQuery>()
+        .event_stream());
+
+    let player_action = spec.schema::<PlayerActionResponse>();
+    spec.add("get", "/api/players", Operation::new("players", "Connected players and bans")
+        .query::<RunProfileQuery>()
+        .json_response(200, "Players overview", spec.schema::<PlayersOverview>()));
+    spec.add("post", "/api/players/kick", Operation::new("players", "Kick a player")
+        .json_body(spec.schema::<PlayerKickRequest>())
+        .json_response(200, "Player kicked", player_action.clone()));
+    spec.add("post", "/api/players/ban", Operation::new("players", "Ban a player")
+        .json_body(spec.schema::<PlayerBanRequest>())
+        .json_response(200, "Player banned", player_action.clone()));
+    spec.add("post", "/api/players/broadcast", Operation::new("players", "Send a message to all players")
+        .json_body(spec.schema::<PlayerBroadcastRequest>())
+        .json_response(200, "Message sent", player_action));
+
+    spec.add("post", "/api/rcon/command", Operation::new("rcon", "Run an RCON command")
+        .json_body(spec.schema::<RconCommandRequest>())
+        .json_response(200, "Command response", spec.schema::<RconCommandResponse>()));
+    spec.add("get", "/api/rcon/messages/stream", Operation::new("rcon", "RCON server messages as server-sent events")
+        .query::<RunProfileQuery>()
+        .event_stream());
+
+    spec.add("get", "/api/audit/export", Operation::new("audit", "Export audit log entries")
+        .query::<AuditQuery>()
+        .text_response("Matching entries as JSON lines or CSV", &["application/x-ndjson", "text/csv"]));
+
+    spec.add("get", OPENAPI_PATH, Operation::new("meta", "This document")
+        .json_response(200, "OpenAPI 3 document", json!({ "type": "object" })));
+
+    let profile = spec.schema::<ServerProfile>();
+    let profile_request = spec.schema::<ProfileRequest>();
+    spec.add("get", "/api/v1/profiles", Operation::new("profiles", "List profiles")
+        .json_response(200, "All profiles", json!({ "type": "array", "items": profile.clone() })));
+    spec.add("post", "/api/v1/profiles", Operation::new("profiles", "Create a profile")
+        .json_body(profile_request.clone())
+        .json_response(201, "Created profile", profile.clone()));
+    spec.add("get", "/api/v1/profiles/{profile_id}", Operation::new("profiles", "Read a profile")
+        .json_response(200, "Profile", profile.clone()));
+    spec.add("put", "/api/v1/profiles/{profile_id}", Operation::new("profiles", "Replace a profile's editable fields")
+        .json_body(profile_request)
+        .json_response(200, "Updated profile", profile));
+    spec.add("delete", "/api/v1/profiles/{profile_id}", Operation::new("profiles", "Delete a stopped profile")
+        .empty_response(204, "Profile deleted"));
+    spec.add("post", "/api/v1/profiles/{profile_id}/resolve", Operation::new("profiles", "Resolve the profile's workshop item")
+        .json_response(200, "Updated profile and resolve result", spec.schema::<ResolveResponse>()));
+    spec.add("get", "/api/v1/profiles/{profile_id}/config", Operation::new("profiles", "Preview the generated server config")
+        .json_response(200, "Server config", json!({ "type": "object" })));
+    spec.add("post", "/api/v1/profiles/{profile_id}/config", Operation::new("profiles", "Resolve, generate and write the server config")
+        .json_response(200, "Written config", spec.schema::<ConfigWriteResponse>()));
+    spec.add("get", "/api/v1/profiles/{profile_id}/versions", Operation::new("profiles", "List pinned and latest mod versions")
+        .json_response(200, "Every mod of the profile", json!({ "type": "array", "items": spec.schema::<ModVersionStatus>() })));
+    spec.add("post", "/api/v1/profiles/{profile_id}/versions/accept", Operation::new("profiles", "Accept pending mod upgrades and rewrite the server config")
+        .json_body(spec.schema::<AcceptUpgradesRequest>())
+        .json_response(200, "Accepted upgrades and written config", spec.schema::<AcceptUpgradesResponse>()));
+
+    let mod_entry = spec.schema::<ModEntry>();
+    spec.add("get", "/api/v1/mods", Operation::new("mods", "List mods")
+        .json_response(200, "All mods", json!({ "type": "array", "items": mod_entry.clone() })));
+    spec.add("post", "/api/v1/mods", Operation::new("mods", "Add a mod")
+        .json_body(spec.schema::<ModCreateRequest>())
+        .json_response(201, "Added mod", mod_entry.clone()));
+    spec.add("get", "/api/v1/mods/{mod_id}", Operation::new("mods", "Read a mod")
+        .json_response(200, "Mod", mod_entry.clone()));
+    spec.add("put", "/api/v1/mods/{mod_id}", Operation::new("mods", "Rename a mod")
+        .json_body(spec.schema::<ModUpdateRequest>())
+        .json_response(200, "Updated mod", mod_entry));
+    spec.add("delete", "/api/v1/mods/{mod_id}", Operation::new("mods", "Delete a mod no package uses")
+        .empty_response(204, "Mod deleted"));
+
+    let package = spec.schema::<ModPackage>();
+    let package_request = spec.schema::<PackageRequest>();
+    spec.add("get", "/api/v1/packages", Operation::new("packages", "List packages")
+        .json_response(200, "All packages", json!({ "type": "array", "items": package.clone() })));
+    spec.add("post", "/api/v1/packages", Operation::new("packages", "Create a package")
+        .json_body(package_request.clone())
+        .json_response(201, "Created package", package.clone()));
+    spec.add("get", "/api/v1/packages/{package_id}", Operation::new("packages", "Read a package")
+        .json_response(200, "Package", package.clone()));
+    spec.add("put", "/api/v1/packages/{package_id}", Operation::new("packages", "Replace a package")
+        .json_body(package_request)
+        .json_response(200, "Updated package", package));
+    spec.add("delete", "/api/v1/packages/{package_id}", Operation::new("packages", "Delete a package")
+        .empty_response(204, "Package deleted"));
+
+    spec.finish()
+}
+
+/// Collects paths and the schemas they reference. Interior mutability lets
+/// schemas be requested while an operation is being added.
+struct Spec {
+    generator: RefCell<SchemaGenerator>,
+    paths: RefCell<Map<String, Value>>,
+}
+
+impl Spec {
+    fn new() -> Self {
+        Self {
+            generator: RefCell::new(SchemaSettings::openapi3().into_generator()),
+            paths: RefCell::new(Map::new()),
+        }
+    }
+
+    /// A `$ref` into `components/schemas` for named types, inline otherwise.
+    fn schema<T: JsonSchema>(&self) -> Value {
+        to_value(self.generator.borrow_mut().subschema_for::<T>())
+    }
+
+    fn add(&self, method: &str, path: &str, mut operation: Operation) {
+        for name in path_params(path) {
+            operation.parameters.push(json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": { "type": "string" },
+            }));
+        }
+        // The versioned API answers errors with a JSON body, the older routes with plain text.
+        let error = if path.starts_with("/api/v1/") {
+            json!({
+                "description": "Error",
+                "content": { "application/json": { "schema": self.schema::<ApiErrorBody>() } },
+            })
+        } else {
+            json!({
+                "description": "Error message",
+                "content": { "text/plain": { "schema": { "type": "string" } } },
+            })
+        };
+        operation.responses.insert("default".to_string(), error);
+
+        let mut value = json!({
+            "tags": [operation.tag],
+            "summary": operation.summary,
+            "responses": operation.responses,
+        });
+        if !operation.parameters.is_empty() {
+            value["parameters"] = Value::Array(operation.parameters);
+        }
+        if let Some(body) = operation.request_body {
+            value["requestBody"] = body;
+        }
+        let mut paths = self.paths.borrow_mut();
+        let item = paths.entry(path.to_string()).or_insert_with(|| json!({}));
+        item[method] = value;
+    }
+
+    fn finish(self) -> Value {
+        // Definitions skip the generator's visitors, which e.g. turn the `true`
+        // schema of `serde_json::Value` fields into `{}` for OpenAPI 3.0.
+        let mut generator = self.generator.into_inner();
+        let mut definitions = generator.take_definitions();
+        for visitor in generator.visitors_mut() {
+            for schema in definitions.values_mut() {
+                visitor.visit_schema(schema);
+            }
+        }
+        let schemas: Map<String, Value> = definitions
+            .iter()
+            .map(|(name, schema)| (name.clone(), to_value(schema)))
+            .collect();
+        json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": "ARSSM API",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "paths": self.paths.into_inner(),
+            "components": {
+                "schemas": schemas,
+                "securitySchemes": {
+                    "bearer": { "type": "http", "scheme": "bearer" },
+                    "basic": { "type": "http", "scheme": "basic" },
+                    "session": { "type": "apiKey", "in": "cookie", "name": SESSION_COOKIE },
+                },
+            },
+            "security": [{ "bearer": [] }, { "basic": [] }, { "session": [] }],
+        })
+    }
+}
+
+struct Operation {
+    tag: &'static str,
+    summary: &'static str,
+    parameters: Vec<Value>,
+    request_body: Option<Value>,
+    responses: Map<String, Value>,
+}
+
+impl Operation {
+    fn new(tag: &'static str, summary: &'static str) -> Self {
+        Self {
+            tag,
+            summary,
+            parameters: Vec::new(),
+            request_body: None,
+            responses: Map::new(),
+        }
+    }
+
+    /// One query parameter per field of `T`.
+    fn query<T: JsonSchema>(mut self) -> Self {
+        let root = SchemaSettings::openapi3()
+            .into_generator()
+            .into_root_schema_for::<T>();
+        if let Some(object) = root.schema.object {
+            for (name, schema) in object.properties {
+                self.parameters.push(json!({
+                    "name": name,
+                    "in": "query",
+                    "required": object.required.contains(&name),
+                    "schema": to_value(&schema),
+                }));
+            }
+        }
+        self
+    }
+
+    fn query_param(mut self, name: &str, description: &str, schema: Value) -> Self {
+        self.parameters.push(json!({
+            "name": name,
+            "in": "query",
+            "required": false,
+            "description": description,
+            "schema": schema,
+        }));
+        self
+    }
+
+    fn json_body(mut self, schema: Value) -> Self {
+        self.request_body = Some(json!({
+            "required": true,
+            "content": { "application/json": { "schema": schema } },
+        }));
+        self
+    }
+
+    fn json_response(mut self, status: u16, description: &str, schema: Value) -> Self {
+        self.responses.insert(
+            status.to_string(),
+            json!({
+                "description": description,
+                "content": { "application/json": { "schema": schema } },
+            }),
+        );
+        self
+    }
+
+    fn text_response(mut self, description: &str, content_types: &[&str]) -> Self {
+        let content: Map<String, Value> = content_types
+            .iter()
+            .map(|content_type| {
+                (
+                    content_type.to_string(),
+                    json!({ "schema": { "type": "string" } }),
+                )
+            })
+            .collect();
+        self.responses.insert(
+            "200".to_string(),
+            json!({ "description": description, "content": content }),
+        );
+        self
+    }
+
+    fn event_stream(self) -> Self {
+        self.text_response("Server-sent events, one line per event", &["text/event-stream"])
+    }
+
+    fn empty_response(mut self, status: u16, description: &str) -> Self {
+        self.responses
+            .insert(status.to_string(), json!({ "description": description }));
+        self
+    }
+}
+
+fn path_params(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Value {
+    serde_json::to_value(value).expect("schemas serialize to JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// `(method, path)` for every `.route(...)` in `source`, with axum's `:param`
+    /// written the OpenAPI way.
+    fn routes_in(source: &str, prefix: &str) -> BTreeSet<(String, String)> {
+        let method = regex::Regex::new(r"\b(get|post|put|delete|patch)\(").unwrap();
+        let param = regex::Regex::new(r":(\w+)").unwrap();
+        let mut routes = BTreeSet::new();
+        let mut rest = source;
+        while let Some(start) = rest.find(".route(") {
+            let args = &rest[start + ".route(".len()..];
+            let mut depth = 1;
+            let end = args
+                .char_indices()
+                .find(|(_, c)| {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                })
+                .map(|(index, _)| index)
+                .expect("unbalanced .route call");
+            let call = &args[..end];
+            let path = if call.starts_with("openapi::OPENAPI_PATH") {
+                OPENAPI_PATH
+            } else {
+                call.split('"').nth(1).expect("route path")
+            };
+            let path = format!("{prefix}{path}");
+            let path = param.replace_all(&path, "{$1}").to_string();
+            for captures in method.captures_iter(call) {
+                routes.insert((captures[1].to_string(), path.clone()));
+            }
+            rest = &args[end..];
+        }
+        routes
+    }
+
+    #[test]
+    fn spec_matches_router() {
+        let router = include_str!("mod.rs");
+        assert!(router.contains(r#".nest("/api/v1", api_v1::router())"#));
+        let mut routed: BTreeSet<_> = routes_in(router, "")
+            .into_iter()
+            .filter(|(_, path)| path.starts_with("/api/"))
+            .collect();
+        routed.extend(routes_in(include_str!("api_v1.rs"), "/api/v1"));
+
+        let spec = openapi_spec();
+        let documented: BTreeSet<_> = spec["paths"]
+            .as_object()
+            .expect("paths")
+            .iter()
+            .flat_map(|(path, item)| {
+                item.as_object()
+                    .expect("path item")
+                    .keys()
+                    .map(move |method| (method.clone(), path.clone()))
+            })
+            .collect();
+
+        let undocumented: Vec<_> = routed.difference(&documented).collect();
+        let stale: Vec<_> = documented.difference(&routed).collect();
+        assert!(
+            undocumented.is_empty() && stale.is_empty(),
+            "OpenAPI spec out of sync with the router; undocumented: {undocumented:?}, not routed: {stale:?}"
+        );
+    }
+
+    #[test]
+    fn schema_references_resolve() {
+        let spec = openapi_spec();
+        let schemas = spec["components"]["schemas"].as_object().expect("schemas");
+        for name in ["RunStatus", "AppSettings", "WorkshopResolveRequest", "WorkshopResolveResult"] {
+            assert!(schemas.contains_key(name), "{name} missing");
+        }
+
+        // OpenAPI 3.0 has no boolean schemas.
+        assert!(schemas["ConfigWriteResponse"]["properties"]["config"].is_object());
+
+        let text = spec.to_string();
+        let reference = regex::Regex::new(r##""\$ref":"#/components/schemas/([^"]+)""##).unwrap();
+        for captures in reference.captures_iter(&text) {
+            assert!(schemas.contains_key(&captures[1]), "dangling $ref {}", &captures[1]);
+        }
+
+        let status = &spec["paths"]["/api/run/status"]["get"];
+        assert_eq!(status["parameters"][0]["name"], "profile_id");
+        assert_eq!(
+            status["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/RunStatus"
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use crate::forms::{ModForm, PackageCreateForm, PackageForm, PackageSelectionForm};
-use crate::services::{parse_mod_id_input, update_list_selection};
+use crate::routes::AppState;
+use crate::security::audit::Actor;
+use crate::services::{known_mod_metadata, parse_mod_id_input, update_list_selection};
 use crate::views::packages::{render_package_edit_page_with_selection, render_packages_page_full};
-use axum::{Form, extract::Path, http::StatusCode, response::Html};
-use backend::storage::{load_mods, load_packages, save_mods, save_packages};
+use axum::{Form, extract::{Path, State}, http::StatusCode, response::Html};
+use backend::storage::{list_profiles, load_mods, load_packages, save_mods, save_packages};
 
 pub async fn packages_page() -> Result<Html<String>, (StatusCode, String)> {
     let mods = load_mods()
@@ -15,6 +17,8 @@ pub async fn packages_page() -> Result<Html<String>, (StatusCode, String)> {
 }
 
 pub async fn add_mod(
+    State(state): State<AppState>,
+    actor: Actor,
     Form(form): Form<ModForm>,
 ) -> Result<Html<String>, (StatusCode, String)> {
     let mut mods = load_mods()
@@ -42,13 +46,20 @@ pub async fn add_mod(
         )));
     }
 
-    mods.push(backend::models::ModEntry {
+    let profiles = list_profiles().await.unwrap_or_default();
+    let entry = backend::models::ModEntry {
+        metadata: known_mod_metadata(&profiles, &mod_id),
         mod_id,
         name: form.name.trim().to_string(),
-    });
+    };
+    mods.push(entry.clone());
     save_mods(&mods)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_object(&actor, "mod.create", &entry.mod_id, None, Some(&entry))
+        .await;
 
     Ok(Html(render_packages_page_full(
         &mods,
@@ -58,6 +69,8 @@ pub async fn add_mod(
 }
 
 pub async fn edit_mod(
+    State(state): State<AppState>,
+    actor: Actor,
     Path(mod_id): Path<String>,
     Form(form): Form<ModForm>,
 ) -> Result<Html<String>, (StatusCode, String)> {
@@ -76,26 +89,24 @@ pub async fn edit_mod(
         )));
     }
 
-    let updated = mods.iter_mut().any(|entry| {
-        if entry.mod_id == mod_id {
-            entry.name = form.name.trim().to_string();
-            true
-        } else {
-            false
-        }
-    });
-
-    if !updated {
+    let Some(entry) = mods.iter_mut().find(|entry| entry.mod_id == mod_id) else {
         return Ok(Html(render_packages_page_full(
             &mods,
             &packages,
             Some("Mod not found."),
         )));
-    }
+    };
+    let before = entry.clone();
+    entry.name = form.name.trim().to_string();
+    let after = entry.clone();
 
     save_mods(&mods)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_change(&actor, "mod.update", &mod_id, &before, &after)
+        .await;
 
     Ok(Html(render_packages_page_full(
         &mods,
@@ -105,6 +116,8 @@ pub async fn edit_mod(
 }
 
 pub async fn delete_mod(
+    State(state): State<AppState>,
+    actor: Actor,
     Path(mod_id): Path<String>,
 ) -> Result<Html<String>, (StatusCode, String)> {
     let mut mods = load_mods()
@@ -122,10 +135,15 @@ pub async fn delete_mod(
         )));
     }
 
+    let before = mods.iter().find(|entry| entry.mod_id == mod_id).cloned();
     mods.retain(|entry| entry.mod_id != mod_id);
     save_mods(&mods)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_object(&actor, "mod.delete", &mod_id, before.as_ref(), None)
+        .await;
 
     Ok(Html(render_packages_page_full(
         &mods,
@@ -135,6 +153,8 @@ pub async fn delete_mod(
 }
 
 pub async fn add_package(
+    State(state): State<AppState>,
+    actor: Actor,
     Form(form): Form<PackageCreateForm>,
 ) -> Result<Html<String>, (StatusCode, String)> {
     let mods = load_mods()
@@ -161,6 +181,10 @@ pub async fn add_package(
     save_packages(&packages)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_object(&actor, "package.create", &package.package_id, None, Some(&package))
+        .await;
 
     Ok(Html(render_package_edit_page_with_selection(
         &package,
@@ -170,6 +194,8 @@ pub async fn add_package(
 }
 
 pub async fn edit_package(
+    State(state): State<AppState>,
+    actor: Actor,
     Path(package_id): Path<String>,
     Form(form): Form<PackageForm>,
 ) -> Result<Html<String>, (StatusCode, String)> {
@@ -188,27 +214,25 @@ pub async fn edit_package(
         )));
     }
 
-    let updated = packages.iter_mut().any(|entry| {
-        if entry.package_id == package_id {
-            entry.name = form.name.trim().to_string();
-            entry.mod_ids = form.mod_ids.clone().unwrap_or_default();
-            true
-        } else {
-            false
-        }
-    });
-
-    if !updated {
+    let Some(entry) = packages.iter_mut().find(|entry| entry.package_id == package_id) else {
         return Ok(Html(render_packages_page_full(
             &mods,
             &packages,
             Some("Package not found."),
         )));
-    }
+    };
+    let before = entry.clone();
+    entry.name = form.name.trim().to_string();
+    entry.mod_ids = form.mod_ids.clone().unwrap_or_default();
+    let after = entry.clone();
 
     save_packages(&packages)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_change(&actor, "package.update", &package_id, &before, &after)
+        .await;
 
     Ok(Html(render_packages_page_full(
         &mods,
@@ -218,6 +242,8 @@ pub async fn edit_package(
 }
 
 pub async fn delete_package(
+    State(state): State<AppState>,
+    actor: Actor,
     Path(package_id): Path<String>,
 ) -> Result<Html<String>, (StatusCode, String)> {
     let mods = load_mods()
@@ -226,10 +252,15 @@ pub async fn delete_package(
     let mut packages = load_packages()
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let before = packages.iter().find(|entry| entry.package_id == package_id).cloned();
     packages.retain(|entry| entry.package_id != package_id);
     save_packages(&packages)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_object(&actor, "package.delete", &package_id, before.as_ref(), None)
+        .await;
 
     Ok(Html(render_packages_page_full(
         &mods,
@@ -282,7 +313,7 @@ pub async fn package_edit_page(
     )))
 }
 
-fn new_package_id() -> String {
+pub(crate) fn new_package_id() -> String {
     let nanos = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .map(|duration| duration.as_nanos())
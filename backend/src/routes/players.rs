@@ -0,0 +1,357 @@
+use crate::forms::{PlayerBanRequest, PlayerBroadcastRequest, PlayerKickRequest, RunProfileQuery};
+use crate::routes::rcon::{rcon_endpoint, running_profile_client};
+use crate::routes::run::profile_id_or_active;
+use crate::routes::AppState;
+use crate::security::audit::Actor;
+use crate::services::current_epoch_seconds;
+use crate::views::players::render_players_page;
+use axum::{
+    Form, Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::Html,
+};
+use backend::models::BanRecord;
+use backend::rcon::{
+    add_ban_command, ban_command, kick_command, parse_players, say_command, RconPlayer,
+};
+use backend::storage::{list_profiles, load_bans, load_settings, save_bans};
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+const BAN_SYNC_INTERVAL: Duration = Duration::from_secs(10);
+const BAN_SYNC_ATTEMPTS: u32 = 30;
+
+#[derive(serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct PlayersOverview {
+    pub profile_id: String,
+    pub running: bool,
+    pub players: Vec<RconPlayer>,
+    pub bans: Vec<BanRecord>,
+    pub rcon_error: Option<String>,
+}
+
+#[derive(serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct PlayerActionResponse {
+    profile_id: String,
+    message: String,
+}
+
+pub async fn players_page(
+    State(state): State<AppState>,
+    Query(query): Query<RunProfileQuery>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    render_page(&state, query.profile_id, None).await
+}
+
+pub async fn players_api(
+    State(state): State<AppState>,
+    Query(query): Query<RunProfileQuery>,
+) -> Result<Json<PlayersOverview>, (StatusCode, String)> {
+    let settings = load_settings(&state.settings_path)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let profile_id = profile_id_or_active(&settings, query.profile_id)?;
+    Ok(Json(players_overview(&state, &profile_id).await?))
+}
+
+pub async fn kick_player_api(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(request): Json<PlayerKickRequest>,
+) -> Result<Json<PlayerActionResponse>, (StatusCode, String)> {
+    let (profile_id, message) = kick_player(&state, request, &actor).await?;
+    Ok(Json(PlayerActionResponse { profile_id, message }))
+}
+
+pub async fn ban_player_api(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(request): Json<PlayerBanRequest>,
+) -> Result<Json<PlayerActionResponse>, (StatusCode, String)> {
+    let (profile_id, message) = ban_player(&state, request, &actor).await?;
+    Ok(Json(PlayerActionResponse { profile_id, message }))
+}
+
+pub async fn broadcast_api(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(request): Json<PlayerBroadcastRequest>,
+) -> Result<Json<PlayerActionResponse>, (StatusCode, String)> {
+    let (profile_id, message) = broadcast(&state, request, &actor).await?;
+    Ok(Json(PlayerActionResponse { profile_id, message }))
+}
+
+pub async fn kick_player_action(
+    State(state): State<AppState>,
+    actor: Actor,
+    Form(form): Form<PlayerKickRequest>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let requested = form.profile_id.clone();
+    let message = match kick_player(&state, form, &actor).await {
+        Ok((_, message)) => message,
+        Err((_, message)) => message,
+    };
+    render_page(&state, requested, Some(&message)).await
+}
+
+pub async fn ban_player_action(
+    State(state): State<AppState>,
+    actor: Actor,
+    Form(form): Form<PlayerBanRequest>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let requested = form.profile_id.clone();
+    let message = match ban_player(&state, form, &actor).await {
+        Ok((_, message)) => message,
+        Err((_, message)) => message,
+    };
+    render_page(&state, requested, Some(&message)).await
+}
+
+pub async fn broadcast_action(
+    State(state): State<AppState>,
+    actor: Actor,
+    Form(form): Form<PlayerBroadcastRequest>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let requested = form.profile_id.clone();
+    let message = match broadcast(&state, form, &actor).await {
+        Ok((_, message)) => message,
+        Err((_, message)) => message,
+    };
+    render_page(&state, requested, Some(&message)).await
+}
+
+async fn render_page(
+    state: &AppState,
+    requested: Option<String>,
+    message: Option<&str>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let profiles = list_profiles()
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let settings = load_settings(&state.settings_path)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let overview = match profile_id_or_active(&settings, requested) {
+        Ok(profile_id) => Some(players_overview(state, &profile_id).await?),
+        Err(_) => None,
+    };
+    Ok(Html(render_players_page(&profiles, overview.as_ref(), message)))
+}
+
+async fn players_overview(
+    state: &AppState,
+    profile_id: &str,
+) -> Result<PlayersOverview, (StatusCode, String)> {
+    let now = current_epoch_seconds();
+    let bans = load_bans(profile_id)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?
+        .into_iter()
+        .filter(|ban| ban.is_active(now))
+        .collect();
+    let running = state.run_manager.status(profile_id).await.running;
+    let (players, rcon_error) = if running {
+        match list_players(state, profile_id).await {
+            Ok(players) => (players, None),
+            Err((_, message)) => (Vec::new(), Some(message)),
+        }
+    } else {
+        (Vec::new(), None)
+    };
+    Ok(PlayersOverview {
+        profile_id: profile_id.to_string(),
+        running,
+        players,
+        bans,
+        rcon_error,
+    })
+}
+
+async fn list_players(
+    state: &AppState,
+    profile_id: &str,
+) -> Result<Vec<RconPlayer>, (StatusCode, String)> {
+    let (_, client) = running_profile_client(state, Some(profile_id.to_string())).await?;
+    let response = client
+        .command("players")
+        .await
+        .map_err(|message| (StatusCode::BAD_GATEWAY, message))?;
+    Ok(parse_players(&response))
+}
+
+async fn kick_player(
+    state: &AppState,
+    request: PlayerKickRequest,
+    actor: &Actor,
+) -> Result<(String, String), (StatusCode, String)> {
+    let (profile_id, client) = running_profile_client(state, request.profile_id).await?;
+    client
+        .command(&kick_command(request.player_id, &request.reason))
+        .await
+        .map_err(|message| (StatusCode::BAD_GATEWAY, message))?;
+    state
+        .audit
+        .record(
+            actor,
+            "player.kick",
+            &profile_id,
+            None,
+            Some(json!({ "player_id": request.player_id, "reason": request.reason.trim() })),
+        )
+        .await;
+    Ok((profile_id, format!("Player #{} kicked.", request.player_id)))
+}
+
+async fn ban_player(
+    state: &AppState,
+    request: PlayerBanRequest,
+    actor: &Actor,
+) -> Result<(String, String), (StatusCode, String)> {
+    let (profile_id, client) = running_profile_client(state, request.profile_id).await?;
+    let response = client
+        .command("players")
+        .await
+        .map_err(|message| (StatusCode::BAD_GATEWAY, message))?;
+    let player = parse_players(&response)
+        .into_iter()
+        .find(|player| player.id == request.player_id)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("player #{} is not connected", request.player_id),
+            )
+        })?;
+
+    client
+        .command(&ban_command(
+            request.player_id,
+            request.duration_minutes,
+            &request.reason,
+        ))
+        .await
+        .map_err(|message| (StatusCode::BAD_GATEWAY, message))?;
+
+    let now = current_epoch_seconds();
+    let mut bans = load_bans(&profile_id)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    bans.retain(|ban| ban.guid != player.guid && ban.is_active(now));
+    bans.push(BanRecord {
+        guid: player.guid.clone(),
+        name: player.name.clone(),
+        reason: request.reason.trim().to_string(),
+        banned_at: now,
+        expires_at: (request.duration_minutes > 0)
+            .then(|| now + request.duration_minutes * 60),
+        banned_by: Some(actor.username.clone()),
+    });
+    save_bans(&profile_id, &bans)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record(
+            actor,
+            "player.ban",
+            &profile_id,
+            None,
+            Some(json!({
+                "guid": player.guid,
+                "name": player.name,
+                "duration_minutes": request.duration_minutes,
+                "reason": request.reason.trim(),
+            })),
+        )
+        .await;
+
+    let duration = if request.duration_minutes == 0 {
+        "permanently".to_string()
+    } else {
+        format!("for {} minutes", request.duration_minutes)
+    };
+    Ok((profile_id, format!("{} banned {duration}.", player.name)))
+}
+
+async fn broadcast(
+    state: &AppState,
+    request: PlayerBroadcastRequest,
+    actor: &Actor,
+) -> Result<(String, String), (StatusCode, String)> {
+    if request.message.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "message must not be empty".to_string()));
+    }
+    let (profile_id, client) = running_profile_client(state, request.profile_id).await?;
+    client
+        .command(&say_command(&request.message))
+        .await
+        .map_err(|message| (StatusCode::BAD_GATEWAY, message))?;
+    state
+        .audit
+        .record(
+            actor,
+            "player.broadcast",
+            &profile_id,
+            None,
+            Some(json!({ "message": request.message.trim() })),
+        )
+        .await;
+    Ok((profile_id, "Message sent.".to_string()))
+}
+
+/// Re-applies the ban list after every server launch, so watchdog restarts keep bans too.
+pub(crate) fn spawn_ban_sync_on_start(state: AppState) {
+    let mut starts = state.run_manager.subscribe_starts();
+    tokio::spawn(async move {
+        loop {
+            match starts.recv().await {
+                Ok(profile_id) => spawn_ban_sync(state.clone(), profile_id),
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("missed {skipped} server start events for ban sync");
+                }
+                Err(RecvError::Closed) => return,
+            }
+        }
+    });
+}
+
+/// Re-applies ARSSM's ban list once RCON is reachable after a start.
+fn spawn_ban_sync(state: AppState, profile_id: String) {
+    tokio::spawn(async move {
+        for _ in 0..BAN_SYNC_ATTEMPTS {
+            tokio::time::sleep(BAN_SYNC_INTERVAL).await;
+            if !state.run_manager.status(&profile_id).await.running {
+                return;
+            }
+            match sync_bans(&state, &profile_id).await {
+                Ok(()) => return,
+                Err(err) => tracing::debug!(profile_id = %profile_id, "ban sync pending: {err}"),
+            }
+        }
+        tracing::warn!(profile_id = %profile_id, "could not restore bans via RCON");
+    });
+}
+
+async fn sync_bans(state: &AppState, profile_id: &str) -> Result<(), String> {
+    let now = current_epoch_seconds();
+    let bans: Vec<BanRecord> = load_bans(profile_id)
+        .await?
+        .into_iter()
+        .filter(|ban| ban.is_active(now))
+        .collect();
+    if bans.is_empty() {
+        return Ok(());
+    }
+
+    let settings = load_settings(&state.settings_path).await?;
+    let endpoint = rcon_endpoint(&settings, profile_id).await?;
+    let client = state.rcon.client(profile_id, &endpoint).await?;
+    let existing = client.command("bans").await?;
+    for ban in bans.iter().filter(|ban| !existing.contains(&ban.guid)) {
+        client
+            .command(&add_ban_command(&ban.guid, ban.remaining_minutes(now), &ban.reason))
+            .await?;
+    }
+    Ok(())
+}
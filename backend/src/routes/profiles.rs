@@ -1,22 +1,26 @@
 use crate::forms::{
-    EditProfileForm, NewProfileCreateForm, NewProfileResolveForm, OptionalPackagesForm,
-    ProfilePathsForm, ProfileTabQuery, WorkshopSaveForm,
+    AcceptUpgradesForm, EditProfileForm, ModVersionPinForm, NewProfileCreateForm,
+    NewProfileResolveForm, OptionalPackagesForm, ProfilePathsForm, ProfileTabQuery,
+    WorkshopSaveForm,
 };
 use crate::routes::AppState;
+use crate::security::audit::Actor;
 use crate::services::{
-    effective_path_value, generate_config_for_profile, normalize_optional_path, parse_mod_ids,
-    parse_scenario_ids, update_list_selection,
+    accept_upgrades, collect_optional_mod_ids, effective_path_value, generate_config_for_profile,
+    mod_version_statuses, normalize_optional_path, parse_mod_id_input, parse_mod_ids,
+    parse_scenario_ids, pin_resolved_versions, update_list_selection,
 };
 use crate::views::profiles::{
     render_config_preview, render_config_preview_partial, render_new_profile_resolve,
-    render_new_profile_wizard, render_profile_detail, render_profile_edit, render_profiles_page,
-    render_workshop_page, render_workshop_panel,
+    render_new_profile_wizard, render_profile_detail, render_profile_edit, render_profile_versions,
+    render_profiles_page, render_workshop_page, render_workshop_panel,
 };
 use axum::{Form, extract::{Path, State}, http::{HeaderMap, StatusCode}, response::Html};
-use backend::models::ServerProfile;
+use backend::models::{apply_mod_metadata, ModVersionStatus, RestartPolicy, ServerProfile};
+use backend::workshop::{extract_workshop_id_from_url, profile_resolve_hash, result_resolve_hash};
 use backend::storage::{
-    delete_profile, generated_config_path, load_packages, load_profile, load_settings,
-    list_profiles, save_profile, save_settings, settings_path,
+    delete_profile, generated_config_path, load_mods, load_packages, load_profile, load_settings,
+    list_profiles, save_mods, save_profile, save_settings, settings_path,
 };
 
 pub async fn profiles_page(
@@ -76,6 +80,7 @@ pub async fn edit_profile_page(
 
 pub async fn save_profile_edit(
     State(state): State<AppState>,
+    actor: Actor,
     Path(profile_id): Path<String>,
     Form(form): Form<EditProfileForm>,
 ) -> Result<Html<String>, (StatusCode, String)> {
@@ -99,15 +104,26 @@ pub async fn save_profile_edit(
         )));
     }
 
+    let before = profile.clone();
     profile.display_name = form.display_name.trim().to_string();
     profile.workshop_url = form.workshop_url.trim().to_string();
     profile.selected_scenario_id_path = normalize_optional_path(&form.selected_scenario_id_path.unwrap_or_default());
     profile.optional_package_ids = form.optional_package_ids.clone().unwrap_or_default();
     profile.optional_mod_ids = parse_mod_ids(form.optional_mod_ids.as_deref().unwrap_or(""));
+    if let Some(policy) = form.restart_policy.as_deref().and_then(RestartPolicy::parse) {
+        profile.restart_policy = policy;
+    }
+    profile.update_before_start = form.update_before_start.is_some();
+    profile.pin_mod_versions = form.pin_mod_versions.is_some();
+    pin_resolved_versions(&mut profile);
 
     save_profile(&profile)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_change(&actor, "profile.update", &profile_id, &before, &profile)
+        .await;
 
     Ok(Html(render_profile_edit(
         &profile,
@@ -119,6 +135,8 @@ pub async fn save_profile_edit(
 }
 
 pub async fn update_profile_optional_packages(
+    State(state): State<AppState>,
+    actor: Actor,
     Path(profile_id): Path<String>,
     Form(form): Form<OptionalPackagesForm>,
 ) -> Result<Html<String>, (StatusCode, String)> {
@@ -132,6 +150,7 @@ pub async fn update_profile_optional_packages(
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
 
+    let before = profile.clone();
     profile.optional_package_ids = update_list_selection(
         form.optional_package_ids,
         &form.action,
@@ -141,6 +160,10 @@ pub async fn update_profile_optional_packages(
     save_profile(&profile)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_change(&actor, "profile.update", &profile_id, &before, &profile)
+        .await;
 
     Ok(Html(render_profile_edit(
         &profile,
@@ -152,11 +175,23 @@ pub async fn update_profile_optional_packages(
 }
 
 pub async fn delete_profile_action(
+    State(state): State<AppState>,
+    actor: Actor,
     Path(profile_id): Path<String>,
 ) -> Result<Html<String>, (StatusCode, String)> {
+    let before = load_profile(&profile_id).await.ok();
+    state
+        .run_manager
+        .remove(&profile_id)
+        .await
+        .map_err(|message| (StatusCode::CONFLICT, message))?;
     delete_profile(&profile_id)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_object(&actor, "profile.delete", &profile_id, before.as_ref(), None)
+        .await;
     let profiles = list_profiles()
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
@@ -172,6 +207,7 @@ pub async fn delete_profile_action(
 
 pub async fn save_profile_paths(
     State(state): State<AppState>,
+    actor: Actor,
     Path(profile_id): Path<String>,
     Form(form): Form<ProfilePathsForm>,
 ) -> Result<Html<String>, (StatusCode, String)> {
@@ -185,14 +221,20 @@ pub async fn save_profile_paths(
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
 
+    let before = profile.clone();
     profile.steamcmd_dir_override = normalize_optional_path(&form.steamcmd_dir_override);
     profile.reforger_server_exe_override = normalize_optional_path(&form.reforger_server_exe_override);
     profile.reforger_server_work_dir_override = normalize_optional_path(&form.reforger_server_work_dir_override);
     profile.profile_dir_base_override = normalize_optional_path(&form.profile_dir_base_override);
+    profile.steam_branch_override = normalize_optional_path(&form.steam_branch_override);
 
     save_profile(&profile)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_change(&actor, "profile.update", &profile_id, &before, &profile)
+        .await;
 
     Ok(Html(render_profile_edit(
         &profile,
@@ -204,6 +246,8 @@ pub async fn save_profile_paths(
 }
 
 pub async fn save_profile_overrides(
+    State(state): State<AppState>,
+    actor: Actor,
     Path(profile_id): Path<String>,
     Form(form): Form<std::collections::HashMap<String, String>>,
 ) -> Result<Html<String>, (StatusCode, String)> {
@@ -229,12 +273,17 @@ pub async fn save_profile_overrides(
             )));
         }
     };
+    let before = profile.clone();
     profile.server_json_overrides = overrides;
     profile.server_json_override_enabled = enabled;
 
     save_profile(&profile)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_change(&actor, "profile.overrides", &profile_id, &before, &profile)
+        .await;
 
     Ok(Html(render_profile_edit(
         &profile,
@@ -272,6 +321,8 @@ pub async fn new_profile_resolve(
 }
 
 pub async fn new_profile_create(
+    State(state): State<AppState>,
+    actor: Actor,
     Form(form): Form<NewProfileCreateForm>,
 ) -> Result<Html<String>, (StatusCode, String)> {
     if form.display_name.trim().is_empty() {
@@ -293,7 +344,7 @@ pub async fn new_profile_create(
         .map(parse_mod_ids)
         .unwrap_or_default();
 
-    let profile = ServerProfile {
+    let mut profile = ServerProfile {
         profile_id: new_profile_id(),
         display_name: form.display_name.trim().to_string(),
         workshop_url: form.workshop_url.trim().to_string(),
@@ -307,26 +358,42 @@ pub async fn new_profile_create(
         optional_mod_ids,
         optional_package_ids: Vec::new(),
         load_session_save: false,
+        restart_policy: RestartPolicy::Never,
+        update_before_start: false,
         steamcmd_dir_override: None,
         reforger_server_exe_override: None,
         reforger_server_work_dir_override: None,
         profile_dir_base_override: None,
+        steam_branch_override: None,
         server_json_overrides: serde_json::json!({}),
         server_json_override_enabled: std::collections::HashMap::new(),
         generated_config_path: None,
         last_resolved_at: Some(now_timestamp()),
         last_resolve_hash: None,
+        mod_metadata: form
+            .mod_metadata
+            .as_deref()
+            .and_then(|value| serde_json::from_str(value).ok())
+            .unwrap_or_default(),
+        pin_mod_versions: false,
+        mod_version_pins: Default::default(),
     };
+    profile.last_resolve_hash = profile_resolve_hash(&profile);
 
     save_profile(&profile)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_object(&actor, "profile.create", &profile.profile_id, None, Some(&profile))
+        .await;
 
     Ok(Html(render_profile_detail(&profile, None)))
 }
 
 pub async fn activate_profile(
     State(state): State<AppState>,
+    actor: Actor,
     Path(profile_id): Path<String>,
 ) -> Result<Html<String>, (StatusCode, String)> {
     let profiles = list_profiles()
@@ -335,12 +402,17 @@ pub async fn activate_profile(
     let mut settings = load_settings(&state.settings_path)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let before = settings.clone();
     if profiles.iter().any(|profile| profile.profile_id == profile_id) {
         settings.active_profile_id = Some(profile_id.clone());
     }
     save_settings(&state.settings_path, &settings)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_change(&actor, "profile.activate", &profile_id, &before, &settings)
+        .await;
     Ok(Html(render_profiles_page(
         &profiles,
         settings.active_profile_id.as_deref(),
@@ -359,6 +431,7 @@ pub async fn profile_workshop_page(
 
 pub async fn profile_workshop_resolve(
     State(state): State<AppState>,
+    actor: Actor,
     headers: HeaderMap,
     Path(profile_id): Path<String>,
 ) -> Result<Html<String>, (StatusCode, String)> {
@@ -374,9 +447,14 @@ pub async fn profile_workshop_resolve(
         )));
     }
 
+    let before = profile.clone();
     let result = resolve_and_update_profile(&state, &mut profile)
         .await
         .map_err(|message| (StatusCode::BAD_GATEWAY, message))?;
+    state
+        .audit
+        .record_change(&actor, "profile.resolve", &profile_id, &before, &profile)
+        .await;
 
     if is_hx_request(&headers) {
         return Ok(Html(render_workshop_panel(
@@ -390,16 +468,23 @@ pub async fn profile_workshop_resolve(
 }
 
 pub async fn profile_workshop_save(
+    State(state): State<AppState>,
+    actor: Actor,
     Path(profile_id): Path<String>,
     Form(form): Form<WorkshopSaveForm>,
 ) -> Result<Html<String>, (StatusCode, String)> {
     let mut profile = load_profile(&profile_id)
         .await
         .map_err(|message| (StatusCode::NOT_FOUND, message))?;
+    let before = profile.clone();
     profile.selected_scenario_id_path = normalize_optional_path(&form.selected_scenario_id_path);
     save_profile(&profile)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_change(&actor, "profile.update", &profile_id, &before, &profile)
+        .await;
     Ok(Html(render_workshop_page(
         &profile,
         None,
@@ -407,6 +492,149 @@ pub async fn profile_workshop_save(
     )))
 }
 
+pub async fn profile_versions_page(
+    Path(profile_id): Path<String>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let profile = load_profile(&profile_id)
+        .await
+        .map_err(|message| (StatusCode::NOT_FOUND, message))?;
+    versions_page(&profile, None).await
+}
+
+pub async fn profile_versions_check(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(profile_id): Path<String>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let mut profile = load_profile(&profile_id)
+        .await
+        .map_err(|message| (StatusCode::NOT_FOUND, message))?;
+    let before = profile.clone();
+    let notice = match resolve_and_update_profile(&state, &mut profile).await {
+        Ok(result) => {
+            state
+                .audit
+                .record_change(&actor, "profile.resolve", &profile_id, &before, &profile)
+                .await;
+            if result.errors.is_empty() {
+                "Latest versions resolved.".to_string()
+            } else {
+                "Latest versions resolved with warnings.".to_string()
+            }
+        }
+        Err(err) => format!("Resolve failed: {err}"),
+    };
+    versions_page(&profile, Some(&notice)).await
+}
+
+pub async fn profile_version_pin(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(profile_id): Path<String>,
+    Form(form): Form<ModVersionPinForm>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let mut profile = load_profile(&profile_id)
+        .await
+        .map_err(|message| (StatusCode::NOT_FOUND, message))?;
+    let mod_id = parse_mod_id_input(&form.mod_id)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("invalid mod ID {}", form.mod_id)))?;
+    let before = profile.clone();
+    let version = form.version.trim();
+    let notice = if version.is_empty() {
+        profile.mod_version_pins.remove(&mod_id);
+        format!("Pin of {mod_id} removed.")
+    } else {
+        profile.mod_version_pins.insert(mod_id.clone(), version.to_string());
+        format!("{mod_id} pinned to {version}.")
+    };
+    save_profile(&profile)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_change(&actor, "profile.version_pin", &profile_id, &before, &profile)
+        .await;
+    versions_page(&profile, Some(&notice)).await
+}
+
+pub async fn profile_versions_accept(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(profile_id): Path<String>,
+    Form(form): Form<AcceptUpgradesForm>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let mut profile = load_profile(&profile_id)
+        .await
+        .map_err(|message| (StatusCode::NOT_FOUND, message))?;
+    let mod_ids: Vec<String> = form.mod_id.into_iter().collect();
+    let notice = match accept_profile_upgrades(&state, &actor, &mut profile, &mod_ids).await {
+        Ok((accepted, written)) if written.resolve_result.errors.is_empty() => format!(
+            "Accepted {} upgrade(s); server.json written to {}.",
+            accepted.len(),
+            written.path.display()
+        ),
+        Ok((accepted, written)) => format!(
+            "Accepted {} upgrade(s); server.json written to {} with resolve warnings.",
+            accepted.len(),
+            written.path.display()
+        ),
+        Err((_, message)) => message,
+    };
+    versions_page(&profile, Some(&notice)).await
+}
+
+async fn versions_page(
+    profile: &ServerProfile,
+    notice: Option<&str>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let statuses = profile_version_statuses(profile)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    Ok(Html(render_profile_versions(profile, &statuses, notice)))
+}
+
+pub(crate) async fn profile_version_statuses(
+    profile: &ServerProfile,
+) -> Result<Vec<ModVersionStatus>, String> {
+    let packages = load_packages().await?;
+    let mods = load_mods().await?;
+    Ok(mod_version_statuses(profile, &packages, &mods))
+}
+
+/// Moves the pins of `mod_ids` (all pending ones when empty) to their latest
+/// resolved version, then rewrites the profile's server.json. The pins stay
+/// saved when writing the config fails.
+pub(crate) async fn accept_profile_upgrades(
+    state: &AppState,
+    actor: &Actor,
+    profile: &mut ServerProfile,
+    mod_ids: &[String],
+) -> Result<(Vec<String>, WrittenConfig), (StatusCode, String)> {
+    let statuses = profile_version_statuses(profile)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let before = profile.clone();
+    let accepted = accept_upgrades(profile, &statuses, mod_ids);
+    if accepted.is_empty() {
+        return Err((StatusCode::CONFLICT, "No pending upgrades to accept.".to_string()));
+    }
+    save_profile(profile)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_change(actor, "profile.upgrade_accept", &profile.profile_id, &before, profile)
+        .await;
+
+    let written = write_profile_config(state, profile).await.map_err(|(status, message)| {
+        (
+            status,
+            format!("Upgrades accepted, but server.json was not written: {message}"),
+        )
+    })?;
+    Ok((accepted, written))
+}
+
 pub async fn config_preview_page(
     State(state): State<AppState>,
     Path(profile_id): Path<String>,
@@ -421,7 +649,10 @@ pub async fn config_preview_page(
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
 
-    let preview = match generate_config_for_profile(&profile, &settings, &packages) {
+    let mods = load_mods()
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let preview = match generate_config_for_profile(&profile, &settings, &packages, &mods) {
         Ok(value) => serde_json::to_string_pretty(&value)
             .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?,
         Err(err) => err,
@@ -454,7 +685,10 @@ pub async fn config_preview_partial(
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
 
-    let preview = match generate_config_for_profile(&profile, &settings, &packages) {
+    let mods = load_mods()
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let preview = match generate_config_for_profile(&profile, &settings, &packages, &mods) {
         Ok(value) => serde_json::to_string_pretty(&value)
             .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?,
         Err(err) => err,
@@ -470,51 +704,28 @@ pub async fn config_preview_partial(
 
 pub async fn write_config(
     State(state): State<AppState>,
+    actor: Actor,
     Path(profile_id): Path<String>,
 ) -> Result<Html<String>, (StatusCode, String)> {
     let mut profile = load_profile(&profile_id)
         .await
         .map_err(|message| (StatusCode::NOT_FOUND, message))?;
 
-    let resolve_result = resolve_and_update_profile(&state, &mut profile)
-        .await
-        .map_err(|message| (StatusCode::BAD_GATEWAY, message))?;
-
-    if let Err(message) = validate_selected_scenario(&profile, &resolve_result.scenarios) {
-        return Err((StatusCode::BAD_REQUEST, message));
-    }
-
-    let settings = load_settings(&state.settings_path)
-        .await
-        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
-    let packages = load_packages()
-        .await
-        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
-    let config = generate_config_for_profile(&profile, &settings, &packages)
-        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
-    let config_json = serde_json::to_string_pretty(&config)
-        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
-
-    let server_work_dir = effective_path_value(
-        &profile.reforger_server_work_dir_override,
-        &settings.reforger_server_work_dir,
-    );
-    let path = generated_config_path(&server_work_dir, &profile.profile_id);
-    if let Some(parent) = path.parent() {
-        tokio::fs::create_dir_all(parent)
-            .await
-            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
-    }
-    tokio::fs::write(&path, &config_json)
-        .await
+    let written = write_profile_config(&state, &mut profile).await?;
+    state
+        .audit
+        .record(
+            &actor,
+            "profile.config_write",
+            &profile_id,
+            None,
+            Some(serde_json::json!({ "path": written.path.to_string_lossy() })),
+        )
+        .await;
+    let config_json = serde_json::to_string_pretty(&written.config)
         .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
 
-    profile.generated_config_path = Some(path.to_string_lossy().to_string());
-    save_profile(&profile)
-        .await
-        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
-
-    let notice = if resolve_result.errors.is_empty() {
+    let notice = if written.resolve_result.errors.is_empty() {
         "Config written successfully."
     } else {
         "Config written with resolve warnings."
@@ -535,7 +746,7 @@ pub async fn regenerate_config(
         .await
         .map_err(|message| (StatusCode::NOT_FOUND, message))?;
 
-    let resolve_result = resolve_and_update_profile(&state, &mut profile)
+    let resolve_result = resolve_for_config(&state, &mut profile)
         .await
         .map_err(|message| (StatusCode::BAD_GATEWAY, message))?;
 
@@ -558,7 +769,10 @@ pub async fn regenerate_config(
     let packages = load_packages()
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
-    let preview = match generate_config_for_profile(&profile, &settings, &packages) {
+    let mods = load_mods()
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let preview = match generate_config_for_profile(&profile, &settings, &packages, &mods) {
         Ok(value) => serde_json::to_string_pretty(&value)
             .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?,
         Err(err) => err,
@@ -567,6 +781,65 @@ pub async fn regenerate_config(
     Ok(Html(render_config_preview(&profile, &preview, notice)))
 }
 
+pub(crate) struct WrittenConfig {
+    pub path: std::path::PathBuf,
+    pub config: serde_json::Value,
+    pub resolve_result: backend::workshop::WorkshopResolveResult,
+}
+
+/// Resolves the profile, generates its server config and writes it into the
+/// server work dir, remembering the path on the profile.
+pub(crate) async fn write_profile_config(
+    state: &AppState,
+    profile: &mut ServerProfile,
+) -> Result<WrittenConfig, (StatusCode, String)> {
+    let resolve_result = resolve_for_config(state, profile)
+        .await
+        .map_err(|message| (StatusCode::BAD_GATEWAY, message))?;
+
+    if let Err(message) = validate_selected_scenario(profile, &resolve_result.scenarios) {
+        return Err((StatusCode::CONFLICT, message));
+    }
+
+    let settings = load_settings(&state.settings_path)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let packages = load_packages()
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let mods = load_mods()
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let config = generate_config_for_profile(profile, &settings, &packages, &mods)
+        .map_err(|message| (StatusCode::CONFLICT, message))?;
+    let config_json = serde_json::to_string_pretty(&config)
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+
+    let server_work_dir = effective_path_value(
+        &profile.reforger_server_work_dir_override,
+        &settings.reforger_server_work_dir,
+    );
+    let path = generated_config_path(&server_work_dir, &profile.profile_id);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    }
+    tokio::fs::write(&path, &config_json)
+        .await
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+
+    profile.generated_config_path = Some(path.to_string_lossy().to_string());
+    save_profile(profile)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    Ok(WrittenConfig {
+        path,
+        config,
+        resolve_result,
+    })
+}
+
 fn is_hx_request(headers: &HeaderMap) -> bool {
     headers
         .get("HX-Request")
@@ -589,7 +862,7 @@ fn validate_selected_scenario(profile: &ServerProfile, scenarios: &[String]) ->
     Ok(())
 }
 
-async fn resolve_and_update_profile(
+pub(crate) async fn resolve_and_update_profile(
     state: &AppState,
     profile: &mut ServerProfile,
 ) -> Result<backend::workshop::WorkshopResolveResult, String> {
@@ -598,15 +871,80 @@ async fn resolve_and_update_profile(
     }
 
     let result = state.workshop_resolver.resolve(&profile.workshop_url, 5).await?;
+    apply_resolve_result(profile, &result);
+
+    let packages = load_packages().await?;
+    let mut optional_ids = collect_optional_mod_ids(profile, &packages);
+    optional_ids.retain(|mod_id| *mod_id != result.root_id && !result.dependency_ids.contains(mod_id));
+    optional_ids.sort();
+    optional_ids.dedup();
+    let (optional_metadata, errors) = state.workshop_resolver.fetch_metadata(&optional_ids).await;
+    for err in errors {
+        tracing::warn!(profile_id = %profile.profile_id, "optional mod details unavailable: {err}");
+    }
+    profile.mod_metadata.extend(optional_metadata);
+    pin_resolved_versions(profile);
+    save_profile(profile).await?;
+
+    let mut mods = load_mods().await?;
+    if apply_mod_metadata(&mut mods, &profile.mod_metadata) {
+        save_mods(&mods).await?;
+    }
+    Ok(result)
+}
+
+/// Resolves the profile before generating its config. When the workshop cannot be
+/// reached and nothing is cached, the last resolve stored on the profile is used so
+/// configs can still be written; the failure is reported as a resolve warning.
+async fn resolve_for_config(
+    state: &AppState,
+    profile: &mut ServerProfile,
+) -> Result<backend::workshop::WorkshopResolveResult, String> {
+    let err = match resolve_and_update_profile(state, profile).await {
+        Ok(result) => return Ok(result),
+        Err(err) => err,
+    };
+    let Some(root_id) = profile.root_mod_id.clone() else {
+        return Err(err);
+    };
+    if extract_workshop_id_from_url(&profile.workshop_url).as_deref() != Some(root_id.as_str()) {
+        return Err(err);
+    }
+    tracing::warn!(profile_id = %profile.profile_id, "workshop resolve failed, using the last resolve: {err}");
+    Ok(backend::workshop::WorkshopResolveResult {
+        root_id,
+        root_url: profile.workshop_url.clone(),
+        scenarios: profile.scenarios.clone(),
+        dependency_ids: profile.dependency_mod_ids.clone(),
+        errors: vec![format!("workshop unavailable, using the last resolve: {err}")],
+        metadata: profile.mod_metadata.clone(),
+    })
+}
+
+pub(crate) fn apply_resolve_result(
+    profile: &mut ServerProfile,
+    result: &backend::workshop::WorkshopResolveResult,
+) {
+    // Optional mods are not part of the workshop resolve; keep what was fetched for them.
+    let mut mod_metadata = result.metadata.clone();
+    for (mod_id, metadata) in profile.mod_metadata.iter() {
+        let was_resolved = profile.root_mod_id.as_ref() == Some(mod_id)
+            || profile.dependency_mod_ids.contains(mod_id);
+        if !was_resolved {
+            mod_metadata.entry(mod_id.clone()).or_insert_with(|| metadata.clone());
+        }
+    }
+
     profile.root_mod_id = Some(result.root_id.clone());
     profile.dependency_mod_ids = result.dependency_ids.clone();
     profile.scenarios = result.scenarios.clone();
     profile.last_resolved_at = Some(now_timestamp());
-    save_profile(profile).await?;
-    Ok(result)
+    profile.last_resolve_hash = Some(result_resolve_hash(result));
+    profile.mod_metadata = mod_metadata;
+    pin_resolved_versions(profile);
 }
 
-fn new_profile_id() -> String {
+pub(crate) fn new_profile_id() -> String {
     let nanos = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .map(|duration| duration.as_nanos())
@@ -0,0 +1,97 @@
+use crate::forms::{RconCommandRequest, RunProfileQuery};
+use crate::routes::run::profile_id_or_active;
+use crate::routes::AppState;
+use crate::services::effective_path_value;
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+use axum::response::sse::{Event, Sse};
+use backend::rcon::{RconClient, RconEndpoint};
+use backend::storage::{generated_config_path, load_profile, load_settings};
+use tokio_stream::{StreamExt, wrappers::BroadcastStream};
+
+#[derive(serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct RconCommandResponse {
+    profile_id: String,
+    response: String,
+}
+
+pub async fn rcon_command(
+    State(state): State<AppState>,
+    Json(request): Json<RconCommandRequest>,
+) -> Result<Json<RconCommandResponse>, (StatusCode, String)> {
+    let command = request.command.trim();
+    if command.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "command must not be empty".to_string()));
+    }
+    let (profile_id, client) = running_profile_client(&state, request.profile_id).await?;
+    let response = client
+        .command(command)
+        .await
+        .map_err(|message| (StatusCode::BAD_GATEWAY, message))?;
+    Ok(Json(RconCommandResponse {
+        profile_id,
+        response,
+    }))
+}
+
+pub async fn rcon_messages_stream(
+    State(state): State<AppState>,
+    Query(query): Query<RunProfileQuery>,
+) -> Result<
+    Sse<impl tokio_stream::Stream<Item = Result<Event, std::convert::Infallible>>>,
+    (StatusCode, String),
+> {
+    let (_, client) = running_profile_client(&state, query.profile_id).await?;
+    let stream = BroadcastStream::new(client.subscribe())
+        .filter_map(|message| message.ok())
+        .map(|line| Ok(Event::default().data(line)));
+    Ok(Sse::new(stream))
+}
+
+pub(crate) async fn running_profile_client(
+    state: &AppState,
+    requested: Option<String>,
+) -> Result<(String, RconClient), (StatusCode, String)> {
+    let settings = load_settings(&state.settings_path)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let profile_id = profile_id_or_active(&settings, requested)?;
+    if !state.run_manager.status(&profile_id).await.running {
+        return Err((StatusCode::CONFLICT, "server is not running".to_string()));
+    }
+    let endpoint = rcon_endpoint(&settings, &profile_id)
+        .await
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+    let client = state
+        .rcon
+        .client(&profile_id, &endpoint)
+        .await
+        .map_err(|message| (StatusCode::BAD_GATEWAY, message))?;
+    Ok((profile_id, client))
+}
+
+pub(crate) async fn rcon_endpoint(
+    settings: &backend::storage::AppSettings,
+    profile_id: &str,
+) -> Result<RconEndpoint, String> {
+    let profile = load_profile(profile_id).await?;
+    let config_path = match profile.generated_config_path.as_deref() {
+        Some(path) if !path.trim().is_empty() => std::path::PathBuf::from(path),
+        _ => {
+            let work_dir = effective_path_value(
+                &profile.reforger_server_work_dir_override,
+                &settings.reforger_server_work_dir,
+            );
+            generated_config_path(&work_dir, &profile.profile_id)
+        }
+    };
+    let contents = tokio::fs::read_to_string(&config_path)
+        .await
+        .map_err(|err| format!("failed to read {}: {err}", config_path.display()))?;
+    let config: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|err| format!("invalid server config {}: {err}", config_path.display()))?;
+    RconEndpoint::from_server_config(&config)
+}
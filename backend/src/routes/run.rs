@@ -1,38 +1,91 @@
-use crate::forms::RunStartRequest;
+use crate::forms::{RunProfileQuery, RunStartRequest};
 use crate::routes::AppState;
-use crate::services::{effective_path_value, generate_config_for_profile};
+use crate::security::audit::Actor;
+use crate::services::{effective_path_value, generate_config_for_profile, profile_branch};
 use crate::views::run::render_run_logs_page;
 use axum::{
     Json,
-    extract::State,
+    extract::{Query, State},
     http::StatusCode,
     response::Html,
 };
 use axum::response::sse::{Event, Sse};
-use backend::runner::RunStatus;
+use crate::routes::profiles::apply_resolve_result;
+use backend::models::ServerProfile;
+use backend::runner::{RunStatus, StopOutcome};
 use backend::storage::{
-    generated_config_path, list_profiles, load_packages, load_profile, load_settings, save_profile,
+    generated_config_path, list_profiles, load_mods, load_packages, load_profile, load_settings,
+    profile_path, save_profile, DependencyChangeAction,
+};
+use backend::workshop::{
+    diff_resolve_result, profile_resolve_hash, result_resolve_hash, DependencyDiff,
 };
 use std::path::PathBuf;
 use tokio_stream::{StreamExt, wrappers::BroadcastStream};
 
-pub async fn run_logs_page() -> Result<Html<String>, (StatusCode, String)> {
+pub async fn run_logs_page(
+    State(state): State<AppState>,
+) -> Result<Html<String>, (StatusCode, String)> {
     let profiles = list_profiles()
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
-    Ok(Html(render_run_logs_page(&profiles)))
+    let settings = load_settings(&state.settings_path)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    Ok(Html(render_run_logs_page(
+        &profiles,
+        settings.active_profile_id.as_deref(),
+    )))
 }
 
 pub async fn run_status(
     State(state): State<AppState>,
+    Query(query): Query<RunProfileQuery>,
 ) -> Result<Json<RunStatus>, (StatusCode, String)> {
-    Ok(Json(state.run_manager.status().await))
+    let profile_id = requested_profile_id(&state, query.profile_id).await?;
+    Ok(Json(state.run_manager.status(&profile_id).await))
+}
+
+pub async fn run_instances(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<RunStatus>>, (StatusCode, String)> {
+    Ok(Json(state.run_manager.statuses().await))
+}
+
+#[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct StartReport {
+    pub dependency_changes: Option<DependencyDiff>,
+    pub warnings: Vec<String>,
+}
+
+impl StartReport {
+    pub fn notice(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(diff) = self.dependency_changes.as_ref() {
+            parts.push(format!("Dependencies changed since last resolve: {}", diff.summary()));
+        }
+        parts.extend(self.warnings.iter().cloned());
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(" "))
+        }
+    }
+}
+
+#[derive(serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct RunStartResponse {
+    #[serde(flatten)]
+    status: RunStatus,
+    #[serde(flatten)]
+    report: StartReport,
 }
 
 pub async fn run_start(
     State(state): State<AppState>,
+    actor: Actor,
     Json(request): Json<RunStartRequest>,
-) -> Result<Json<RunStatus>, (StatusCode, String)> {
+) -> Result<Json<RunStartResponse>, (StatusCode, String)> {
     let settings = load_settings(&state.settings_path)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
@@ -41,75 +94,152 @@ pub async fn run_start(
         return Err((StatusCode::BAD_REQUEST, message));
     }
 
-    let profile_id = match request.profile_id.clone().filter(|value| !value.trim().is_empty()) {
-        Some(value) => value,
-        None => settings
-            .active_profile_id
-            .clone()
-            .ok_or_else(|| (StatusCode::BAD_REQUEST, "active profile not set".to_string()))?,
-    };
+    let profile_id = profile_id_or_active(&settings, request.profile_id)?;
 
-    start_profile(&state, &settings, &profile_id)
+    let report = start_profile(&state, &settings, &profile_id)
         .await
         .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+    state.audit.record(&actor, "run.start", &profile_id, None, None).await;
 
-    Ok(Json(state.run_manager.status().await))
+    Ok(Json(RunStartResponse {
+        status: state.run_manager.status(&profile_id).await,
+        report,
+    }))
+}
+
+#[derive(serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct RunStopResponse {
+    #[serde(flatten)]
+    status: RunStatus,
+    stop_outcome: StopOutcome,
 }
 
 pub async fn run_stop(
     State(state): State<AppState>,
-) -> Result<Json<RunStatus>, (StatusCode, String)> {
-    state
-        .run_manager
-        .stop()
+    actor: Actor,
+    Query(query): Query<RunProfileQuery>,
+) -> Result<Json<RunStopResponse>, (StatusCode, String)> {
+    let settings = load_settings(&state.settings_path)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let profile_id = profile_id_or_active(&settings, query.profile_id)?;
+    let stop_outcome = stop_profile(&state, &settings, &profile_id)
         .await
         .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
-    Ok(Json(state.run_manager.status().await))
+    state
+        .audit
+        .record(
+            &actor,
+            "run.stop",
+            &profile_id,
+            None,
+            Some(serde_json::json!({ "outcome": stop_outcome.describe() })),
+        )
+        .await;
+    Ok(Json(RunStopResponse {
+        status: state.run_manager.status(&profile_id).await,
+        stop_outcome,
+    }))
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, schemars::JsonSchema)]
 pub(crate) struct LogTailResponse {
     lines: Vec<String>,
 }
 
 pub async fn run_logs_tail(
     State(state): State<AppState>,
-    axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
 ) -> Result<Json<LogTailResponse>, (StatusCode, String)> {
     let limit = params
         .get("n")
         .and_then(|value| value.parse::<usize>().ok())
         .unwrap_or(200);
-    let lines = state.run_manager.tail_persisted(limit).await;
+    let profile_id = requested_profile_id(&state, params.get("profile_id").cloned()).await?;
+    let lines = state.run_manager.tail_persisted(&profile_id, limit).await;
     Ok(Json(LogTailResponse { lines }))
 }
 
 pub async fn run_logs_stream(
     State(state): State<AppState>,
-) -> Sse<impl tokio_stream::Stream<Item = Result<Event, std::convert::Infallible>>> {
-    let receiver = state.run_manager.subscribe();
+    Query(query): Query<RunProfileQuery>,
+) -> Result<
+    Sse<impl tokio_stream::Stream<Item = Result<Event, std::convert::Infallible>>>,
+    (StatusCode, String),
+> {
+    let profile_id = requested_profile_id(&state, query.profile_id).await?;
+    let receiver = state.run_manager.subscribe(&profile_id);
     let stream = BroadcastStream::new(receiver)
         .filter_map(|message| message.ok())
         .map(|line| Ok(Event::default().data(line)));
-    Sse::new(stream)
+    Ok(Sse::new(stream))
+}
+
+pub(crate) async fn requested_profile_id(
+    state: &AppState,
+    requested: Option<String>,
+) -> Result<String, (StatusCode, String)> {
+    let settings = load_settings(&state.settings_path)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let profile_id = profile_id_or_active(&settings, requested)?;
+    if tokio::fs::metadata(profile_path(&profile_id)).await.is_err() {
+        return Err((StatusCode::NOT_FOUND, format!("profile {profile_id} not found")));
+    }
+    Ok(profile_id)
+}
+
+pub(crate) fn profile_id_or_active(
+    settings: &backend::storage::AppSettings,
+    requested: Option<String>,
+) -> Result<String, (StatusCode, String)> {
+    match requested.filter(|value| !value.trim().is_empty()) {
+        Some(value) => Ok(value.trim().to_string()),
+        None => settings
+            .active_profile_id
+            .clone()
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, "active profile not set".to_string())),
+    }
 }
 
 pub(crate) async fn start_profile(
     state: &AppState,
     settings: &backend::storage::AppSettings,
     profile_id: &str,
-) -> Result<(), String> {
+) -> Result<StartReport, String> {
+    let status = state.run_manager.status(profile_id).await;
+    if status.running || status.restarting {
+        return Err(format!("server already running for profile {profile_id}"));
+    }
     let mut profile = load_profile(profile_id).await?;
+    let mut report = StartReport::default();
+    let dependencies_updated =
+        check_dependency_changes(state, settings, &mut profile, &mut report).await?;
 
     let server_work_dir = effective_path_value(
         &profile.reforger_server_work_dir_override,
         &settings.reforger_server_work_dir,
     );
+    // Held until the server is launched, so no manual update can start on this install meanwhile.
+    let _work_dir_guard = state.steamcmd.lock_work_dir(&server_work_dir).await;
+    if state.steamcmd.is_updating(&server_work_dir).await {
+        return Err(format!(
+            "a SteamCMD update is running for {server_work_dir}; wait for it to finish"
+        ));
+    }
+    if profile.update_before_start {
+        let steamcmd_dir =
+            effective_path_value(&profile.steamcmd_dir_override, &settings.steamcmd_dir);
+        let branch = profile_branch(&profile, settings);
+        update_before_start(state, profile_id, &steamcmd_dir, &server_work_dir, branch.as_deref())
+            .await?;
+    }
     let config_path = generated_config_path(&server_work_dir, &profile.profile_id);
 
-    if tokio::fs::metadata(&config_path).await.is_err() {
+    if dependencies_updated || tokio::fs::metadata(&config_path).await.is_err() {
         let packages = load_packages().await?;
-        let config_value = generate_config_for_profile(&profile, settings, &packages)?;
+        let mods = load_mods().await?;
+        let config_value = generate_config_for_profile(&profile, settings, &packages, &mods)?;
         let config_json = serde_json::to_string_pretty(&config_value)
             .map_err(|err| format!("failed to serialize config: {err}"))?;
         if let Some(parent) = config_path.parent() {
@@ -133,9 +263,114 @@ pub(crate) async fn start_profile(
     state
         .run_manager
         .start(&server_exe, &server_work_dir, &profile, &config_path, &profile_dir)
+        .await?;
+    Ok(report)
+}
+
+/// Runs SteamCMD for the work dir and mirrors its output into the log of the run
+/// it precedes. The caller holds the work dir lock.
+async fn update_before_start(
+    state: &AppState,
+    profile_id: &str,
+    steamcmd_dir: &str,
+    work_dir: &str,
+    branch: Option<&str>,
+) -> Result<(), String> {
+    state.run_manager.begin_run(profile_id).await?;
+    let mut job =
+        crate::routes::settings::start_steamcmd_update_locked(state, steamcmd_dir, work_dir, branch)
+            .await
+            .map_err(|err| format!("update before start failed: {err}"))?;
+    let result = loop {
+        tokio::select! {
+            Some(line) = job.output.recv() => {
+                state.run_manager.log_line(profile_id, line).await;
+            }
+            result = &mut job.result => {
+                while let Ok(line) = job.output.try_recv() {
+                    state.run_manager.log_line(profile_id, line).await;
+                }
+                break result.map_err(|err| format!("SteamCMD update task failed: {err}"))?;
+            }
+        }
+    };
+    if result.success {
+        Ok(())
+    } else {
+        Err(format!("update before start failed: {}; server not started", result.message))
+    }
+}
+
+pub(crate) async fn stop_profile(
+    state: &AppState,
+    settings: &backend::storage::AppSettings,
+    profile_id: &str,
+) -> Result<StopOutcome, String> {
+    state.rcon.disconnect(profile_id).await;
+    state
+        .run_manager
+        .stop(profile_id, settings.stop_grace_period())
         .await
 }
 
+async fn check_dependency_changes(
+    state: &AppState,
+    settings: &backend::storage::AppSettings,
+    profile: &mut ServerProfile,
+    report: &mut StartReport,
+) -> Result<bool, String> {
+    if profile.workshop_url.trim().is_empty() {
+        return Ok(false);
+    }
+
+    let result = match state.workshop_resolver.resolve(&profile.workshop_url, 5).await {
+        Ok(result) => result,
+        Err(err) => {
+            tracing::warn!(profile_id = %profile.profile_id, "dependency check skipped: {err}");
+            report
+                .warnings
+                .push(format!("Dependency check skipped: {err}"));
+            return Ok(false);
+        }
+    };
+
+    let current_hash = result_resolve_hash(&result);
+    let stored_hash = profile
+        .last_resolve_hash
+        .clone()
+        .or_else(|| profile_resolve_hash(profile));
+    if stored_hash.as_deref() == Some(current_hash.as_str()) {
+        return Ok(false);
+    }
+
+    if stored_hash.is_some() {
+        let diff = diff_resolve_result(profile, &result);
+        if !diff.is_empty() {
+            if settings.dependency_change_action == DependencyChangeAction::Block {
+                return Err(format!(
+                    "dependencies changed since last resolve ({}); re-resolve the workshop before starting",
+                    diff.summary()
+                ));
+            }
+            if let Some(selected) = profile.selected_scenario_id_path.as_deref() {
+                if diff.vanished_scenarios.iter().any(|value| value == selected) {
+                    return Err(format!("selected scenario no longer available: {selected}"));
+                }
+            }
+            tracing::warn!(
+                profile_id = %profile.profile_id,
+                "dependencies changed since last resolve: {}",
+                diff.summary()
+            );
+            report.dependency_changes = Some(diff);
+        }
+    }
+
+    apply_resolve_result(profile, &result);
+    save_profile(profile).await?;
+    Ok(true)
+}
+
 pub(crate) async fn active_profile_name(profile_id: Option<&str>) -> Option<String> {
     let profile_id = profile_id?;
     load_profile(profile_id).await.ok().map(|profile| profile.display_name)
@@ -1,10 +1,17 @@
-use crate::forms::{SettingsForm, SettingsQuery};
+use crate::forms::{RunProfileQuery, SettingsForm, SettingsQuery};
 use crate::routes::AppState;
+use crate::security::audit::Actor;
+use crate::security::tls::{describe_certificate, CertificateSummary};
 use crate::views::settings::render_settings_page;
-use crate::services::clean_path_input;
-use axum::{Form, Json, extract::State, http::StatusCode, response::Html};
+use crate::services::{clean_path_input, effective_path_value, profile_branch, work_dir_branches};
+use axum::{Form, Json, extract::{Query, State}, http::StatusCode, response::Html};
+use axum::response::sse::{Event, Sse};
 use backend::defaults::parse_defaults_form;
-use backend::storage::{AppSettings, load_settings, save_settings};
+use backend::steamcmd::{branch_conflicts, normalize_branch, parse_progress, SteamcmdStatus, UpdateJob};
+use backend::storage::{
+    AppSettings, DependencyChangeAction, LoginLimits, WebServerSettings, list_profiles, load_profile, load_settings, paths_equal, save_settings,
+};
+use tokio_stream::{StreamExt, wrappers::BroadcastStream};
 
 pub async fn settings_page(
     State(state): State<AppState>,
@@ -16,6 +23,7 @@ pub async fn settings_page(
     apply_default_server_json(&mut settings);
     Ok(Html(render_settings_page(
         &settings,
+        certificate_summary(&state).await.as_ref(),
         query.tab.as_deref(),
         None,
     )))
@@ -23,11 +31,13 @@ pub async fn settings_page(
 
 pub async fn settings_save(
     State(state): State<AppState>,
+    actor: Actor,
     Form(form): Form<SettingsForm>,
 ) -> Result<Html<String>, (StatusCode, String)> {
     let existing = load_settings(&state.settings_path)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let before = existing.clone();
     let mut settings = AppSettings {
         steamcmd_dir: clean_path_input(&form.steamcmd_dir),
         reforger_server_exe: clean_path_input(&form.reforger_server_exe),
@@ -36,6 +46,53 @@ pub async fn settings_save(
         active_profile_id: existing.active_profile_id,
         server_json_defaults: existing.server_json_defaults,
         server_json_enabled: existing.server_json_enabled,
+        dependency_change_action: form
+            .dependency_change_action
+            .as_deref()
+            .and_then(DependencyChangeAction::parse)
+            .unwrap_or(existing.dependency_change_action),
+        stop_grace_period_secs: form
+            .stop_grace_period_secs
+            .as_deref()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(existing.stop_grace_period_secs),
+        steam_branch: form
+            .steam_branch
+            .as_deref()
+            .map(|value| value.trim().to_string())
+            .unwrap_or(existing.steam_branch),
+        login_limits: LoginLimits {
+            max_failures: parse_number(&form.login_max_failures)
+                .unwrap_or(existing.login_limits.max_failures),
+            window_secs: parse_number(&form.login_window_secs)
+                .unwrap_or(existing.login_limits.window_secs),
+            lockout_secs: parse_number(&form.login_lockout_secs)
+                .unwrap_or(existing.login_limits.lockout_secs),
+            base_delay_ms: parse_number(&form.login_base_delay_ms)
+                .unwrap_or(existing.login_limits.base_delay_ms),
+            max_delay_ms: parse_number(&form.login_max_delay_ms)
+                .unwrap_or(existing.login_limits.max_delay_ms),
+        },
+        web_server: WebServerSettings {
+            bind_address: form
+                .web_bind_address
+                .as_deref()
+                .map(|value| value.trim().to_string())
+                .unwrap_or(existing.web_server.bind_address),
+            port: parse_number(&form.web_port).unwrap_or(existing.web_server.port),
+            tls_cert_path: form
+                .web_tls_cert_path
+                .as_deref()
+                .map(clean_path_input)
+                .unwrap_or(existing.web_server.tls_cert_path),
+            tls_key_path: form
+                .web_tls_key_path
+                .as_deref()
+                .map(clean_path_input)
+                .unwrap_or(existing.web_server.tls_key_path),
+            plain_http: form.web_plain_http.is_some(),
+        },
+        workshop_cache: existing.workshop_cache,
     };
 
     apply_default_server_json(&mut settings);
@@ -43,6 +100,7 @@ pub async fn settings_save(
     if let Err(message) = settings.validate() {
         return Ok(Html(render_settings_page(
             &settings,
+            certificate_summary(&state).await.as_ref(),
             Some("paths"),
             Some(&message),
         )));
@@ -51,9 +109,15 @@ pub async fn settings_save(
     save_settings(&state.settings_path, &settings)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state.login_throttle.set_limits(settings.login_limits);
+    state
+        .audit
+        .record_change(&actor, "settings.update", "settings", &before, &settings)
+        .await;
 
     Ok(Html(render_settings_page(
         &settings,
+        certificate_summary(&state).await.as_ref(),
         Some("paths"),
         Some("Settings saved."),
     )))
@@ -61,6 +125,7 @@ pub async fn settings_save(
 
 pub async fn settings_defaults_save(
     State(state): State<AppState>,
+    actor: Actor,
     Form(form): Form<std::collections::HashMap<String, String>>,
 ) -> Result<Html<String>, (StatusCode, String)> {
     let mut settings = load_settings(&state.settings_path)
@@ -73,20 +138,27 @@ pub async fn settings_defaults_save(
         Err(err) => {
             return Ok(Html(render_settings_page(
                 &settings,
+                certificate_summary(&state).await.as_ref(),
                 Some("defaults"),
                 Some(&err),
             )))
         }
     };
+    let before = settings.clone();
     settings.server_json_defaults = defaults;
     settings.server_json_enabled = enabled;
 
     save_settings(&state.settings_path, &settings)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state
+        .audit
+        .record_change(&actor, "settings.defaults", "server_json_defaults", &before, &settings)
+        .await;
 
     Ok(Html(render_settings_page(
         &settings,
+        certificate_summary(&state).await.as_ref(),
         Some("defaults"),
         Some("Defaults saved."),
     )))
@@ -103,28 +175,167 @@ pub async fn get_settings_api(
 
 pub async fn save_settings_api(
     State(state): State<AppState>,
+    actor: Actor,
     Json(settings): Json<AppSettings>,
 ) -> Result<Json<AppSettings>, (StatusCode, String)> {
     if let Err(message) = settings.validate() {
         return Err((StatusCode::BAD_REQUEST, message));
     }
+    let existing = load_settings(&state.settings_path)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
 
     save_settings(&state.settings_path, &settings)
         .await
         .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state.login_throttle.set_limits(settings.login_limits);
+    state.workshop_cache.set_settings(settings.workshop_cache);
+    state
+        .audit
+        .record_change(&actor, "settings.update", "settings", &existing, &settings)
+        .await;
 
     Ok(Json(settings))
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, schemars::JsonSchema)]
 pub struct SteamcmdUpdateResponse {
     pub message: String,
+    pub warnings: Vec<String>,
+    #[serde(flatten)]
+    pub status: SteamcmdStatus,
+}
+
+pub async fn steamcmd_update(
+    State(state): State<AppState>,
+    actor: Actor,
+    Query(query): Query<RunProfileQuery>,
+) -> Result<Json<SteamcmdUpdateResponse>, (StatusCode, String)> {
+    let settings = load_settings(&state.settings_path)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let (steamcmd_dir, work_dir, branch) = match query.profile_id.filter(|value| !value.trim().is_empty()) {
+        Some(profile_id) => {
+            let profile = load_profile(profile_id.trim())
+                .await
+                .map_err(|message| (StatusCode::NOT_FOUND, message))?;
+            (
+                effective_path_value(&profile.steamcmd_dir_override, &settings.steamcmd_dir),
+                effective_path_value(
+                    &profile.reforger_server_work_dir_override,
+                    &settings.reforger_server_work_dir,
+                ),
+                profile_branch(&profile, &settings),
+            )
+        }
+        None => (
+            settings.steamcmd_dir.clone(),
+            settings.reforger_server_work_dir.clone(),
+            normalize_branch(&settings.steam_branch),
+        ),
+    };
+    if steamcmd_dir.trim().is_empty() || work_dir.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "steamcmd_dir and reforger_server_work_dir must be configured".to_string(),
+        ));
+    }
+
+    start_steamcmd_update(&state, &steamcmd_dir, &work_dir, branch.as_deref())
+        .await
+        .map_err(|message| (StatusCode::CONFLICT, message))?;
+    state
+        .audit
+        .record(
+            &actor,
+            "steamcmd.update",
+            &work_dir,
+            None,
+            Some(serde_json::json!({ "branch": branch.as_deref().unwrap_or("public") })),
+        )
+        .await;
+    let profiles = list_profiles()
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let warnings = branch_conflicts(&work_dir_branches(&profiles, &settings))
+        .iter()
+        .filter(|conflict| paths_equal(&conflict.work_dir, &work_dir))
+        .map(|conflict| conflict.describe())
+        .collect();
+    Ok(Json(SteamcmdUpdateResponse {
+        message: format!(
+            "SteamCMD update started for {work_dir} ({} branch).",
+            branch.as_deref().unwrap_or("public")
+        ),
+        warnings,
+        status: state.steamcmd.status().await,
+    }))
+}
+
+pub(crate) async fn start_steamcmd_update(
+    state: &AppState,
+    steamcmd_dir: &str,
+    work_dir: &str,
+    branch: Option<&str>,
+) -> Result<UpdateJob, String> {
+    let _work_dir_guard = state.steamcmd.lock_work_dir(work_dir).await;
+    start_steamcmd_update_locked(state, steamcmd_dir, work_dir, branch).await
+}
+
+/// Like [`start_steamcmd_update`], for callers that already hold the work dir lock.
+pub(crate) async fn start_steamcmd_update_locked(
+    state: &AppState,
+    steamcmd_dir: &str,
+    work_dir: &str,
+    branch: Option<&str>,
+) -> Result<UpdateJob, String> {
+    let running = state.run_manager.profiles_using_work_dir(work_dir).await;
+    if !running.is_empty() {
+        return Err(format!(
+            "server is running from this work dir (profiles: {}); stop it before updating",
+            running.join(", ")
+        ));
+    }
+    state.steamcmd.start(steamcmd_dir, work_dir, branch).await
+}
+
+pub async fn steamcmd_status(State(state): State<AppState>) -> Json<SteamcmdStatus> {
+    Json(state.steamcmd.status().await)
+}
+
+pub async fn steamcmd_stream(
+    State(state): State<AppState>,
+) -> Sse<impl tokio_stream::Stream<Item = Result<Event, std::convert::Infallible>>> {
+    let stream = BroadcastStream::new(state.steamcmd.subscribe())
+        .filter_map(|message| message.ok())
+        .map(|line| {
+            // Progress lines carry the parsed percentage alongside the raw line.
+            let event = match parse_progress(&line) {
+                Some(progress) => Event::default()
+                    .event("progress")
+                    .json_data(serde_json::json!({
+                        "state": progress.state,
+                        "percent": progress.percent,
+                        "line": line,
+                    }))
+                    .unwrap_or_else(|_| Event::default().data(line)),
+                None => Event::default().data(line),
+            };
+            Ok(event)
+        });
+    Sse::new(stream)
+}
+
+async fn certificate_summary(state: &AppState) -> Option<CertificateSummary> {
+    let path = state.tls_cert_path.as_ref()?;
+    describe_certificate(path)
+        .await
+        .map_err(|err| tracing::warn!("{err}"))
+        .ok()
 }
 
-pub async fn steamcmd_update() -> Json<SteamcmdUpdateResponse> {
-    Json(SteamcmdUpdateResponse {
-        message: "SteamCMD update placeholder executed.".to_string(),
-    })
+fn parse_number<T: std::str::FromStr>(value: &Option<String>) -> Option<T> {
+    value.as_deref().and_then(|value| value.trim().parse().ok())
 }
 
 fn apply_default_server_json(settings: &mut AppSettings) {
@@ -0,0 +1,98 @@
+use crate::forms::TokenCreateForm;
+use crate::routes::AppState;
+use crate::security::audit::Actor;
+use crate::security::tokens::TokenScope;
+use crate::services::current_epoch_seconds;
+use crate::views::tokens::render_tokens_page;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Html,
+    Form,
+};
+
+pub async fn tokens_page(State(state): State<AppState>) -> Result<Html<String>, (StatusCode, String)> {
+    render_page(&state, None, None).await
+}
+
+pub async fn create_token_action(
+    State(state): State<AppState>,
+    actor: Actor,
+    Form(form): Form<TokenCreateForm>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let scopes: Vec<TokenScope> = [
+        (form.scope_read.is_some(), TokenScope::Read),
+        (form.scope_run_control.is_some(), TokenScope::RunControl),
+        (form.scope_config_write.is_some(), TokenScope::ConfigWrite),
+    ]
+    .into_iter()
+    .filter_map(|(selected, scope)| selected.then_some(scope))
+    .collect();
+    let now = current_epoch_seconds();
+    let expires_at = match form.expires_days.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+        Some(days) => match days.parse::<u64>() {
+            Ok(days) if days > 0 => Some(now + days * 24 * 60 * 60),
+            _ => {
+                return render_page(&state, Some("Expiry must be a positive number of days."), None).await;
+            }
+        },
+        None => None,
+    };
+
+    match state
+        .tokens
+        .create(&form.name, scopes, expires_at, &actor.username, now)
+        .await
+    {
+        Ok((token, secret)) => {
+            tracing::info!(actor = %actor.username, "created API token {}", token.name);
+            let scopes: Vec<&str> = token.scopes.iter().map(|scope| scope.as_str()).collect();
+            state
+                .audit
+                .record(
+                    &actor,
+                    "token.create",
+                    &token.name,
+                    None,
+                    Some(serde_json::json!({ "id": token.id, "scopes": scopes, "expires_at": token.expires_at })),
+                )
+                .await;
+            let message = format!("Token {} created. Copy it now, it is shown only once.", token.name);
+            render_page(&state, Some(&message), Some(&secret)).await
+        }
+        Err(err) => render_page(&state, Some(&err), None).await,
+    }
+}
+
+pub async fn revoke_token_action(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(token_id): Path<String>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let message = match state.tokens.revoke(&token_id).await {
+        Ok(token) => {
+            tracing::info!(actor = %actor.username, "revoked API token {}", token.name);
+            state
+                .audit
+                .record(&actor, "token.revoke", &token.name, Some(serde_json::json!({ "id": token.id })), None)
+                .await;
+            format!("Token {} revoked.", token.name)
+        }
+        Err(err) => err,
+    };
+    render_page(&state, Some(&message), None).await
+}
+
+async fn render_page(
+    state: &AppState,
+    message: Option<&str>,
+    new_secret: Option<&str>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let tokens = state.tokens.list().await;
+    Ok(Html(render_tokens_page(
+        &tokens,
+        current_epoch_seconds(),
+        message,
+        new_secret,
+    )))
+}
@@ -0,0 +1,137 @@
+use crate::forms::{UserCreateForm, UserPasswordForm, UserRoleForm};
+use crate::routes::AppState;
+use crate::security::audit::Actor;
+use crate::security::users::Role;
+use crate::views::users::render_users_page;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Html,
+    Form,
+};
+
+pub async fn users_page(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> Result<Html<String>, (StatusCode, String)> {
+    render_page(&state, &actor, None).await
+}
+
+pub async fn add_user_action(
+    State(state): State<AppState>,
+    actor: Actor,
+    Form(form): Form<UserCreateForm>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let message = match parse_role(&form.role) {
+        Ok(role) => match state.users.add(&form.username, &form.password, role).await {
+            Ok(()) => {
+                tracing::info!(actor = %actor.username, "added user {} ({})", form.username.trim(), role.as_str());
+                state
+                    .audit
+                    .record(
+                        &actor,
+                        "user.create",
+                        form.username.trim(),
+                        None,
+                        Some(serde_json::json!({ "role": role.as_str() })),
+                    )
+                    .await;
+                format!("User {} added.", form.username.trim())
+            }
+            Err(err) => err,
+        },
+        Err(err) => err,
+    };
+    render_page(&state, &actor, Some(&message)).await
+}
+
+pub async fn set_role_action(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(username): Path<String>,
+    Form(form): Form<UserRoleForm>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let message = match parse_role(&form.role) {
+        Ok(_) if username == actor.username => "You cannot change your own role.".to_string(),
+        Ok(role) => match state.users.set_role(&username, role).await {
+            Ok(previous) => {
+                tracing::info!(actor = %actor.username, "set role of {username} to {}", role.as_str());
+                state
+                    .audit
+                    .record(
+                        &actor,
+                        "user.role",
+                        &username,
+                        Some(serde_json::json!({ "role": previous.as_str() })),
+                        Some(serde_json::json!({ "role": role.as_str() })),
+                    )
+                    .await;
+                format!("{username} is now {}.", role.as_str())
+            }
+            Err(err) => err,
+        },
+        Err(err) => err,
+    };
+    render_page(&state, &actor, Some(&message)).await
+}
+
+pub async fn set_password_action(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(username): Path<String>,
+    Form(form): Form<UserPasswordForm>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let message = match state.users.set_password(&username, &form.password).await {
+        Ok(()) => {
+            tracing::info!(actor = %actor.username, "changed password of {username}");
+            if let Err(err) = state.sessions.end_user_sessions(&username).await {
+                tracing::warn!("failed to end sessions of {username}: {err}");
+            }
+            state.audit.record(&actor, "user.password", &username, None, None).await;
+            format!("Password for {username} updated.")
+        }
+        Err(err) => err,
+    };
+    render_page(&state, &actor, Some(&message)).await
+}
+
+pub async fn delete_user_action(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(username): Path<String>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let message = if username == actor.username {
+        "You cannot delete your own account.".to_string()
+    } else {
+        match state.users.remove(&username).await {
+            Ok(()) => {
+                tracing::info!(actor = %actor.username, "deleted user {username}");
+                if let Err(err) = state.sessions.end_user_sessions(&username).await {
+                    tracing::warn!("failed to end sessions of {username}: {err}");
+                }
+                state.audit.record(&actor, "user.delete", &username, None, None).await;
+                format!("User {username} deleted.")
+            }
+            Err(err) => err,
+        }
+    };
+    render_page(&state, &actor, Some(&message)).await
+}
+
+fn parse_role(value: &str) -> Result<Role, String> {
+    Role::parse(value).ok_or_else(|| format!("unknown role: {value}"))
+}
+
+async fn render_page(
+    state: &AppState,
+    actor: &Actor,
+    message: Option<&str>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let users = state.users.list().await;
+    Ok(Html(render_users_page(
+        &users,
+        state.users.bootstrap_username(),
+        &actor.username,
+        message,
+    )))
+}
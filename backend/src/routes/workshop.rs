@@ -1,6 +1,11 @@
+use crate::forms::{WorkshopCacheForm, WorkshopCachePurgeForm, WorkshopCachePurgeQuery};
 use crate::routes::AppState;
-use axum::{Json, extract::State, http::StatusCode};
+use crate::security::audit::Actor;
+use crate::views::workshop::render_workshop_cache_page;
+use axum::{Form, Json, extract::{Query, State}, http::StatusCode, response::Html};
+use backend::storage::{load_settings, save_settings, WorkshopCacheSettings};
 use backend::workshop::WorkshopResolveRequest;
+use backend::workshop_cache::WorkshopCacheStatus;
 
 pub async fn resolve_workshop(
     State(state): State<AppState>,
@@ -19,3 +24,102 @@ pub async fn resolve_workshop(
 
     Ok(Json(result))
 }
+
+#[derive(serde::Serialize, schemars::JsonSchema)]
+pub struct WorkshopCachePurgeResponse {
+    pub removed: usize,
+}
+
+pub async fn workshop_cache_status(
+    State(state): State<AppState>,
+) -> Result<Json<WorkshopCacheStatus>, (StatusCode, String)> {
+    state
+        .workshop_cache
+        .status()
+        .await
+        .map(Json)
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))
+}
+
+pub async fn purge_workshop_cache_api(
+    State(state): State<AppState>,
+    actor: Actor,
+    Query(query): Query<WorkshopCachePurgeQuery>,
+) -> Result<Json<WorkshopCachePurgeResponse>, (StatusCode, String)> {
+    let removed = purge(&state, &actor, query.expired_only)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    Ok(Json(WorkshopCachePurgeResponse { removed }))
+}
+
+pub async fn workshop_cache_page(
+    State(state): State<AppState>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    render_page(&state, None).await
+}
+
+pub async fn workshop_cache_settings_action(
+    State(state): State<AppState>,
+    actor: Actor,
+    Form(form): Form<WorkshopCacheForm>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let Ok(ttl_secs) = form.ttl_secs.trim().parse::<u64>() else {
+        return render_page(&state, Some("TTL must be a whole number of seconds.")).await;
+    };
+    let mut settings = load_settings(&state.settings_path)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let before = settings.clone();
+    settings.workshop_cache = WorkshopCacheSettings {
+        ttl_secs,
+        offline: form.offline.is_some(),
+    };
+
+    save_settings(&state.settings_path, &settings)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    state.workshop_cache.set_settings(settings.workshop_cache);
+    state
+        .audit
+        .record_change(&actor, "settings.update", "settings", &before, &settings)
+        .await;
+
+    render_page(&state, Some("Cache settings saved.")).await
+}
+
+pub async fn purge_workshop_cache_action(
+    State(state): State<AppState>,
+    actor: Actor,
+    Form(form): Form<WorkshopCachePurgeForm>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let message = match purge(&state, &actor, form.scope == "expired").await {
+        Ok(1) => "Removed 1 cached page.".to_string(),
+        Ok(removed) => format!("Removed {removed} cached pages."),
+        Err(err) => err,
+    };
+    render_page(&state, Some(&message)).await
+}
+
+async fn purge(state: &AppState, actor: &Actor, expired_only: bool) -> Result<usize, String> {
+    let removed = state.workshop_cache.purge(expired_only).await?;
+    state
+        .audit
+        .record(
+            actor,
+            "workshop_cache.purge",
+            if expired_only { "expired" } else { "all" },
+            None,
+            Some(serde_json::json!({ "removed": removed })),
+        )
+        .await;
+    Ok(removed)
+}
+
+async fn render_page(state: &AppState, message: Option<&str>) -> Result<Html<String>, (StatusCode, String)> {
+    let status = state
+        .workshop_cache
+        .status()
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    Ok(Html(render_workshop_cache_page(&status, message)))
+}
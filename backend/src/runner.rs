@@ -1,88 +1,323 @@
-use crate::{models::ServerProfile, storage::logs_dir};
+use crate::{
+    a2s::{query_info, A2sEndpoint, A2sInfo},
+    models::{RestartPolicy, ServerProfile},
+    storage::{logs_dir, paths_equal},
+};
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
+    ffi::OsString,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
     process::{Child, Command},
-    sync::{broadcast, Mutex},
+    sync::{broadcast, oneshot, Mutex},
 };
 use std::io::{Read, Seek, SeekFrom};
 
 const MAX_LOG_LINES: usize = 500;
 
+struct StopRequest {
+    grace_period: Duration,
+    reply: oneshot::Sender<Result<StopOutcome, String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StopOutcome {
+    Graceful,
+    Killed,
+    RestartCancelled,
+}
+
+impl StopOutcome {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Graceful => "Server stopped gracefully.",
+            Self::Killed => "Server did not exit within the grace period and was killed.",
+            Self::RestartCancelled => "Pending automatic restart cancelled.",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct RunManager {
+    instances: Arc<std::sync::Mutex<HashMap<String, RunInstance>>>,
+    config: RunManagerConfig,
+    started: broadcast::Sender<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RunManagerConfig {
+    pub backoff: RestartBackoff,
+    pub logs_dir: PathBuf,
+    pub query_interval: Duration,
+    pub query_timeout: Duration,
+}
+
+impl Default for RunManagerConfig {
+    fn default() -> Self {
+        Self {
+            backoff: RestartBackoff::default(),
+            logs_dir: logs_dir(),
+            query_interval: Duration::from_secs(10),
+            query_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RestartBackoff {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub max_consecutive_restarts: u32,
+    pub stable_after: Duration,
+}
+
+impl Default for RestartBackoff {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(300),
+            max_consecutive_restarts: 5,
+            stable_after: Duration::from_secs(600),
+        }
+    }
+}
+
+impl RestartBackoff {
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+#[derive(Clone)]
+struct RunInstance {
+    profile_id: String,
     inner: Arc<Mutex<RunInner>>,
     sender: broadcast::Sender<String>,
+    started: broadcast::Sender<String>,
+    config: RunManagerConfig,
 }
 
 struct RunInner {
-    child: Option<Child>,
-    profile_id: Option<String>,
+    running: bool,
+    restarting: bool,
+    generation: u64,
+    stop_tx: Option<oneshot::Sender<StopRequest>>,
+    launch: Option<LaunchSpec>,
     pid: Option<u32>,
     started_at: Option<u64>,
+    restart_count: u32,
+    consecutive_restarts: u32,
+    last_exit: Option<ExitReason>,
+    server_info: Option<A2sInfo>,
     buffer: VecDeque<String>,
     log_path: Option<PathBuf>,
+    /// Set by `begin_run`; the next start keeps the buffer and log file it opened.
+    run_begun: bool,
+}
+
+#[derive(Clone)]
+struct LaunchSpec {
+    server_exe: String,
+    work_dir: String,
+    args: Vec<OsString>,
+    restart_policy: RestartPolicy,
+    query_endpoint: Option<A2sEndpoint>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct ExitReason {
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    pub requested: bool,
+    pub exited_at: u64,
+}
+
+impl ExitReason {
+    fn from_status(status: Option<std::process::ExitStatus>, requested: bool) -> Self {
+        let code = status.and_then(|status| status.code());
+        #[cfg(unix)]
+        let signal = {
+            use std::os::unix::process::ExitStatusExt;
+            status.and_then(|status| status.signal())
+        };
+        #[cfg(not(unix))]
+        let signal = None;
+        Self {
+            code,
+            signal,
+            requested,
+            exited_at: current_epoch_seconds(),
+        }
+    }
+
+    pub fn is_crash(&self) -> bool {
+        !self.requested && self.code != Some(0)
+    }
+
+    pub fn describe(&self) -> String {
+        if self.requested {
+            "stopped by ARSSM".to_string()
+        } else if let Some(code) = self.code {
+            format!("exit code {code}")
+        } else if let Some(signal) = self.signal {
+            format!("killed by signal {signal}")
+        } else {
+            "unknown exit".to_string()
+        }
+    }
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
 pub struct RunStatus {
     pub running: bool,
     pub pid: Option<u32>,
-    pub profile_id: Option<String>,
+    pub profile_id: String,
     pub started_at: Option<u64>,
+    pub restarting: bool,
+    pub restart_count: u32,
+    pub last_exit: Option<ExitReason>,
+    /// Last successful A2S_INFO answer; `None` until the server is joinable.
+    pub server_info: Option<A2sInfo>,
 }
 
-impl RunManager {
-    pub fn new() -> Self {
-        let (sender, _) = broadcast::channel(200);
-        let inner = RunInner {
-            child: None,
-            profile_id: None,
+impl RunStatus {
+    fn idle(profile_id: &str) -> Self {
+        Self {
+            running: false,
             pid: None,
+            profile_id: profile_id.to_string(),
             started_at: None,
-            buffer: VecDeque::new(),
-            log_path: None,
-        };
+            restarting: false,
+            restart_count: 0,
+            last_exit: None,
+            server_info: None,
+        }
+    }
+}
+
+impl Default for RunManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunManager {
+    pub fn new() -> Self {
+        Self::with_config(RunManagerConfig::default())
+    }
+
+    pub fn with_config(config: RunManagerConfig) -> Self {
+        let (started, _) = broadcast::channel(64);
         Self {
-            inner: Arc::new(Mutex::new(inner)),
-            sender,
+            instances: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            config,
+            started,
         }
     }
 
-    pub fn subscribe(&self) -> broadcast::Receiver<String> {
-        self.sender.subscribe()
+    fn instance(&self, profile_id: &str) -> RunInstance {
+        let mut instances = self.instances.lock().expect("run registry lock");
+        instances
+            .entry(profile_id.to_string())
+            .or_insert_with(|| {
+                RunInstance::new(profile_id, self.config.clone(), self.started.clone())
+            })
+            .clone()
     }
 
-    pub async fn status(&self) -> RunStatus {
-        let mut inner = self.inner.lock().await;
-        if let Some(child) = inner.child.as_mut() {
-            if let Ok(Some(_)) = child.try_wait() {
-                inner.child = None;
-                inner.profile_id = None;
-                inner.pid = None;
-            }
+    /// Looks up an instance without registering one, so reads never grow the registry.
+    fn find_instance(&self, profile_id: &str) -> Option<RunInstance> {
+        let instances = self.instances.lock().expect("run registry lock");
+        instances.get(profile_id).cloned()
+    }
+
+    fn known_instances(&self) -> Vec<RunInstance> {
+        let instances = self.instances.lock().expect("run registry lock");
+        let mut list: Vec<RunInstance> = instances.values().cloned().collect();
+        list.sort_by(|a, b| a.profile_id.cmp(&b.profile_id));
+        list
+    }
+
+    /// Yields the profile id each time a server process is launched, including watchdog restarts.
+    pub fn subscribe_starts(&self) -> broadcast::Receiver<String> {
+        self.started.subscribe()
+    }
+
+    /// Registers the profile so that lines logged before its server starts reach the stream.
+    pub fn subscribe(&self, profile_id: &str) -> broadcast::Receiver<String> {
+        self.instance(profile_id).sender.subscribe()
+    }
+
+    pub async fn status(&self, profile_id: &str) -> RunStatus {
+        match self.find_instance(profile_id) {
+            Some(instance) => instance.status().await,
+            None => RunStatus::idle(profile_id),
         }
-        RunStatus {
-            running: inner.child.is_some(),
-            pid: inner.pid,
-            profile_id: inner.profile_id.clone(),
-            started_at: inner.started_at,
+    }
+
+    pub async fn statuses(&self) -> Vec<RunStatus> {
+        let mut statuses = Vec::new();
+        for instance in self.known_instances() {
+            statuses.push(instance.status().await);
         }
+        statuses
     }
 
-    pub async fn tail(&self, limit: usize) -> Vec<String> {
-        let inner = self.inner.lock().await;
-        let start = inner.buffer.len().saturating_sub(limit);
-        inner.buffer.iter().skip(start).cloned().collect()
+    pub async fn running_profile_ids(&self) -> Vec<String> {
+        self.statuses()
+            .await
+            .into_iter()
+            .filter(|status| status.running)
+            .map(|status| status.profile_id)
+            .collect()
+    }
+
+    pub async fn profiles_using_work_dir(&self, work_dir: &str) -> Vec<String> {
+        let mut profile_ids = Vec::new();
+        for instance in self.known_instances() {
+            let inner = instance.inner.lock().await;
+            let uses_dir = inner
+                .launch
+                .as_ref()
+                .map(|launch| paths_equal(&launch.work_dir, work_dir))
+                .unwrap_or(false);
+            if uses_dir && (inner.running || inner.restarting) {
+                profile_ids.push(instance.profile_id.clone());
+            }
+        }
+        profile_ids
+    }
+
+    /// Opens a new run log before the server starts, so lines of pre-start tasks logged
+    /// with `log_line` stay in the tail and log file of the run that follows.
+    pub async fn begin_run(&self, profile_id: &str) -> Result<(), String> {
+        self.instance(profile_id).begin_run().await
+    }
+
+    /// Adds a line to the profile's log stream without a running server, e.g. for pre-start tasks.
+    pub async fn log_line(&self, profile_id: &str, line: String) {
+        self.instance(profile_id).push_line(line).await;
     }
 
-    pub async fn tail_persisted(&self, limit: usize) -> Vec<String> {
+    pub async fn tail(&self, profile_id: &str, limit: usize) -> Vec<String> {
+        match self.find_instance(profile_id) {
+            Some(instance) => instance.tail(limit).await,
+            None => Vec::new(),
+        }
+    }
+
+    pub async fn tail_persisted(&self, profile_id: &str, limit: usize) -> Vec<String> {
+        let Some(instance) = self.find_instance(profile_id) else {
+            return Vec::new();
+        };
         let path = {
-            let inner = self.inner.lock().await;
+            let inner = instance.inner.lock().await;
             inner.log_path.clone()
         };
 
@@ -92,7 +327,20 @@ impl RunManager {
             }
         }
 
-        self.tail(limit).await
+        instance.tail(limit).await
+    }
+
+    /// Forgets a deleted profile. Refused while its server runs or waits for a restart.
+    pub async fn remove(&self, profile_id: &str) -> Result<(), String> {
+        let Some(instance) = self.find_instance(profile_id) else {
+            return Ok(());
+        };
+        let inner = instance.inner.lock().await;
+        if inner.running || inner.restarting {
+            return Err(format!("profile {profile_id} is running; stop it first"));
+        }
+        self.instances.lock().expect("run registry lock").remove(profile_id);
+        Ok(())
     }
 
     pub async fn start(
@@ -103,79 +351,343 @@ impl RunManager {
         config_path: &Path,
         profile_dir: &Path,
     ) -> Result<(), String> {
+        let mut args = vec![
+            OsString::from("-config"),
+            config_path.as_os_str().to_os_string(),
+            OsString::from("-profile"),
+            profile_dir.as_os_str().to_os_string(),
+        ];
+        if profile.load_session_save {
+            args.push(OsString::from("-loadSessionSave"));
+        }
+        let query_endpoint = tokio::fs::read_to_string(config_path)
+            .await
+            .ok()
+            .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+            .and_then(|config| A2sEndpoint::from_server_config(&config).ok());
+        let launch = LaunchSpec {
+            server_exe: server_exe.to_string(),
+            work_dir: server_work_dir.to_string(),
+            args,
+            restart_policy: profile.restart_policy,
+            query_endpoint,
+        };
+        self.instance(&profile.profile_id).start(launch).await
+    }
+
+    pub async fn stop(&self, profile_id: &str, grace_period: Duration) -> Result<StopOutcome, String> {
+        match self.find_instance(profile_id) {
+            Some(instance) => instance.stop(grace_period).await,
+            None => Err("server is not running".to_string()),
+        }
+    }
+}
+
+impl RunInstance {
+    fn new(profile_id: &str, config: RunManagerConfig, started: broadcast::Sender<String>) -> Self {
+        let (sender, _) = broadcast::channel(200);
+        let inner = RunInner {
+            running: false,
+            restarting: false,
+            generation: 0,
+            stop_tx: None,
+            launch: None,
+            pid: None,
+            started_at: None,
+            restart_count: 0,
+            consecutive_restarts: 0,
+            last_exit: None,
+            server_info: None,
+            buffer: VecDeque::new(),
+            log_path: None,
+            run_begun: false,
+        };
+        Self {
+            profile_id: profile_id.to_string(),
+            inner: Arc::new(Mutex::new(inner)),
+            sender,
+            started,
+            config,
+        }
+    }
+
+    async fn status(&self) -> RunStatus {
+        let inner = self.inner.lock().await;
+        RunStatus {
+            running: inner.running,
+            pid: inner.pid,
+            profile_id: self.profile_id.clone(),
+            started_at: inner.started_at,
+            restarting: inner.restarting,
+            restart_count: inner.restart_count,
+            last_exit: inner.last_exit.clone(),
+            server_info: inner.server_info.clone(),
+        }
+    }
+
+    async fn tail(&self, limit: usize) -> Vec<String> {
+        let inner = self.inner.lock().await;
+        let start = inner.buffer.len().saturating_sub(limit);
+        inner.buffer.iter().skip(start).cloned().collect()
+    }
+
+    async fn start(&self, launch: LaunchSpec) -> Result<(), String> {
         let mut inner = self.inner.lock().await;
-        if inner.child.is_some() {
-            return Err("server already running".to_string());
+        if inner.running || inner.restarting {
+            return Err(format!("server already running for profile {}", self.profile_id));
         }
 
-        let mut command = Command::new(server_exe);
+        let child = self.spawn_child(&launch)?;
+        inner.generation += 1;
+        inner.restart_count = 0;
+        inner.consecutive_restarts = 0;
+        if !inner.run_begun {
+            self.open_run_log(&mut inner);
+        }
+        inner.run_begun = false;
+        let query_endpoint = launch.query_endpoint.clone();
+        inner.launch = Some(launch);
+        let stop_rx = attach_child(&mut inner, &child);
+        let generation = inner.generation;
+        drop(inner);
+
+        tokio::spawn(self.clone().supervise(child, generation, stop_rx));
+        if let Some(endpoint) = query_endpoint {
+            tokio::spawn(self.clone().poll_server_info(generation, endpoint));
+        }
+        Ok(())
+    }
+
+    async fn begin_run(&self) -> Result<(), String> {
+        let mut inner = self.inner.lock().await;
+        if inner.running || inner.restarting {
+            return Err(format!("server already running for profile {}", self.profile_id));
+        }
+        self.open_run_log(&mut inner);
+        inner.run_begun = true;
+        Ok(())
+    }
+
+    fn open_run_log(&self, inner: &mut RunInner) {
+        inner.buffer.clear();
+        inner.log_path = Some(log_file_path(&self.config.logs_dir, &self.profile_id));
+    }
+
+    async fn stop(&self, grace_period: Duration) -> Result<StopOutcome, String> {
+        let stop_tx = {
+            let mut inner = self.inner.lock().await;
+            inner.generation += 1;
+            if inner.restarting {
+                inner.restarting = false;
+                return Ok(StopOutcome::RestartCancelled);
+            }
+            inner.stop_tx.take()
+        };
+
+        let stop_tx = stop_tx.ok_or_else(|| "server is not running".to_string())?;
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let request = StopRequest {
+            grace_period,
+            reply: reply_tx,
+        };
+        stop_tx
+            .send(request)
+            .map_err(|_| "server is not running".to_string())?;
+        reply_rx
+            .await
+            .map_err(|_| "failed to stop server: supervisor exited".to_string())?
+    }
+
+    fn spawn_child(&self, launch: &LaunchSpec) -> Result<Child, String> {
+        let mut command = Command::new(&launch.server_exe);
         command
-            .current_dir(server_work_dir)
-            .arg("-config")
-            .arg(config_path)
-            .arg("-profile")
-            .arg(profile_dir)
+            .current_dir(&launch.work_dir)
+            .args(&launch.args)
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped());
 
-        if profile.load_session_save {
-            command.arg("-loadSessionSave");
-        }
-
         let mut child = command
             .spawn()
             .map_err(|err| format!("failed to start server: {err}"))?;
 
-        let stdout = child.stdout.take();
-        let stderr = child.stderr.take();
-        inner.pid = child.id();
-        inner.profile_id = Some(profile.profile_id.clone());
-        inner.started_at = Some(current_epoch_seconds());
-        inner.child = Some(child);
-        inner.buffer.clear();
-        inner.log_path = Some(log_file_path(profile.profile_id.as_str()));
-
-        if let Some(stdout) = stdout {
-            let manager = self.clone();
+        if let Some(stdout) = child.stdout.take() {
+            let instance = self.clone();
             tokio::spawn(async move {
                 let mut lines = BufReader::new(stdout).lines();
                 while let Ok(Some(line)) = lines.next_line().await {
-                    manager.push_line(line).await;
+                    instance.push_line(line).await;
                 }
             });
         }
 
-        if let Some(stderr) = stderr {
-            let manager = self.clone();
+        if let Some(stderr) = child.stderr.take() {
+            let instance = self.clone();
             tokio::spawn(async move {
                 let mut lines = BufReader::new(stderr).lines();
                 while let Ok(Some(line)) = lines.next_line().await {
-                    manager.push_line(line).await;
+                    instance.push_line(line).await;
                 }
             });
         }
 
-        Ok(())
+        let _ = self.started.send(self.profile_id.clone());
+        Ok(child)
     }
 
-    pub async fn stop(&self) -> Result<(), String> {
-        let mut child = {
+    async fn supervise(
+        self,
+        mut child: Child,
+        generation: u64,
+        mut stop_rx: oneshot::Receiver<StopRequest>,
+    ) {
+        loop {
+            let (status, reply, stop_result) = tokio::select! {
+                status = child.wait() => (status.ok(), None, Ok(StopOutcome::Graceful)),
+                request = &mut stop_rx => match request {
+                    Ok(request) => {
+                        self.push_line(format!(
+                            "[arssm] stopping server (grace period {}s)",
+                            request.grace_period.as_secs()
+                        ))
+                        .await;
+                        let result = terminate_gracefully(&mut child, request.grace_period).await;
+                        (child.wait().await.ok(), Some(request.reply), result)
+                    }
+                    Err(_) => (child.wait().await.ok(), None, Ok(StopOutcome::Graceful)),
+                },
+            };
+
+            let exit = ExitReason::from_status(status, reply.is_some());
+            let restart_delay = {
+                let mut inner = self.inner.lock().await;
+                let uptime = inner
+                    .started_at
+                    .map(|started| current_epoch_seconds().saturating_sub(started))
+                    .unwrap_or(0);
+                inner.running = false;
+                inner.pid = None;
+                inner.started_at = None;
+                inner.stop_tx = None;
+                inner.server_info = None;
+                inner.last_exit = Some(exit.clone());
+                self.restart_decision(&mut inner, &exit, generation, uptime)
+            };
+
+            if let Some(reply) = reply {
+                if let Ok(outcome) = stop_result.as_ref() {
+                    self.push_line(format!("[arssm] {}", outcome.describe())).await;
+                }
+                let _ = reply.send(stop_result);
+                return;
+            }
+
+            self.push_line(format!("[arssm] server exited: {}", exit.describe()))
+                .await;
+            if exit.is_crash() {
+                tracing::warn!(profile_id = %self.profile_id, "server exited: {}", exit.describe());
+            }
+
+            let (delay, attempt) = match restart_delay {
+                RestartDecision::Restart { delay, attempt } => (delay, attempt),
+                RestartDecision::CrashLoop => {
+                    tracing::error!(profile_id = %self.profile_id, "crash loop detected; automatic restarts disabled");
+                    self.push_line(format!(
+                        "[arssm] crash loop detected after {} restarts; giving up",
+                        self.config.backoff.max_consecutive_restarts
+                    ))
+                    .await;
+                    return;
+                }
+                RestartDecision::None => return,
+            };
+
+            self.push_line(format!(
+                "[arssm] restarting in {}s (attempt {attempt})",
+                delay.as_secs_f32()
+            ))
+            .await;
+            tokio::time::sleep(delay).await;
+
             let mut inner = self.inner.lock().await;
-            inner.profile_id = None;
-            inner.pid = None;
-            inner.started_at = None;
-            inner.child.take()
+            if inner.generation != generation || !inner.restarting {
+                return;
+            }
+            let Some(launch) = inner.launch.clone() else {
+                inner.restarting = false;
+                return;
+            };
+            match self.spawn_child(&launch) {
+                Ok(next_child) => {
+                    stop_rx = attach_child(&mut inner, &next_child);
+                    inner.restart_count += 1;
+                    child = next_child;
+                }
+                Err(err) => {
+                    inner.restarting = false;
+                    drop(inner);
+                    self.push_line(format!("[arssm] restart failed: {err}")).await;
+                    return;
+                }
+            }
+        }
+    }
+
+    async fn poll_server_info(self, generation: u64, endpoint: A2sEndpoint) {
+        loop {
+            let running = {
+                let inner = self.inner.lock().await;
+                if inner.generation != generation || !(inner.running || inner.restarting) {
+                    return;
+                }
+                inner.running
+            };
+
+            if running {
+                let info = query_info(&endpoint, self.config.query_timeout).await.ok();
+                let mut inner = self.inner.lock().await;
+                if inner.generation == generation && inner.running {
+                    inner.server_info = info;
+                }
+            }
+            tokio::time::sleep(self.config.query_interval).await;
+        }
+    }
+
+    fn restart_decision(
+        &self,
+        inner: &mut RunInner,
+        exit: &ExitReason,
+        generation: u64,
+        uptime: u64,
+    ) -> RestartDecision {
+        if exit.requested || inner.generation != generation {
+            return RestartDecision::None;
+        }
+        let policy = inner
+            .launch
+            .as_ref()
+            .map(|launch| launch.restart_policy)
+            .unwrap_or_default();
+        let wanted = match policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnCrash => exit.is_crash(),
+            RestartPolicy::Always => true,
         };
+        if !wanted {
+            return RestartDecision::None;
+        }
 
-        if let Some(ref mut child) = child {
-            child
-                .kill()
-                .await
-                .map_err(|err| format!("failed to stop server: {err}"))?;
-            let _ = child.wait().await;
-            Ok(())
-        } else {
-            Err("server is not running".to_string())
+        if uptime >= self.config.backoff.stable_after.as_secs() {
+            inner.consecutive_restarts = 0;
+        }
+        if inner.consecutive_restarts >= self.config.backoff.max_consecutive_restarts {
+            return RestartDecision::CrashLoop;
+        }
+        inner.consecutive_restarts += 1;
+        inner.restarting = true;
+        RestartDecision::Restart {
+            delay: self.config.backoff.delay_for(inner.consecutive_restarts),
+            attempt: inner.consecutive_restarts,
         }
     }
 
@@ -195,6 +707,70 @@ impl RunManager {
     }
 }
 
+enum RestartDecision {
+    None,
+    Restart { delay: Duration, attempt: u32 },
+    CrashLoop,
+}
+
+fn attach_child(inner: &mut RunInner, child: &Child) -> oneshot::Receiver<StopRequest> {
+    let (stop_tx, stop_rx) = oneshot::channel();
+    inner.stop_tx = Some(stop_tx);
+    inner.pid = child.id();
+    inner.started_at = Some(current_epoch_seconds());
+    inner.running = true;
+    inner.restarting = false;
+    stop_rx
+}
+
+async fn terminate_gracefully(child: &mut Child, grace_period: Duration) -> Result<StopOutcome, String> {
+    if let Some(pid) = child.id() {
+        if !grace_period.is_zero() && request_termination(pid).is_ok() {
+            if let Ok(Ok(_)) = tokio::time::timeout(grace_period, child.wait()).await {
+                return Ok(StopOutcome::Graceful);
+            }
+        }
+    }
+
+    child
+        .kill()
+        .await
+        .map_err(|err| format!("failed to stop server: {err}"))?;
+    Ok(StopOutcome::Killed)
+}
+
+#[cfg(unix)]
+fn request_termination(pid: u32) -> Result<(), String> {
+    let pid = libc::pid_t::try_from(pid).map_err(|_| format!("invalid pid {pid}"))?;
+    // SAFETY: kill(2) only sends a signal; the pid comes from our own child handle.
+    let result = unsafe { libc::kill(pid, libc::SIGTERM) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(format!("failed to send SIGTERM: {}", std::io::Error::last_os_error()))
+    }
+}
+
+#[cfg(windows)]
+fn request_termination(pid: u32) -> Result<(), String> {
+    let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map_err(|err| format!("failed to run taskkill: {err}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("taskkill failed: {status}"))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn request_termination(_pid: u32) -> Result<(), String> {
+    Err("graceful termination not supported on this platform".to_string())
+}
+
 fn current_epoch_seconds() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -202,9 +778,9 @@ fn current_epoch_seconds() -> u64 {
         .unwrap_or(0)
 }
 
-fn log_file_path(profile_id: &str) -> PathBuf {
+fn log_file_path(logs_dir: &Path, profile_id: &str) -> PathBuf {
     let timestamp = current_epoch_seconds();
-    logs_dir().join(format!("{profile_id}-{timestamp}.log"))
+    logs_dir.join(format!("{profile_id}-{timestamp}.log"))
 }
 
 async fn append_line_to_file(path: &Path, line: &str) -> Result<(), String> {
@@ -278,24 +854,384 @@ mod tests {
 
     #[tokio::test]
     async fn tail_returns_last_lines() {
-        let manager = RunManager::new();
+        let manager = test_manager();
         for idx in 0..10 {
-            manager.push_line(format!("line-{idx}")).await;
+            manager.instance("pve").push_line(format!("line-{idx}")).await;
         }
 
-        let tail = manager.tail(3).await;
+        let tail = manager.tail("pve", 3).await;
         assert_eq!(tail, vec!["line-7", "line-8", "line-9"]);
     }
 
     #[tokio::test]
     async fn broadcast_stream_emits_lines() {
-        let manager = RunManager::new();
-        let receiver = manager.subscribe();
+        let manager = test_manager();
+        let receiver = manager.subscribe("pve");
         let mut stream = BroadcastStream::new(receiver).filter_map(|message| message.ok());
 
-        manager.push_line("hello".to_string()).await;
+        manager.instance("pve").push_line("hello".to_string()).await;
 
         let next = stream.next().await.expect("missing line");
         assert_eq!(next, "hello");
     }
+
+    #[tokio::test]
+    async fn instances_keep_separate_logs() {
+        let manager = test_manager();
+        let mut pvp_receiver = manager.subscribe("pvp");
+
+        manager.instance("pve").push_line("pve-line".to_string()).await;
+        manager.instance("pvp").push_line("pvp-line".to_string()).await;
+
+        assert_eq!(manager.tail("pve", 10).await, vec!["pve-line"]);
+        assert_eq!(manager.tail("pvp", 10).await, vec!["pvp-line"]);
+        assert_eq!(pvp_receiver.recv().await.expect("missing line"), "pvp-line");
+        assert!(pvp_receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn log_line_reaches_stream_without_server() {
+        let manager = test_manager();
+        let mut receiver = manager.subscribe("pve");
+
+        manager.log_line("pve", "[arssm] updating".to_string()).await;
+
+        assert!(!manager.status("pve").await.running);
+        assert_eq!(manager.tail("pve", 10).await, vec!["[arssm] updating"]);
+        assert_eq!(receiver.recv().await.expect("missing line"), "[arssm] updating");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn pre_start_lines_stay_in_the_run_log() {
+        let manager = test_manager();
+        let profile = test_profile("updated");
+        manager.begin_run("updated").await.expect("begin run");
+        manager.log_line("updated", "[steamcmd] up to date".to_string()).await;
+
+        launch(&manager, "updated", "echo started", &profile).await;
+        wait_for_exit(&manager, "updated").await;
+
+        let expected = ["[steamcmd] up to date".to_string(), "started".to_string()];
+        let tail = manager.tail("updated", 10).await;
+        assert!(expected.iter().all(|line| tail.contains(line)), "{tail:?}");
+        let persisted = manager.tail_persisted("updated", 10).await;
+        assert!(expected.iter().all(|line| persisted.contains(line)), "{persisted:?}");
+    }
+
+    #[cfg(unix)]
+    fn fake_server(name: &str, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!(
+            "arssm-runner-{name}-{}-{}",
+            std::process::id(),
+            current_epoch_seconds()
+        ));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let path = dir.join("fake-server.sh");
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).expect("write script");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .expect("chmod script");
+        path
+    }
+
+    #[cfg(unix)]
+    fn test_profile(profile_id: &str) -> ServerProfile {
+        test_profile_with_policy(profile_id, RestartPolicy::Never)
+    }
+
+    #[cfg(unix)]
+    fn test_profile_with_policy(profile_id: &str, policy: RestartPolicy) -> ServerProfile {
+        serde_json::from_value(serde_json::json!({
+            "profile_id": profile_id,
+            "display_name": profile_id,
+            "workshop_url": "",
+            "restart_policy": policy,
+        }))
+        .expect("profile")
+    }
+
+    fn test_manager() -> RunManager {
+        RunManager::with_config(RunManagerConfig {
+            backoff: RestartBackoff {
+                initial_delay: Duration::from_millis(10),
+                max_delay: Duration::from_millis(40),
+                max_consecutive_restarts: 2,
+                stable_after: Duration::from_secs(60),
+            },
+            logs_dir: std::env::temp_dir().join(format!("arssm-runner-logs-{}", std::process::id())),
+            query_interval: Duration::from_millis(20),
+            query_timeout: Duration::from_millis(100),
+        })
+    }
+
+    #[cfg(unix)]
+    async fn wait_for_exit(manager: &RunManager, profile_id: &str) -> RunStatus {
+        for _ in 0..200 {
+            let status = manager.status(profile_id).await;
+            if !status.running && !status.restarting && status.last_exit.is_some() {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("server did not settle");
+    }
+
+    #[cfg(unix)]
+    async fn launch(manager: &RunManager, name: &str, script: &str, profile: &ServerProfile) {
+        let script = fake_server(name, script);
+        let work_dir = script.parent().expect("script dir").to_path_buf();
+        manager
+            .start(
+                &script.to_string_lossy(),
+                &work_dir.to_string_lossy(),
+                profile,
+                &work_dir,
+                &work_dir,
+            )
+            .await
+            .expect("start");
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_and_caps() {
+        let backoff = RestartBackoff::default();
+        assert_eq!(backoff.delay_for(1), Duration::from_secs(5));
+        assert_eq!(backoff.delay_for(2), Duration::from_secs(10));
+        assert_eq!(backoff.delay_for(4), Duration::from_secs(40));
+        assert_eq!(backoff.delay_for(20), Duration::from_secs(300));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn watchdog_restarts_crashes_until_cutoff() {
+        let manager = test_manager();
+        let mut starts = manager.subscribe_starts();
+        let profile = test_profile_with_policy("crashy", RestartPolicy::OnCrash);
+        launch(&manager, "crashy", "exit 3", &profile).await;
+
+        let status = wait_for_exit(&manager, "crashy").await;
+        assert_eq!(status.restart_count, 2);
+        for _ in 0..3 {
+            assert_eq!(starts.try_recv().expect("start event"), "crashy");
+        }
+        let exit = status.last_exit.expect("exit reason");
+        assert_eq!(exit.code, Some(3));
+        assert!(exit.is_crash());
+        assert!(manager
+            .tail("crashy", 20)
+            .await
+            .iter()
+            .any(|line| line.contains("crash loop detected")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn on_crash_policy_ignores_clean_exit() {
+        let manager = test_manager();
+        let profile = test_profile_with_policy("clean", RestartPolicy::OnCrash);
+        launch(&manager, "clean", "exit 0", &profile).await;
+
+        let status = wait_for_exit(&manager, "clean").await;
+        assert_eq!(status.restart_count, 0);
+        assert_eq!(status.last_exit.expect("exit reason").code, Some(0));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn always_policy_restarts_clean_exit() {
+        let manager = test_manager();
+        let profile = test_profile_with_policy("always", RestartPolicy::Always);
+        launch(&manager, "always", "exit 0", &profile).await;
+
+        let status = wait_for_exit(&manager, "always").await;
+        assert_eq!(status.restart_count, 2);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stop_is_recorded_as_requested_exit() {
+        let manager = test_manager();
+        let profile = test_profile_with_policy("stopped", RestartPolicy::Always);
+        launch(&manager, "stopped", "exec sleep 30", &profile).await;
+
+        let outcome = manager
+            .stop("stopped", Duration::from_secs(5))
+            .await
+            .expect("stop");
+        assert_eq!(outcome, StopOutcome::Graceful);
+        let status = manager.status("stopped").await;
+        assert!(!status.running);
+        assert!(!status.restarting);
+        let exit = status.last_exit.expect("exit reason");
+        assert!(exit.requested);
+        assert!(!exit.is_crash());
+        assert_eq!(exit.signal, Some(libc::SIGTERM));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stop_escalates_to_kill_after_grace_period() {
+        let manager = test_manager();
+        let profile = test_profile("stubborn");
+        launch(&manager, "stubborn", "trap '' TERM\necho trapped\nexec sleep 30", &profile).await;
+        // Stopping before the trap is installed would end the script gracefully.
+        for _ in 0..100 {
+            if manager.tail("stubborn", 10).await.iter().any(|line| line.contains("trapped")) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let outcome = manager
+            .stop("stubborn", Duration::from_millis(200))
+            .await
+            .expect("stop");
+        assert_eq!(outcome, StopOutcome::Killed);
+        let exit = manager.status("stubborn").await.last_exit.expect("exit reason");
+        assert_eq!(exit.signal, Some(libc::SIGKILL));
+        assert!(manager
+            .tail("stubborn", 10)
+            .await
+            .iter()
+            .any(|line| line.contains("was killed")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn same_profile_is_never_started_twice() {
+        let manager = test_manager();
+        let script = fake_server("twice", "exec sleep 30");
+        let work_dir = script.parent().expect("script dir").to_path_buf();
+        let exe = script.to_string_lossy().to_string();
+        let work = work_dir.to_string_lossy().to_string();
+
+        for profile_id in ["pve", "pvp"] {
+            manager
+                .start(&exe, &work, &test_profile(profile_id), &work_dir, &work_dir)
+                .await
+                .expect("start");
+        }
+        let second = manager
+            .start(&exe, &work, &test_profile("pve"), &work_dir, &work_dir)
+            .await;
+        assert!(second.is_err());
+        assert_eq!(manager.running_profile_ids().await, vec!["pve", "pvp"]);
+
+        manager.stop("pve", Duration::from_secs(5)).await.expect("stop pve");
+        assert!(!manager.status("pve").await.running);
+        assert!(manager.status("pvp").await.running);
+        manager.stop("pvp", Duration::from_secs(5)).await.expect("stop pvp");
+    }
+
+    #[tokio::test]
+    async fn status_is_reported_per_profile() {
+        let manager = test_manager();
+        let status = manager.status("pve").await;
+        assert!(!status.running);
+        assert_eq!(status.profile_id, "pve");
+        assert!(manager.running_profile_ids().await.is_empty());
+        assert!(manager.stop("pve", Duration::from_secs(1)).await.is_err());
+        assert!(manager.statuses().await.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn deleted_profiles_leave_the_registry_once_stopped() {
+        let manager = test_manager();
+        let profile = test_profile("removed");
+        launch(&manager, "removed", "exec sleep 30", &profile).await;
+
+        assert!(manager.remove("removed").await.is_err());
+        manager.stop("removed", Duration::from_secs(5)).await.expect("stop");
+        manager.remove("removed").await.expect("remove");
+        assert!(manager.statuses().await.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn running_profiles_are_found_by_work_dir() {
+        let manager = test_manager();
+        let script = fake_server("workdir", "exec sleep 30");
+        let work_dir = script.parent().expect("script dir").to_path_buf();
+        manager
+            .start(
+                &script.to_string_lossy(),
+                &work_dir.to_string_lossy(),
+                &test_profile("workdir"),
+                &work_dir,
+                &work_dir,
+            )
+            .await
+            .expect("start");
+
+        let with_slash = format!("{}/", work_dir.to_string_lossy());
+        assert_eq!(manager.profiles_using_work_dir(&with_slash).await, vec!["workdir"]);
+        assert!(manager.profiles_using_work_dir("/nonexistent").await.is_empty());
+        manager
+            .stop("workdir", Duration::from_secs(5))
+            .await
+            .expect("stop");
+        assert!(manager.profiles_using_work_dir(&with_slash).await.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn server_info_is_polled_from_a2s() {
+        let responder = tokio::net::UdpSocket::bind("127.0.0.1:0").await.expect("bind a2s");
+        let port = responder.local_addr().expect("local addr").port();
+        tokio::spawn(async move {
+            let mut info = vec![0xFF, 0xFF, 0xFF, 0xFF, 0x49, 17];
+            for value in ["Joinable", "Arland", "reforger", "Arma Reforger"] {
+                info.extend_from_slice(value.as_bytes());
+                info.push(0);
+            }
+            info.extend_from_slice(&[0, 0, 5, 64, 0, b'd', b'l', 0, 1]);
+            info.extend_from_slice(b"1.0\0");
+            let mut buffer = [0u8; 512];
+            while let Ok((_, peer)) = responder.recv_from(&mut buffer).await {
+                let _ = responder.send_to(&info, peer).await;
+            }
+        });
+
+        let manager = test_manager();
+        let profile = test_profile("queried");
+        let script = fake_server("queried", "exec sleep 30");
+        let work_dir = script.parent().expect("script dir").to_path_buf();
+        let config_path = work_dir.join("server.json");
+        std::fs::write(
+            &config_path,
+            serde_json::json!({ "a2s": { "address": "0.0.0.0", "port": port } }).to_string(),
+        )
+        .expect("write config");
+        manager
+            .start(
+                &script.to_string_lossy(),
+                &work_dir.to_string_lossy(),
+                &profile,
+                &config_path,
+                &work_dir,
+            )
+            .await
+            .expect("start");
+
+        let mut server_info = None;
+        for _ in 0..100 {
+            server_info = manager.status("queried").await.server_info;
+            if server_info.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let server_info = server_info.expect("server info");
+        assert_eq!(server_info.map, "Arland");
+        assert_eq!((server_info.players, server_info.max_players), (5, 64));
+
+        manager
+            .stop("queried", Duration::from_secs(5))
+            .await
+            .expect("stop");
+        assert!(manager.status("queried").await.server_info.is_none());
+    }
 }
@@ -0,0 +1,352 @@
+use crate::security::users::AuthUser;
+use axum::{extract::FromRequestParts, http::request::Parts};
+use backend::storage;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Keys whose values never end up in the audit log.
+const REDACTED_KEYS: [&str; 4] = ["password", "passphrase", "secret", "token"];
+
+pub fn audit_log_path() -> PathBuf {
+    storage::base_dir().join("audit.jsonl")
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: u64,
+    pub actor: String,
+    #[serde(default)]
+    pub source_ip: Option<String>,
+    pub action: String,
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+/// The client address as seen by `auth_middleware`, attached to each request.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub IpAddr);
+
+/// Who performed a request, extracted from the extensions set by `auth_middleware`.
+#[derive(Debug, Clone)]
+pub struct Actor {
+    pub username: String,
+    pub ip: Option<IpAddr>,
+}
+
+#[async_trait::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            username: parts
+                .extensions
+                .get::<AuthUser>()
+                .map(|user| user.username.clone())
+                .unwrap_or_else(|| "anonymous".to_string()),
+            ip: parts.extensions.get::<ClientIp>().map(|ClientIp(ip)| *ip),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub actor: Option<String>,
+    /// Matches the action itself or its group, e.g. `profile` matches `profile.delete`.
+    pub action: Option<String>,
+    /// Case-insensitive substring of the target.
+    pub target: Option<String>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
+impl AuditFilter {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        if self.actor.as_deref().is_some_and(|actor| entry.actor != actor) {
+            return false;
+        }
+        if let Some(action) = self.action.as_deref() {
+            let in_group = entry
+                .action
+                .strip_prefix(action)
+                .is_some_and(|rest| rest.starts_with('.'));
+            if entry.action != action && !in_group {
+                return false;
+            }
+        }
+        if let Some(target) = self.target.as_deref() {
+            if !entry.target.to_lowercase().contains(&target.to_lowercase()) {
+                return false;
+            }
+        }
+        self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp < until)
+    }
+}
+
+/// Append-only JSONL log of administrative actions.
+#[derive(Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Records an action. Failures are logged rather than failing the action, which
+    /// has already happened by the time it is recorded.
+    pub async fn record(
+        &self,
+        actor: &Actor,
+        action: &str,
+        target: &str,
+        before: Option<Value>,
+        after: Option<Value>,
+    ) {
+        let entry = AuditEntry {
+            timestamp: crate::services::current_epoch_seconds(),
+            actor: actor.username.clone(),
+            source_ip: actor.ip.map(|ip| ip.to_string()),
+            action: action.to_string(),
+            target: target.to_string(),
+            before: before.map(redact),
+            after: after.map(redact),
+        };
+        if let Err(err) = self.append(&entry).await {
+            tracing::error!(action, target, "failed to write audit log: {err}");
+        }
+    }
+
+    /// Records an update with only the fields that changed between `before` and `after`.
+    pub async fn record_change<T: Serialize>(
+        &self,
+        actor: &Actor,
+        action: &str,
+        target: &str,
+        before: &T,
+        after: &T,
+    ) {
+        let (before, after) = summarize_change(&to_value(before), &to_value(after));
+        self.record(actor, action, target, Some(before), Some(after)).await;
+    }
+
+    /// Records a creation or deletion with the full object on one side.
+    pub async fn record_object<T: Serialize>(
+        &self,
+        actor: &Actor,
+        action: &str,
+        target: &str,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) {
+        self.record(
+            actor,
+            action,
+            target,
+            before.map(to_value),
+            after.map(to_value),
+        )
+        .await;
+    }
+
+    async fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        let mut line = serde_json::to_string(entry)
+            .map_err(|err| format!("failed to serialize audit entry: {err}"))?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().await;
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| format!("failed to create {}: {err}", parent.display()))?;
+        }
+        let mut options = tokio::fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options
+            .open(&self.path)
+            .await
+            .map_err(|err| format!("failed to open {}: {err}", self.path.display()))?;
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(|err| format!("failed to append to {}: {err}", self.path.display()))?;
+        file.flush()
+            .await
+            .map_err(|err| format!("failed to flush {}: {err}", self.path.display()))
+    }
+
+    /// Matching entries, newest first. Unreadable lines are skipped.
+    pub async fn read(&self, filter: &AuditFilter, limit: Option<usize>) -> Result<Vec<AuditEntry>, String> {
+        let contents = match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(format!("failed to read {}: {err}", self.path.display())),
+        };
+        Ok(contents
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            .filter(|entry| filter.matches(entry))
+            .take(limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// Flattens both values to dotted paths and keeps only the paths that differ.
+pub fn summarize_change(before: &Value, after: &Value) -> (Value, Value) {
+    let mut before_fields = Map::new();
+    let mut after_fields = Map::new();
+    flatten("", before, &mut before_fields);
+    flatten("", after, &mut after_fields);
+
+    let mut changed_before = Map::new();
+    let mut changed_after = Map::new();
+    for (key, value) in &before_fields {
+        if after_fields.get(key) != Some(value) {
+            changed_before.insert(key.clone(), value.clone());
+        }
+    }
+    for (key, value) in &after_fields {
+        if before_fields.get(key) != Some(value) {
+            changed_after.insert(key.clone(), value.clone());
+        }
+    }
+    (Value::Object(changed_before), Value::Object(changed_after))
+}
+
+fn flatten(prefix: &str, value: &Value, out: &mut Map<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(&path, value, out);
+            }
+        }
+        _ => {
+            out.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    REDACTED_KEYS.iter().any(|marker| key.contains(marker))
+}
+
+/// Replaces the values of password, secret and token keys, at any depth.
+pub fn redact(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let value = if is_sensitive(&key) && !value.is_null() {
+                        Value::String("[redacted]".to_string())
+                    } else {
+                        redact(value)
+                    };
+                    (key, value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(redact).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn actor() -> Actor {
+        Actor {
+            username: "alice".to_string(),
+            ip: Some(IpAddr::from([10, 0, 0, 7])),
+        }
+    }
+
+    #[test]
+    fn change_summary_keeps_changed_paths_only() {
+        let before = json!({
+            "display_name": "Old",
+            "game": { "maxPlayers": 32, "name": "Server" },
+            "rcon": { "password": "hunter2" }
+        });
+        let after = json!({
+            "display_name": "Old",
+            "game": { "maxPlayers": 64, "name": "Server" },
+            "rcon": { "password": "hunter3" }
+        });
+        let (before, after) = summarize_change(&before, &after);
+        assert_eq!(before, json!({ "game.maxPlayers": 32, "rcon.password": "hunter2" }));
+        assert_eq!(redact(after), json!({ "game.maxPlayers": 64, "rcon.password": "[redacted]" }));
+    }
+
+    #[tokio::test]
+    async fn entries_are_appended_and_filtered_newest_first() {
+        let dir = std::env::temp_dir().join(format!("arssm-audit-{}", std::process::id()));
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        let log = AuditLog::new(dir.join("audit.jsonl"));
+
+        log.record(&actor(), "profile.delete", "coop-1", Some(json!({ "display_name": "Coop" })), None)
+            .await;
+        log.record(&actor(), "run.stop", "coop-2", None, None).await;
+        let bob = Actor {
+            username: "bob".to_string(),
+            ip: None,
+        };
+        log.record(&bob, "settings.update", "settings", None, None).await;
+
+        let all = log.read(&AuditFilter::default(), None).await.expect("read");
+        assert_eq!(
+            all.iter().map(|entry| entry.action.as_str()).collect::<Vec<_>>(),
+            vec!["settings.update", "run.stop", "profile.delete"]
+        );
+        assert_eq!(all[2].source_ip.as_deref(), Some("10.0.0.7"));
+        assert_eq!(all[2].before, Some(json!({ "display_name": "Coop" })));
+
+        let filter = AuditFilter {
+            actor: Some("alice".to_string()),
+            target: Some("COOP".to_string()),
+            ..AuditFilter::default()
+        };
+        assert_eq!(log.read(&filter, Some(1)).await.expect("read").len(), 1);
+        let filter = AuditFilter {
+            action: Some("profile".to_string()),
+            ..AuditFilter::default()
+        };
+        let matches = log.read(&filter, None).await.expect("read");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].target, "coop-1");
+        let filter = AuditFilter {
+            action: Some("pro".to_string()),
+            ..AuditFilter::default()
+        };
+        assert!(log.read(&filter, None).await.expect("read").is_empty());
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
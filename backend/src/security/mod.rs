@@ -0,0 +1,214 @@
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use subtle::ConstantTimeEq;
+use backend::storage;
+
+pub mod audit;
+pub mod sessions;
+pub mod throttle;
+pub mod tls;
+pub mod tokens;
+pub mod users;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credentials {
+    pub username: String,
+    #[serde(default)]
+    pub password_hash: String,
+    /// Plaintext password from older credential files; hashed on load and never written back.
+    #[serde(default, skip_serializing)]
+    password: Option<String>,
+}
+
+impl Credentials {
+    pub fn new(username: String, password: &str) -> Result<Self, String> {
+        Ok(Self {
+            username,
+            password_hash: hash_password(password)?,
+            password: None,
+        })
+    }
+
+    /// Checks both fields without short-circuiting so a wrong username costs as much as a wrong password.
+    pub fn verify(&self, username: &str, password: &str) -> bool {
+        let username_matches: bool = username.as_bytes().ct_eq(self.username.as_bytes()).into();
+        let password_matches = verify_password(password, &self.password_hash);
+        username_matches & password_matches
+    }
+}
+
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut rand::rngs::OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| format!("failed to hash password: {err}"))
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+pub fn credentials_path() -> PathBuf {
+    storage::base_dir().join("credentials.json")
+}
+
+/// Returns the stored credentials, plus the plaintext password when new ones had to be generated.
+pub async fn load_or_create_credentials() -> Result<(Credentials, Option<String>), String> {
+    load_or_create_credentials_at(&credentials_path()).await
+}
+
+async fn load_or_create_credentials_at(path: &Path) -> Result<(Credentials, Option<String>), String> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => {
+            let mut creds: Credentials = serde_json::from_str(&contents)
+                .map_err(|err| format!("failed to parse credentials: {err}"))?;
+            if creds.password_hash.is_empty() {
+                let password = creds
+                    .password
+                    .take()
+                    .ok_or_else(|| "credentials contain neither password_hash nor password".to_string())?;
+                creds.password_hash = hash_password(&password)?;
+                save_credentials(path, &creds).await?;
+                tracing::info!("migrated plaintext credentials in {} to a password hash", path.display());
+            }
+            Ok((creds, None))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            let (creds, password) = generate_credentials(path).await?;
+            Ok((creds, Some(password)))
+        }
+        Err(err) => Err(format!("failed to read credentials: {err}")),
+    }
+}
+
+/// Replaces the stored credentials with a new random pair, ends the sessions of the
+/// previous admin and returns the plaintext password.
+pub async fn reset_credentials() -> Result<(Credentials, String), String> {
+    let path = credentials_path();
+    let previous = tokio::fs::read_to_string(&path)
+        .await
+        .ok()
+        .and_then(|contents| serde_json::from_str::<Credentials>(&contents).ok());
+    let generated = generate_credentials(&path).await?;
+    if let Some(previous) = previous {
+        let sessions = sessions::SessionManager::load_or_create(
+            &sessions::session_key_path(),
+            &sessions::session_state_path(),
+            sessions::DEFAULT_SESSION_TTL_SECS,
+        )
+        .await?;
+        sessions.end_user_sessions(&previous.username).await?;
+    }
+    Ok(generated)
+}
+
+async fn generate_credentials(path: &Path) -> Result<(Credentials, String), String> {
+    let password = random_token(20);
+    let creds = Credentials::new(random_token(10), &password)?;
+    save_credentials(path, &creds).await?;
+    Ok((creds, password))
+}
+
+pub async fn save_credentials(path: &Path, creds: &Credentials) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(creds)
+        .map_err(|err| format!("failed to serialize credentials: {err}"))?;
+    write_private_file(path, &data).await
+}
+
+/// Atomically replaces `path`, readable only by the owner on unix.
+pub(crate) async fn write_private_file(path: &Path, data: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|err| format!("failed to create {}: {err}", parent.display()))?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp_path, data)
+        .await
+        .map_err(|err| format!("failed to write {}: {err}", tmp_path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o600))
+            .await
+            .map_err(|err| format!("failed to restrict permissions of {}: {err}", tmp_path.display()))?;
+    }
+    tokio::fs::rename(&tmp_path, path)
+        .await
+        .map_err(|err| format!("failed to replace {}: {err}", path.display()))
+}
+
+pub(crate) fn random_token(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("arssm-security-{name}-{}", std::process::id()))
+            .join("credentials.json")
+    }
+
+    #[test]
+    fn verify_accepts_only_matching_pair() {
+        let creds = Credentials::new("admin".to_string(), "secret").expect("hash");
+        assert!(creds.password_hash.starts_with("$argon2"));
+        assert!(!creds.password_hash.contains("secret"));
+        assert!(creds.verify("admin", "secret"));
+        assert!(!creds.verify("admin", "wrong"));
+        assert!(!creds.verify("other", "secret"));
+        assert!(!verify_password("secret", "not-a-hash"));
+    }
+
+    #[tokio::test]
+    async fn plaintext_credentials_are_migrated() {
+        let path = temp_path("migrate");
+        tokio::fs::create_dir_all(path.parent().unwrap()).await.unwrap();
+        tokio::fs::write(&path, r#"{"username": "admin", "password": "legacy"}"#)
+            .await
+            .unwrap();
+
+        let (creds, generated) = load_or_create_credentials_at(&path).await.expect("load");
+        assert!(generated.is_none());
+        assert!(creds.verify("admin", "legacy"));
+
+        let stored = tokio::fs::read_to_string(&path).await.unwrap();
+        assert!(!stored.contains("legacy"));
+        assert!(stored.contains("password_hash"));
+        let (reloaded, _) = load_or_create_credentials_at(&path).await.expect("reload");
+        assert!(reloaded.verify("admin", "legacy"));
+
+        let _ = tokio::fs::remove_dir_all(path.parent().unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn generated_password_is_returned_once() {
+        let path = temp_path("generate");
+        let _ = tokio::fs::remove_dir_all(path.parent().unwrap()).await;
+
+        let (creds, generated) = load_or_create_credentials_at(&path).await.expect("create");
+        let password = generated.expect("generated password");
+        assert!(creds.verify(&creds.username, &password));
+        let (_, again) = load_or_create_credentials_at(&path).await.expect("load");
+        assert!(again.is_none());
+
+        let _ = tokio::fs::remove_dir_all(path.parent().unwrap()).await;
+    }
+}
@@ -0,0 +1,369 @@
+use super::write_private_file;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use subtle::ConstantTimeEq;
+use backend::storage;
+
+pub const SESSION_COOKIE: &str = "arssm_session";
+pub const CSRF_COOKIE: &str = "arssm_csrf";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+pub const CSRF_FIELD: &str = "csrf_token";
+pub const DEFAULT_SESSION_TTL_SECS: u64 = 12 * 60 * 60;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub fn session_key_path() -> PathBuf {
+    storage::base_dir().join("session.key")
+}
+
+pub fn session_state_path() -> PathBuf {
+    storage::base_dir().join("sessions.json")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(rename = "u")]
+    pub username: String,
+    #[serde(rename = "e")]
+    pub expires_at: u64,
+    #[serde(rename = "i")]
+    pub id: String,
+    /// The user's session generation at login; bumping it ends all of the user's sessions.
+    #[serde(rename = "g", default)]
+    pub generation: u64,
+}
+
+/// Logouts and per-user generations, persisted so they survive restarts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionState {
+    #[serde(default)]
+    revoked: HashMap<String, u64>,
+    #[serde(default)]
+    generations: HashMap<String, u64>,
+}
+
+impl SessionState {
+    /// Folds in changes another process wrote to the same file, e.g. `reset-credentials`
+    /// ending the previous admin's sessions while the server runs.
+    fn merge(&mut self, other: SessionState) {
+        for (id, expires_at) in other.revoked {
+            self.revoked.entry(id).or_insert(expires_at);
+        }
+        for (username, generation) in other.generations {
+            let current = self.generations.entry(username).or_insert(0);
+            *current = (*current).max(generation);
+        }
+    }
+
+    fn generation(&self, username: &str) -> u64 {
+        self.generations.get(username).copied().unwrap_or(0)
+    }
+}
+
+/// Issues and checks HMAC-signed session cookies. Sessions are stateless except for
+/// logouts, which are remembered until the revoked session would have expired anyway,
+/// and a per-user generation that password changes bump to end every older session.
+#[derive(Clone)]
+pub struct SessionManager {
+    key: Arc<Vec<u8>>,
+    ttl_secs: u64,
+    secure_cookies: bool,
+    state: Arc<Mutex<SessionState>>,
+    state_path: Option<Arc<PathBuf>>,
+    persist_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl SessionManager {
+    /// A manager that keeps logouts in memory only.
+    pub fn new(key: Vec<u8>, ttl_secs: u64) -> Self {
+        Self {
+            key: Arc::new(key),
+            ttl_secs,
+            secure_cookies: true,
+            state: Arc::new(Mutex::new(SessionState::default())),
+            state_path: None,
+            persist_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Plain-HTTP deployments behind a reverse proxy cannot use `Secure` cookies.
+    pub fn set_secure_cookies(&mut self, secure: bool) {
+        self.secure_cookies = secure;
+    }
+
+    /// Loads the signing key, creating a random one on first start so sessions survive restarts,
+    /// and the logouts and generations stored at `state_path`.
+    pub async fn load_or_create(path: &Path, state_path: &Path, ttl_secs: u64) -> Result<Self, String> {
+        let key = match tokio::fs::read_to_string(path).await {
+            Ok(contents) => URL_SAFE_NO_PAD
+                .decode(contents.trim())
+                .map_err(|err| format!("failed to decode session key: {err}"))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let mut key = vec![0u8; 32];
+                rand::rngs::OsRng.fill_bytes(&mut key);
+                write_private_file(path, &URL_SAFE_NO_PAD.encode(&key)).await?;
+                key
+            }
+            Err(err) => return Err(format!("failed to read session key: {err}")),
+        };
+        if key.len() < 32 {
+            return Err("session key is too short".to_string());
+        }
+        let mut manager = Self::new(key, ttl_secs);
+        *manager.state.lock().expect("session state lock") = read_state(state_path).await?;
+        manager.state_path = Some(Arc::new(state_path.to_path_buf()));
+        Ok(manager)
+    }
+
+    pub fn issue(&self, username: &str, now: u64) -> (Session, String) {
+        let mut id = [0u8; 16];
+        rand::rngs::OsRng.fill_bytes(&mut id);
+        let generation = self.state.lock().expect("session state lock").generation(username);
+        let session = Session {
+            username: username.to_string(),
+            expires_at: now + self.ttl_secs,
+            id: URL_SAFE_NO_PAD.encode(id),
+            generation,
+        };
+        let payload = URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(&session).expect("session serializes"));
+        let signature = URL_SAFE_NO_PAD.encode(self.sign(payload.as_bytes()));
+        (session, format!("{payload}.{signature}"))
+    }
+
+    pub fn verify(&self, cookie: &str, now: u64) -> Option<Session> {
+        let (payload, signature) = cookie.split_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        let mut mac = self.mac();
+        mac.update(payload.as_bytes());
+        mac.verify_slice(&signature).ok()?;
+
+        let session: Session =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+        if session.expires_at <= now {
+            return None;
+        }
+        let state = self.state.lock().expect("session state lock");
+        if state.revoked.contains_key(&session.id)
+            || session.generation != state.generation(&session.username)
+        {
+            return None;
+        }
+        Some(session)
+    }
+
+    pub async fn revoke(&self, session: &Session, now: u64) -> Result<(), String> {
+        self.update(|state| {
+            state.revoked.retain(|_, expires_at| *expires_at > now);
+            state.revoked.insert(session.id.clone(), session.expires_at);
+        })
+        .await
+    }
+
+    /// Ends every session of `username`, e.g. after a password change.
+    pub async fn end_user_sessions(&self, username: &str) -> Result<(), String> {
+        self.update(|state| {
+            *state.generations.entry(username.to_string()).or_insert(0) += 1;
+        })
+        .await
+    }
+
+    async fn update(&self, change: impl FnOnce(&mut SessionState)) -> Result<(), String> {
+        let _persist = self.persist_lock.lock().await;
+        let stored = match self.state_path.as_deref() {
+            Some(path) => read_state(path).await?,
+            None => SessionState::default(),
+        };
+        let data = {
+            let mut state = self.state.lock().expect("session state lock");
+            state.merge(stored);
+            change(&mut state);
+            serde_json::to_string_pretty(&*state)
+                .map_err(|err| format!("failed to serialize sessions: {err}"))?
+        };
+        match self.state_path.as_deref() {
+            Some(path) => write_private_file(path, &data).await,
+            None => Ok(()),
+        }
+    }
+
+    /// The CSRF token is bound to the session, so it needs no server-side storage.
+    pub fn csrf_token(&self, session: &Session) -> String {
+        URL_SAFE_NO_PAD.encode(self.sign(format!("csrf:{}", session.id).as_bytes()))
+    }
+
+    pub fn verify_csrf(&self, session: &Session, token: &str) -> bool {
+        self.csrf_token(session)
+            .as_bytes()
+            .ct_eq(token.trim().as_bytes())
+            .into()
+    }
+
+    pub fn login_cookies(&self, session: &Session, cookie: &str) -> [String; 2] {
+        [
+            self.cookie(SESSION_COOKIE, cookie, self.ttl_secs, true),
+            self.cookie(CSRF_COOKIE, &self.csrf_token(session), self.ttl_secs, false),
+        ]
+    }
+
+    pub fn logout_cookies(&self) -> [String; 2] {
+        [
+            self.cookie(SESSION_COOKIE, "", 0, true),
+            self.cookie(CSRF_COOKIE, "", 0, false),
+        ]
+    }
+
+    fn cookie(&self, name: &str, value: &str, max_age: u64, http_only: bool) -> String {
+        let mut cookie = format!("{name}={value}; Path=/; Max-Age={max_age}; SameSite=Strict");
+        if http_only {
+            cookie.push_str("; HttpOnly");
+        }
+        if self.secure_cookies {
+            cookie.push_str("; Secure");
+        }
+        cookie
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.key).expect("HMAC accepts any key length")
+    }
+
+    fn sign(&self, data: &[u8]) -> Vec<u8> {
+        let mut mac = self.mac();
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    }
+}
+
+async fn read_state(path: &Path) -> Result<SessionState, String> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|err| format!("failed to parse {}: {err}", path.display())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(SessionState::default()),
+        Err(err) => Err(format!("failed to read {}: {err}", path.display())),
+    }
+}
+
+pub fn cookie_value<'a>(headers: &'a axum::http::HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(axum::http::header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> SessionManager {
+        SessionManager::new(vec![7u8; 32], 3600)
+    }
+
+    #[test]
+    fn issued_sessions_verify_until_expiry() {
+        let sessions = manager();
+        let (session, cookie) = sessions.issue("alice", 1_000);
+        assert_eq!(sessions.verify(&cookie, 1_000), Some(session.clone()));
+        assert_eq!(sessions.verify(&cookie, 4_599), Some(session));
+        assert!(sessions.verify(&cookie, 4_600).is_none());
+    }
+
+    #[test]
+    fn tampered_or_foreign_cookies_are_rejected() {
+        let sessions = manager();
+        let (_, cookie) = sessions.issue("viewer", 1_000);
+        let (_, signature) = cookie.split_once('.').unwrap();
+        let forged = Session {
+            username: "root".to_string(),
+            expires_at: 9_999,
+            id: "x".to_string(),
+            generation: 0,
+        };
+        let forged_payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&forged).unwrap());
+        assert!(sessions.verify(&format!("{forged_payload}.{signature}"), 1_000).is_none());
+        assert!(sessions.verify("garbage", 1_000).is_none());
+
+        let other = SessionManager::new(vec![8u8; 32], 3600);
+        assert!(other.verify(&cookie, 1_000).is_none());
+    }
+
+    #[tokio::test]
+    async fn revoked_sessions_stop_verifying() {
+        let sessions = manager();
+        let (session, cookie) = sessions.issue("alice", 1_000);
+        let (_, other_cookie) = sessions.issue("alice", 1_000);
+        sessions.revoke(&session, 1_500).await.expect("revoke");
+        assert!(sessions.verify(&cookie, 1_500).is_none());
+        assert!(sessions.verify(&other_cookie, 1_500).is_some());
+    }
+
+    #[tokio::test]
+    async fn ending_user_sessions_keeps_new_logins_and_other_users() {
+        let sessions = manager();
+        let (_, old_cookie) = sessions.issue("alice", 1_000);
+        let (_, bob_cookie) = sessions.issue("bob", 1_000);
+        sessions.end_user_sessions("alice").await.expect("end sessions");
+        assert!(sessions.verify(&old_cookie, 1_000).is_none());
+        assert!(sessions.verify(&bob_cookie, 1_000).is_some());
+
+        let (_, new_cookie) = sessions.issue("alice", 1_000);
+        assert!(sessions.verify(&new_cookie, 1_000).is_some());
+    }
+
+    #[tokio::test]
+    async fn logouts_and_generations_survive_restarts() {
+        let dir = std::env::temp_dir().join(format!("arssm-sessions-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let (key_path, state_path) = (dir.join("session.key"), dir.join("sessions.json"));
+        let sessions = SessionManager::load_or_create(&key_path, &state_path, 3600)
+            .await
+            .expect("load");
+        let (session, logged_out) = sessions.issue("alice", 1_000);
+        let (_, bob_cookie) = sessions.issue("bob", 1_000);
+        sessions.revoke(&session, 1_000).await.expect("revoke");
+        sessions.end_user_sessions("bob").await.expect("end sessions");
+
+        let restarted = SessionManager::load_or_create(&key_path, &state_path, 3600)
+            .await
+            .expect("reload");
+        assert!(restarted.verify(&logged_out, 1_000).is_none());
+        assert!(restarted.verify(&bob_cookie, 1_000).is_none());
+        let (_, fresh) = restarted.issue("alice", 1_000);
+        assert!(restarted.verify(&fresh, 1_000).is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn csrf_tokens_are_bound_to_the_session() {
+        let sessions = manager();
+        let (session, _) = sessions.issue("alice", 1_000);
+        let (other, _) = sessions.issue("alice", 1_000);
+        let token = sessions.csrf_token(&session);
+        assert!(sessions.verify_csrf(&session, &token));
+        assert!(!sessions.verify_csrf(&other, &token));
+        assert!(!sessions.verify_csrf(&session, ""));
+    }
+
+    #[test]
+    fn cookie_values_are_read_from_headers() {
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert(
+            axum::http::header::COOKIE,
+            "theme=dark; arssm_session=abc.def; arssm_csrf=xyz".parse().unwrap(),
+        );
+        assert_eq!(cookie_value(&headers, SESSION_COOKIE), Some("abc.def"));
+        assert_eq!(cookie_value(&headers, CSRF_COOKIE), Some("xyz"));
+        assert_eq!(cookie_value(&headers, "missing"), None);
+    }
+}
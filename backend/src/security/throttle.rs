@@ -0,0 +1,278 @@
+use backend::storage::LoginLimits;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttemptCheck {
+    /// The attempt may proceed after waiting this long. It is reserved until it is
+    /// finished with [`LoginThrottle::record_success`] or [`LoginThrottle::record_failure`].
+    Allowed(Duration),
+    /// As many attempts are in flight as failures are left before the lockout; retry shortly.
+    Busy,
+    /// The address is locked out for this many more seconds.
+    LockedOut(u64),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FailureRecord {
+    failures: u32,
+    in_flight: u32,
+    window_start: u64,
+    locked_until: Option<u64>,
+}
+
+/// Tracks failed logins per source address. Every failure delays the next attempt
+/// from that address a little longer, and too many failures lock it out for a while.
+/// Attempts in flight are tracked apart from failures: they add no delay, but no more
+/// of them are let through than failures are left before the lockout.
+#[derive(Clone)]
+pub struct LoginThrottle {
+    limits: Arc<Mutex<LoginLimits>>,
+    records: Arc<Mutex<HashMap<IpAddr, FailureRecord>>>,
+}
+
+impl LoginThrottle {
+    pub fn new(limits: LoginLimits) -> Self {
+        Self {
+            limits: Arc::new(Mutex::new(limits)),
+            records: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn set_limits(&self, limits: LoginLimits) {
+        *self.limits.lock().expect("login limits lock") = limits;
+    }
+
+    /// Reserves an attempt, so parallel requests cannot all get through before the
+    /// first of them has failed.
+    pub fn check(&self, ip: IpAddr, now: u64) -> AttemptCheck {
+        let limits = *self.limits.lock().expect("login limits lock");
+        let mut records = self.records.lock().expect("login throttle lock");
+        records.retain(|_, record| {
+            if !is_stale(&limits, record, now) {
+                return true;
+            }
+            // Attempts still in flight keep their reservations past an expired window.
+            record.failures = 0;
+            record.window_start = now;
+            record.locked_until = None;
+            record.in_flight > 0
+        });
+
+        let record = records.entry(ip).or_insert(FailureRecord {
+            failures: 0,
+            in_flight: 0,
+            window_start: now,
+            locked_until: None,
+        });
+        if let Some(locked_until) = record.locked_until {
+            return AttemptCheck::LockedOut(locked_until - now);
+        }
+        if record.failures + record.in_flight >= limits.max_failures {
+            return AttemptCheck::Busy;
+        }
+        record.in_flight += 1;
+        AttemptCheck::Allowed(progressive_delay(&limits, record.failures))
+    }
+
+    /// Finishes a reserved attempt that failed.
+    pub fn record_failure(&self, ip: IpAddr, now: u64) {
+        let limits = *self.limits.lock().expect("login limits lock");
+        let mut records = self.records.lock().expect("login throttle lock");
+        let Some(record) = records.get_mut(&ip) else {
+            return;
+        };
+        record.in_flight = record.in_flight.saturating_sub(1);
+        if record.failures == 0 {
+            record.window_start = now;
+        }
+        record.failures += 1;
+        if record.failures >= limits.max_failures && record.locked_until.is_none() {
+            record.locked_until = Some(now + limits.lockout_secs);
+        }
+    }
+
+    /// Remaining lockout of the address, if its failures have locked it out.
+    pub fn locked_for(&self, ip: IpAddr, now: u64) -> Option<u64> {
+        let records = self.records.lock().expect("login throttle lock");
+        records
+            .get(&ip)
+            .and_then(|record| record.locked_until)
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+    }
+
+    /// Finishes a reserved attempt that succeeded and forgives earlier failures.
+    pub fn record_success(&self, ip: IpAddr) {
+        let mut records = self.records.lock().expect("login throttle lock");
+        let Some(record) = records.get_mut(&ip) else {
+            return;
+        };
+        record.in_flight = record.in_flight.saturating_sub(1);
+        if record.in_flight == 0 {
+            records.remove(&ip);
+        } else {
+            record.failures = 0;
+            record.locked_until = None;
+        }
+    }
+}
+
+fn progressive_delay(limits: &LoginLimits, failures: u32) -> Duration {
+    if failures == 0 {
+        return Duration::ZERO;
+    }
+    let factor = 1u64 << (failures - 1).min(16);
+    Duration::from_millis(limits.base_delay_ms.saturating_mul(factor).min(limits.max_delay_ms))
+}
+
+fn is_stale(limits: &LoginLimits, record: &FailureRecord, now: u64) -> bool {
+    match record.locked_until {
+        Some(until) => until <= now,
+        None => now >= record.window_start + limits.window_secs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> LoginLimits {
+        LoginLimits {
+            max_failures: 5,
+            window_secs: 600,
+            lockout_secs: 900,
+            base_delay_ms: 250,
+            max_delay_ms: 1_000,
+        }
+    }
+
+    fn ip(last: u8) -> IpAddr {
+        IpAddr::from([192, 168, 1, last])
+    }
+
+    /// Makes an attempt that fails once it was let through.
+    fn fail(throttle: &LoginThrottle, ip: IpAddr, now: u64) -> AttemptCheck {
+        let check = throttle.check(ip, now);
+        if matches!(check, AttemptCheck::Allowed(_)) {
+            throttle.record_failure(ip, now);
+        }
+        check
+    }
+
+    fn concurrently<T: Send + 'static>(
+        threads: usize,
+        attempt: impl Fn() -> T + Send + Sync + 'static,
+    ) -> Vec<T> {
+        let barrier = Arc::new(std::sync::Barrier::new(threads));
+        let attempt = Arc::new(attempt);
+        (0..threads)
+            .map(|_| {
+                let barrier = Arc::clone(&barrier);
+                let attempt = Arc::clone(&attempt);
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    attempt()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().expect("attempt thread"))
+            .collect()
+    }
+
+    #[test]
+    fn burst_of_failures_delays_then_locks_out() {
+        let throttle = LoginThrottle::new(limits());
+        let attacker = ip(66);
+        let mut delays = Vec::new();
+        for attempt in 0..20u64 {
+            let now = 1_000 + attempt / 4;
+            match fail(&throttle, attacker, now) {
+                AttemptCheck::Allowed(delay) => delays.push(delay.as_millis()),
+                AttemptCheck::Busy => panic!("no attempt is in flight"),
+                AttemptCheck::LockedOut(remaining) => assert!(remaining > 800),
+            }
+        }
+        assert_eq!(delays, vec![0, 250, 500, 1_000, 1_000]);
+        assert_eq!(throttle.locked_for(attacker, 1_001), Some(900));
+        assert_eq!(throttle.check(attacker, 1_004), AttemptCheck::LockedOut(897));
+        assert_eq!(throttle.check(ip(7), 1_004), AttemptCheck::Allowed(Duration::ZERO));
+
+        assert_eq!(fail(&throttle, attacker, 1_901), AttemptCheck::Allowed(Duration::ZERO));
+        assert_eq!(
+            throttle.check(attacker, 1_901),
+            AttemptCheck::Allowed(Duration::from_millis(250))
+        );
+    }
+
+    #[test]
+    fn success_and_quiet_windows_reset_failures() {
+        let throttle = LoginThrottle::new(limits());
+        let user = ip(10);
+        for _ in 0..4 {
+            assert!(matches!(fail(&throttle, user, 1_000), AttemptCheck::Allowed(_)));
+        }
+        assert_eq!(throttle.check(user, 1_000), AttemptCheck::Allowed(Duration::from_millis(1_000)));
+        throttle.record_success(user);
+        assert_eq!(throttle.check(user, 1_000), AttemptCheck::Allowed(Duration::ZERO));
+        throttle.record_success(user);
+
+        for _ in 0..4 {
+            fail(&throttle, user, 2_000);
+        }
+        assert_eq!(throttle.check(user, 2_600), AttemptCheck::Allowed(Duration::ZERO));
+        assert_eq!(throttle.locked_for(user, 2_600), None);
+    }
+
+    #[test]
+    fn updated_limits_apply_to_the_next_attempt() {
+        let throttle = LoginThrottle::new(limits());
+        throttle.set_limits(LoginLimits {
+            max_failures: 2,
+            ..limits()
+        });
+        assert_eq!(fail(&throttle, ip(3), 1_000), AttemptCheck::Allowed(Duration::ZERO));
+        assert_eq!(throttle.locked_for(ip(3), 1_000), None);
+        assert_eq!(fail(&throttle, ip(3), 1_000), AttemptCheck::Allowed(Duration::from_millis(250)));
+        assert_eq!(throttle.locked_for(ip(3), 1_000), Some(900));
+        assert_eq!(throttle.check(ip(3), 1_000), AttemptCheck::LockedOut(900));
+    }
+
+    #[test]
+    fn concurrent_attempts_cannot_bypass_the_limit() {
+        let throttle = LoginThrottle::new(limits());
+        let attacker = throttle.clone();
+        // Every request reaches the throttle before any of them has failed.
+        let results = concurrently(20, move || fail(&attacker, ip(66), 1_000));
+
+        let allowed = results
+            .iter()
+            .filter(|result| matches!(result, AttemptCheck::Allowed(_)))
+            .count();
+        assert_eq!(allowed, 5);
+        assert_eq!(throttle.check(ip(66), 1_000), AttemptCheck::LockedOut(900));
+    }
+
+    #[test]
+    fn concurrent_successful_attempts_never_lock_out() {
+        let throttle = LoginThrottle::new(limits());
+        let client = throttle.clone();
+        let results = concurrently(20, move || loop {
+            match client.check(ip(20), 1_000) {
+                AttemptCheck::Allowed(delay) => {
+                    client.record_success(ip(20));
+                    break Some(delay);
+                }
+                AttemptCheck::Busy => std::thread::yield_now(),
+                AttemptCheck::LockedOut(_) => break None,
+            }
+        });
+
+        assert!(results.iter().all(|result| *result == Some(Duration::ZERO)));
+        assert_eq!(throttle.locked_for(ip(20), 1_000), None);
+        assert_eq!(throttle.check(ip(20), 1_000), AttemptCheck::Allowed(Duration::ZERO));
+    }
+}
@@ -0,0 +1,259 @@
+use super::{random_token, write_private_file};
+use super::users::Role;
+use backend::storage;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use tokio::sync::RwLock;
+
+const TOKEN_PREFIX: &str = "arssm_";
+const LAST_USED_PERSIST_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenScope {
+    Read,
+    RunControl,
+    ConfigWrite,
+}
+
+impl TokenScope {
+    pub const ALL: [TokenScope; 3] = [TokenScope::Read, TokenScope::RunControl, TokenScope::ConfigWrite];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::RunControl => "run-control",
+            Self::ConfigWrite => "config-write",
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Read => "Status, Logs und Konfiguration lesen",
+            Self::RunControl => "Server starten/stoppen, RCON und Spieler",
+            Self::ConfigWrite => "Settings, Config und SteamCMD ändern",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Vec<TokenScope>,
+    pub created_at: u64,
+    #[serde(default)]
+    pub created_by: String,
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    #[serde(default)]
+    pub last_used_at: Option<u64>,
+}
+
+impl ApiToken {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.map(|expires_at| expires_at <= now).unwrap_or(false)
+    }
+
+    pub fn has_scope(&self, scope: TokenScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// The role whose route group matches the token's widest scope.
+    pub fn role(&self) -> Role {
+        if self.has_scope(TokenScope::ConfigWrite) {
+            Role::Admin
+        } else if self.has_scope(TokenScope::RunControl) {
+            Role::Operator
+        } else {
+            Role::Viewer
+        }
+    }
+}
+
+pub fn tokens_path() -> PathBuf {
+    storage::base_dir().join("api_tokens.json")
+}
+
+/// API tokens are random, so a plain SHA-256 is enough to keep them useless if the file leaks.
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[derive(Clone)]
+pub struct TokenStore {
+    path: PathBuf,
+    tokens: Arc<RwLock<Vec<ApiToken>>>,
+}
+
+impl TokenStore {
+    pub async fn load(path: PathBuf) -> Result<Self, String> {
+        let tokens = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|err| format!("failed to parse API tokens: {err}"))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(format!("failed to read API tokens: {err}")),
+        };
+        Ok(Self {
+            path,
+            tokens: Arc::new(RwLock::new(tokens)),
+        })
+    }
+
+    pub async fn list(&self) -> Vec<ApiToken> {
+        let mut tokens = self.tokens.read().await.clone();
+        tokens.sort_by_key(|token| std::cmp::Reverse(token.created_at));
+        tokens
+    }
+
+    /// Creates a token and returns it together with the plaintext secret, which is not stored.
+    pub async fn create(
+        &self,
+        name: &str,
+        scopes: Vec<TokenScope>,
+        expires_at: Option<u64>,
+        created_by: &str,
+        now: u64,
+    ) -> Result<(ApiToken, String), String> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > 64 {
+            return Err("token name must be 1-64 characters".to_string());
+        }
+        if scopes.is_empty() {
+            return Err("select at least one scope".to_string());
+        }
+        if expires_at.map(|expires_at| expires_at <= now).unwrap_or(false) {
+            return Err("expiry must be in the future".to_string());
+        }
+
+        let secret = format!("{TOKEN_PREFIX}{}", random_token(40));
+        let token = ApiToken {
+            id: random_token(12),
+            name: name.to_string(),
+            token_hash: hash_token(&secret),
+            scopes,
+            created_at: now,
+            created_by: created_by.to_string(),
+            expires_at,
+            last_used_at: None,
+        };
+        let mut tokens = self.tokens.write().await;
+        tokens.push(token.clone());
+        self.persist(&tokens).await?;
+        Ok((token, secret))
+    }
+
+    pub async fn revoke(&self, id: &str) -> Result<ApiToken, String> {
+        let mut tokens = self.tokens.write().await;
+        let index = tokens
+            .iter()
+            .position(|token| token.id == id)
+            .ok_or_else(|| format!("token {id} not found"))?;
+        let token = tokens.remove(index);
+        self.persist(&tokens).await?;
+        Ok(token)
+    }
+
+    /// Returns the matching, unexpired token and records its use.
+    pub async fn authenticate(&self, secret: &str, now: u64) -> Option<ApiToken> {
+        if !secret.starts_with(TOKEN_PREFIX) {
+            return None;
+        }
+        let hash = hash_token(secret);
+        let mut tokens = self.tokens.write().await;
+        let token = tokens
+            .iter_mut()
+            .find(|token| bool::from(token.token_hash.as_bytes().ct_eq(hash.as_bytes())))?;
+        if token.is_expired(now) {
+            return None;
+        }
+        let previous = token.last_used_at.replace(now);
+        let found = token.clone();
+        // Recording every request would rewrite the file constantly; a minute's precision is enough.
+        if previous
+            .map(|previous| now.saturating_sub(previous) >= LAST_USED_PERSIST_SECS)
+            .unwrap_or(true)
+        {
+            if let Err(err) = self.persist(&tokens).await {
+                tracing::warn!("failed to record API token use: {err}");
+            }
+        }
+        Some(found)
+    }
+
+    async fn persist(&self, tokens: &[ApiToken]) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(tokens)
+            .map_err(|err| format!("failed to serialize API tokens: {err}"))?;
+        write_private_file(&self.path, &data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_store(name: &str) -> TokenStore {
+        let dir = std::env::temp_dir().join(format!("arssm-tokens-{name}-{}", std::process::id()));
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        TokenStore::load(dir.join("api_tokens.json")).await.expect("load")
+    }
+
+    #[tokio::test]
+    async fn tokens_authenticate_and_are_stored_hashed() {
+        let store = test_store("auth").await;
+        let (token, secret) = store
+            .create("deploy", vec![TokenScope::RunControl], None, "admin", 1_000)
+            .await
+            .expect("create");
+
+        let stored = tokio::fs::read_to_string(&store.path).await.expect("stored");
+        assert!(!stored.contains(&secret));
+        assert!(stored.contains(&token.token_hash));
+
+        let found = store.authenticate(&secret, 2_000).await.expect("token");
+        assert_eq!(found.id, token.id);
+        assert_eq!(found.last_used_at, Some(2_000));
+        assert!(found.has_scope(TokenScope::RunControl));
+        assert!(!found.has_scope(TokenScope::ConfigWrite));
+        assert!(store.authenticate("arssm_wrong", 2_000).await.is_none());
+
+        let reloaded = TokenStore::load(store.path.clone()).await.expect("reload");
+        assert_eq!(reloaded.list().await[0].last_used_at, Some(2_000));
+
+        let _ = tokio::fs::remove_dir_all(store.path.parent().unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn expired_and_revoked_tokens_are_rejected() {
+        let store = test_store("expiry").await;
+        let (token, secret) = store
+            .create("nightly", vec![TokenScope::Read], Some(5_000), "admin", 1_000)
+            .await
+            .expect("create");
+        assert!(store.authenticate(&secret, 4_999).await.is_some());
+        assert!(store.authenticate(&secret, 5_000).await.is_none());
+
+        let (_, other) = store
+            .create("ci", vec![TokenScope::Read], None, "admin", 1_000)
+            .await
+            .expect("create");
+        store.revoke(&token.id).await.expect("revoke");
+        assert!(store.revoke(&token.id).await.is_err());
+        assert!(store.authenticate(&other, 2_000).await.is_some());
+
+        assert!(store.create("empty", Vec::new(), None, "admin", 1_000).await.is_err());
+        assert!(store
+            .create("past", vec![TokenScope::Read], Some(500), "admin", 1_000)
+            .await
+            .is_err());
+
+        let _ = tokio::fs::remove_dir_all(store.path.parent().unwrap()).await;
+    }
+}
@@ -0,0 +1,281 @@
+use super::{hash_password, verify_password, write_private_file, Credentials};
+use backend::storage;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+const MIN_PASSWORD_LEN: usize = 8;
+const MAX_USERNAME_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Viewer,
+    Operator,
+    Admin,
+}
+
+impl Role {
+    pub const ALL: [Role; 3] = [Role::Viewer, Role::Operator, Role::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Operator => "operator",
+            Self::Admin => "admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "viewer" => Some(Self::Viewer),
+            "operator" => Some(Self::Operator),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Viewer => "Status und Logs",
+            Self::Operator => "Start/Stop/Restart, RCON und Spieler",
+            Self::Admin => "Profile, Settings, Pakete und Benutzer",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserAccount {
+    pub username: String,
+    pub password_hash: String,
+    pub role: Role,
+    #[serde(default)]
+    pub created_at: u64,
+}
+
+/// The authenticated caller, attached to each request as an extension.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub username: String,
+    pub role: Role,
+}
+
+pub fn users_path() -> PathBuf {
+    storage::base_dir().join("users.json")
+}
+
+/// Named accounts from `users.json`, plus the bootstrap admin from `credentials.json`
+/// which always exists so `reset-credentials` can recover access.
+#[derive(Clone)]
+pub struct UserStore {
+    path: PathBuf,
+    bootstrap: Arc<Credentials>,
+    users: Arc<RwLock<Vec<UserAccount>>>,
+}
+
+impl UserStore {
+    pub async fn load(path: PathBuf, bootstrap: Credentials) -> Result<Self, String> {
+        let users = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|err| format!("failed to parse users: {err}"))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(format!("failed to read users: {err}")),
+        };
+        Ok(Self {
+            path,
+            bootstrap: Arc::new(bootstrap),
+            users: Arc::new(RwLock::new(users)),
+        })
+    }
+
+    pub fn bootstrap_username(&self) -> &str {
+        &self.bootstrap.username
+    }
+
+    pub async fn list(&self) -> Vec<UserAccount> {
+        let mut users = self.users.read().await.clone();
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+        users
+    }
+
+    /// Looks up the current role of a user, e.g. for an existing session.
+    pub async fn find(&self, username: &str) -> Option<AuthUser> {
+        if username == self.bootstrap.username {
+            return Some(AuthUser {
+                username: username.to_string(),
+                role: Role::Admin,
+            });
+        }
+        let users = self.users.read().await;
+        users
+            .iter()
+            .find(|user| user.username == username)
+            .map(|user| AuthUser {
+                username: user.username.clone(),
+                role: user.role,
+            })
+    }
+
+    pub async fn authenticate(&self, username: &str, password: &str) -> Option<AuthUser> {
+        let account = {
+            let users = self.users.read().await;
+            users.iter().find(|user| user.username == username).cloned()
+        };
+        let bootstrap = self.bootstrap.clone();
+        let (username, password) = (username.to_string(), password.to_string());
+        // Argon2 verification is deliberately slow; keep it off the async workers.
+        tokio::task::spawn_blocking(move || match account {
+            Some(account) => verify_password(&password, &account.password_hash).then_some(AuthUser {
+                username: account.username,
+                role: account.role,
+            }),
+            None => bootstrap.verify(&username, &password).then_some(AuthUser {
+                username,
+                role: Role::Admin,
+            }),
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    pub async fn add(&self, username: &str, password: &str, role: Role) -> Result<(), String> {
+        let username = username.trim();
+        validate_username(username)?;
+        validate_password(password)?;
+        if username == self.bootstrap.username {
+            return Err(format!("{username} is the bootstrap admin account"));
+        }
+        let password_hash = hash_password_blocking(password).await?;
+        let mut users = self.users.write().await;
+        if users.iter().any(|user| user.username == username) {
+            return Err(format!("user {username} already exists"));
+        }
+        users.push(UserAccount {
+            username: username.to_string(),
+            password_hash,
+            role,
+            created_at: crate::services::current_epoch_seconds(),
+        });
+        self.persist(&users).await
+    }
+
+    /// Changes a user's role and returns the previous one.
+    pub async fn set_role(&self, username: &str, role: Role) -> Result<Role, String> {
+        let mut users = self.users.write().await;
+        let user = find_mut(&mut users, username)?;
+        let previous = std::mem::replace(&mut user.role, role);
+        self.persist(&users).await?;
+        Ok(previous)
+    }
+
+    pub async fn set_password(&self, username: &str, password: &str) -> Result<(), String> {
+        validate_password(password)?;
+        let password_hash = hash_password_blocking(password).await?;
+        let mut users = self.users.write().await;
+        let user = find_mut(&mut users, username)?;
+        user.password_hash = password_hash;
+        self.persist(&users).await
+    }
+
+    pub async fn remove(&self, username: &str) -> Result<(), String> {
+        let mut users = self.users.write().await;
+        let before = users.len();
+        users.retain(|user| user.username != username);
+        if users.len() == before {
+            return Err(format!("user {username} not found"));
+        }
+        self.persist(&users).await
+    }
+
+    async fn persist(&self, users: &[UserAccount]) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(users)
+            .map_err(|err| format!("failed to serialize users: {err}"))?;
+        write_private_file(&self.path, &data).await
+    }
+}
+
+/// Hashes on the blocking pool; Argon2 would otherwise stall an async worker.
+async fn hash_password_blocking(password: &str) -> Result<String, String> {
+    let password = password.to_string();
+    tokio::task::spawn_blocking(move || hash_password(&password))
+        .await
+        .map_err(|err| format!("password hashing failed: {err}"))?
+}
+
+fn find_mut<'a>(users: &'a mut [UserAccount], username: &str) -> Result<&'a mut UserAccount, String> {
+    users
+        .iter_mut()
+        .find(|user| user.username == username)
+        .ok_or_else(|| format!("user {username} not found"))
+}
+
+fn validate_username(username: &str) -> Result<(), String> {
+    if username.is_empty() || username.len() > MAX_USERNAME_LEN {
+        return Err(format!("username must be 1-{MAX_USERNAME_LEN} characters"));
+    }
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        return Err("username may only contain letters, digits, '_', '-' and '.'".to_string());
+    }
+    Ok(())
+}
+
+fn validate_password(password: &str) -> Result<(), String> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(format!("password must be at least {MIN_PASSWORD_LEN} characters"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_store(name: &str) -> UserStore {
+        let dir = std::env::temp_dir().join(format!("arssm-users-{name}-{}", std::process::id()));
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        let bootstrap = Credentials::new("root".to_string(), "bootstrap-pass").expect("hash");
+        UserStore::load(dir.join("users.json"), bootstrap).await.expect("load")
+    }
+
+    #[tokio::test]
+    async fn accounts_authenticate_with_their_role() {
+        let store = test_store("auth").await;
+        store.add("alice", "operator-pass", Role::Operator).await.expect("add");
+
+        let alice = store.authenticate("alice", "operator-pass").await.expect("alice");
+        assert_eq!(alice.role, Role::Operator);
+        assert!(store.authenticate("alice", "wrong-pass").await.is_none());
+        let root = store.authenticate("root", "bootstrap-pass").await.expect("root");
+        assert_eq!(root.role, Role::Admin);
+        assert!(store.authenticate("bob", "operator-pass").await.is_none());
+
+        store.set_role("alice", Role::Viewer).await.expect("role");
+        let reloaded = UserStore::load(store.path.clone(), (*store.bootstrap).clone())
+            .await
+            .expect("reload");
+        let alice = reloaded.authenticate("alice", "operator-pass").await.expect("alice");
+        assert_eq!(alice.role, Role::Viewer);
+
+        let _ = tokio::fs::remove_dir_all(store.path.parent().unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn invalid_accounts_are_rejected() {
+        let store = test_store("invalid").await;
+        assert!(store.add("root", "long-enough", Role::Viewer).await.is_err());
+        assert!(store.add("bad name", "long-enough", Role::Viewer).await.is_err());
+        assert!(store.add("carol", "short", Role::Viewer).await.is_err());
+        store.add("carol", "long-enough", Role::Viewer).await.expect("add");
+        assert!(store.add("carol", "long-enough", Role::Admin).await.is_err());
+        store.remove("carol").await.expect("remove");
+        assert!(store.remove("carol").await.is_err());
+        assert!(Role::Admin > Role::Operator && Role::Operator > Role::Viewer);
+
+        let _ = tokio::fs::remove_dir_all(store.path.parent().unwrap()).await;
+    }
+}
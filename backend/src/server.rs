@@ -0,0 +1,249 @@
+use crate::security::tls;
+use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
+use backend::storage::WebServerSettings;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+const CERT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+pub const USAGE: &str = "usage: backend [--bind <ip>] [--port <port>] [--tls-cert <pem> --tls-key <pem>] [--plain-http]
+       backend reset-credentials";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transport {
+    /// The generated certificate from `tls::ensure_tls_cert`, renewed automatically.
+    SelfSigned,
+    Pem { cert: PathBuf, key: PathBuf },
+    PlainHttp,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListenConfig {
+    pub addr: SocketAddr,
+    pub transport: Transport,
+}
+
+impl ListenConfig {
+    /// Combines settings, `ARSSM_*` environment variables and command line flags,
+    /// in increasing order of precedence.
+    pub fn resolve(
+        settings: &WebServerSettings,
+        env: impl Fn(&str) -> Option<String>,
+        args: &[String],
+    ) -> Result<Self, String> {
+        let mut merged = settings.clone();
+        if let Some(value) = env("ARSSM_BIND") {
+            merged.bind_address = value;
+        }
+        if let Some(value) = env("ARSSM_PORT") {
+            merged.port = parse_port(&value)?;
+        }
+        if let Some(value) = env("ARSSM_TLS_CERT") {
+            merged.tls_cert_path = value;
+        }
+        if let Some(value) = env("ARSSM_TLS_KEY") {
+            merged.tls_key_path = value;
+        }
+        if let Some(value) = env("ARSSM_PLAIN_HTTP") {
+            merged.plain_http = matches!(value.trim(), "1" | "true" | "yes" | "on");
+        }
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| format!("{arg} needs a value\n{USAGE}"))
+            };
+            match arg.as_str() {
+                "--bind" => merged.bind_address = value()?,
+                "--port" => merged.port = parse_port(&value()?)?,
+                "--tls-cert" => merged.tls_cert_path = value()?,
+                "--tls-key" => merged.tls_key_path = value()?,
+                "--plain-http" => merged.plain_http = true,
+                other => return Err(format!("unknown argument {other}\n{USAGE}")),
+            }
+        }
+
+        merged.validate()?;
+        let ip: IpAddr = merged
+            .bind_address
+            .trim()
+            .parse()
+            .map_err(|_| format!("bind address {} is not an IP address", merged.bind_address))?;
+        let transport = if merged.plain_http {
+            Transport::PlainHttp
+        } else if merged.tls_cert_path.trim().is_empty() {
+            Transport::SelfSigned
+        } else {
+            Transport::Pem {
+                cert: PathBuf::from(merged.tls_cert_path.trim()),
+                key: PathBuf::from(merged.tls_key_path.trim()),
+            }
+        };
+        Ok(Self {
+            addr: SocketAddr::new(ip, merged.port),
+            transport,
+        })
+    }
+
+    pub fn is_plain_http(&self) -> bool {
+        self.transport == Transport::PlainHttp
+    }
+
+    /// The certificate file being served, if any.
+    pub fn certificate_path(&self) -> Option<PathBuf> {
+        match &self.transport {
+            Transport::SelfSigned => Some(tls::cert_path()),
+            Transport::Pem { cert, .. } => Some(cert.clone()),
+            Transport::PlainHttp => None,
+        }
+    }
+}
+
+fn parse_port(value: &str) -> Result<u16, String> {
+    value
+        .trim()
+        .parse::<u16>()
+        .ok()
+        .filter(|port| *port != 0)
+        .ok_or_else(|| format!("invalid port {value}"))
+}
+
+pub async fn serve(app: Router, config: ListenConfig) -> Result<(), String> {
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    let (cert, key) = match config.transport {
+        Transport::PlainHttp => {
+            if !config.addr.ip().is_loopback() {
+                warn!(
+                    "plain HTTP is served on {}; only expose it through a reverse proxy that terminates TLS",
+                    config.addr
+                );
+            }
+            info!("server listening on http://{}", config.addr);
+            return axum_server::bind(config.addr)
+                .serve(service)
+                .await
+                .map_err(|err| format!("server failed: {err}"));
+        }
+        Transport::SelfSigned => {
+            let (cert, key) = (tls::cert_path(), tls::key_path());
+            tls::ensure_tls_cert(&cert, &key).await?;
+            tokio::spawn(renew_self_signed(cert.clone(), key.clone()));
+            (cert, key)
+        }
+        Transport::Pem { cert, key } => (cert, key),
+    };
+
+    let tls_config = RustlsConfig::from_pem_file(&cert, &key)
+        .await
+        .map_err(|err| format!("failed to load TLS certificate {}: {err}", cert.display()))?;
+    match tls::describe_certificate(&cert).await {
+        Ok(summary) => info!("TLS certificate fingerprint (SHA-256): {}", summary.fingerprint),
+        Err(err) => warn!("{err}"),
+    }
+    tokio::spawn(watch_certificates(tls_config.clone(), cert, key));
+
+    info!("server listening on https://{}", config.addr);
+    axum_server::bind_rustls(config.addr, tls_config)
+        .serve(service)
+        .await
+        .map_err(|err| format!("server failed: {err}"))
+}
+
+/// Reloads the certificate when either PEM file changes, so renewed certificates
+/// are picked up without a restart. A broken pair keeps the previous one active.
+async fn watch_certificates(tls_config: RustlsConfig, cert: PathBuf, key: PathBuf) {
+    let mut last_seen = modified_times(&cert, &key).await;
+    let mut interval = tokio::time::interval(CERT_POLL_INTERVAL);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let current = modified_times(&cert, &key).await;
+        if current == last_seen {
+            continue;
+        }
+        match tls_config.reload_from_pem_file(&cert, &key).await {
+            Ok(()) => {
+                info!("reloaded TLS certificate from {}", cert.display());
+                last_seen = current;
+            }
+            Err(err) => warn!("failed to reload TLS certificate from {}: {err}", cert.display()),
+        }
+    }
+}
+
+/// Regenerates the self-signed certificate before it expires or when the host's
+/// addresses change; `watch_certificates` then picks up the new files.
+async fn renew_self_signed(cert: PathBuf, key: PathBuf) {
+    let mut interval = tokio::time::interval(tls::RENEW_CHECK_INTERVAL);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        if let Err(err) = tls::ensure_tls_cert(&cert, &key).await {
+            warn!("failed to renew self-signed TLS certificate: {err}");
+        }
+    }
+}
+
+async fn modified_times(cert: &Path, key: &Path) -> (Option<SystemTime>, Option<SystemTime>) {
+    async fn modified(path: &Path) -> Option<SystemTime> {
+        tokio::fs::metadata(path).await.ok()?.modified().ok()
+    }
+    (modified(cert).await, modified(key).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn resolve(env: &[(&str, &str)], args: &[&str]) -> Result<ListenConfig, String> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        ListenConfig::resolve(&WebServerSettings::default(), |key| env.get(key).cloned(), &args)
+    }
+
+    #[test]
+    fn defaults_listen_on_all_interfaces_with_self_signed_tls() {
+        let config = resolve(&[], &[]).expect("config");
+        assert_eq!(config.addr, "0.0.0.0:3000".parse().unwrap());
+        assert_eq!(config.transport, Transport::SelfSigned);
+    }
+
+    #[test]
+    fn command_line_overrides_environment() {
+        let config = resolve(
+            &[("ARSSM_BIND", "127.0.0.1"), ("ARSSM_PORT", "8443")],
+            &["--port", "9443", "--tls-cert", "/etc/arssm/cert.pem", "--tls-key", "/etc/arssm/key.pem"],
+        )
+        .expect("config");
+        assert_eq!(config.addr, "127.0.0.1:9443".parse().unwrap());
+        assert_eq!(
+            config.transport,
+            Transport::Pem {
+                cert: PathBuf::from("/etc/arssm/cert.pem"),
+                key: PathBuf::from("/etc/arssm/key.pem"),
+            }
+        );
+
+        let config = resolve(&[("ARSSM_PLAIN_HTTP", "1")], &["--bind", "::1"]).expect("config");
+        assert_eq!(config.addr, "[::1]:3000".parse().unwrap());
+        assert!(config.is_plain_http());
+    }
+
+    #[test]
+    fn invalid_listener_options_are_rejected() {
+        assert!(resolve(&[("ARSSM_PORT", "0")], &[]).is_err());
+        assert!(resolve(&[], &["--bind", "example.com"]).is_err());
+        assert!(resolve(&[], &["--tls-cert", "cert.pem"]).is_err());
+        assert!(resolve(&[], &["--port"]).is_err());
+        assert!(resolve(&[], &["--verbose"]).is_err());
+    }
+}
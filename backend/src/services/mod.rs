@@ -1,7 +1,8 @@
 use backend::{
-    config_gen::generate_server_config,
+    config_gen::{apply_game_overrides, generate_server_config, ConfigMod},
     defaults,
-    models::{ModPackage, ServerProfile},
+    models::{ModEntry, ModMetadata, ModPackage, ModRole, ModVersionStatus, ServerProfile},
+    steamcmd::{effective_branch, WorkDirBranch},
     storage::AppSettings,
     workshop,
 };
@@ -10,6 +11,7 @@ pub fn generate_config_for_profile(
     profile: &ServerProfile,
     settings: &AppSettings,
     packages: &[ModPackage],
+    mods: &[ModEntry],
 ) -> Result<serde_json::Value, String> {
     let scenario = profile
         .selected_scenario_id_path
@@ -29,16 +31,136 @@ pub fn generate_config_for_profile(
     let mut config = generate_server_config(scenario, &mod_ids, Some(&profile.display_name))?;
     defaults::apply_default_server_json_settings(&mut config, settings);
     defaults::apply_profile_overrides(&mut config, profile)?;
-    backend::config_gen::apply_game_overrides(
+    apply_game_overrides(
         &mut config,
         scenario,
-        &mod_ids,
+        &config_mods(profile, &mod_ids, mods),
         Some(&profile.display_name),
     )?;
 
     Ok(config)
 }
 
+/// Adds the known name and the pinned version to each mod of the profile. Names
+/// come from the profile's last resolve, then from the mod list. Versions come
+/// from `mod_version_pins` only.
+pub fn config_mods(profile: &ServerProfile, mod_ids: &[String], mods: &[ModEntry]) -> Vec<ConfigMod> {
+    mod_ids
+        .iter()
+        .map(|mod_id| {
+            let entry = mods.iter().find(|entry| &entry.mod_id == mod_id);
+            let resolved = resolved_metadata(profile, mods, mod_id);
+            let name = resolved
+                .and_then(|metadata| metadata.name.clone())
+                .or_else(|| {
+                    entry
+                        .map(|entry| entry.name.trim())
+                        .filter(|name| !name.is_empty() && name != mod_id)
+                        .map(str::to_string)
+                });
+            ConfigMod {
+                mod_id: mod_id.clone(),
+                name,
+                version: profile.mod_version_pins.get(mod_id).cloned(),
+            }
+        })
+        .collect()
+}
+
+/// With `pin_mod_versions` set, pins every resolved mod that has no pin yet to
+/// its resolved version. Existing pins are kept, so later resolves only show
+/// up as pending upgrades. Returns whether a pin was added.
+pub fn pin_resolved_versions(profile: &mut ServerProfile) -> bool {
+    if !profile.pin_mod_versions {
+        return false;
+    }
+    let mut changed = false;
+    for (mod_id, metadata) in profile.mod_metadata.iter() {
+        let Some(version) = metadata.version.as_ref() else {
+            continue;
+        };
+        if !profile.mod_version_pins.contains_key(mod_id) {
+            profile.mod_version_pins.insert(mod_id.clone(), version.clone());
+            changed = true;
+        }
+    }
+    changed
+}
+
+fn resolved_metadata<'a>(
+    profile: &'a ServerProfile,
+    mods: &'a [ModEntry],
+    mod_id: &str,
+) -> Option<&'a ModMetadata> {
+    profile.mod_metadata.get(mod_id).or_else(|| {
+        mods.iter()
+            .find(|entry| entry.mod_id == mod_id)
+            .map(|entry| &entry.metadata)
+    })
+}
+
+/// Every mod of the profile, root first, with its pin and latest resolved version.
+pub fn mod_version_statuses(
+    profile: &ServerProfile,
+    packages: &[ModPackage],
+    mods: &[ModEntry],
+) -> Vec<ModVersionStatus> {
+    let root = profile
+        .root_mod_id
+        .clone()
+        .or_else(|| workshop::extract_workshop_id_from_url(&profile.workshop_url));
+    let roles = root
+        .into_iter()
+        .map(|mod_id| (mod_id, ModRole::Root))
+        .chain(profile.dependency_mod_ids.iter().map(|mod_id| (mod_id.clone(), ModRole::Dependency)))
+        .chain(
+            collect_optional_mod_ids(profile, packages)
+                .into_iter()
+                .map(|mod_id| (mod_id, ModRole::Optional)),
+        );
+
+    let mut statuses: Vec<ModVersionStatus> = Vec::new();
+    for (mod_id, role) in roles {
+        if statuses.iter().any(|status| status.mod_id == mod_id) {
+            continue;
+        }
+        let metadata = resolved_metadata(profile, mods, &mod_id).cloned().unwrap_or_default();
+        let pinned_version = profile.mod_version_pins.get(&mod_id).cloned();
+        let upgrade_available = matches!(
+            (&pinned_version, &metadata.version),
+            (Some(pinned), Some(latest)) if pinned != latest
+        );
+        statuses.push(ModVersionStatus {
+            mod_id,
+            role,
+            metadata,
+            pinned_version,
+            upgrade_available,
+        });
+    }
+    statuses
+}
+
+/// Moves the pins of the given mods, or of all mods when `mod_ids` is empty, to
+/// their latest resolved version. Returns the mods that were upgraded.
+pub fn accept_upgrades(
+    profile: &mut ServerProfile,
+    statuses: &[ModVersionStatus],
+    mod_ids: &[String],
+) -> Vec<String> {
+    let mut accepted = Vec::new();
+    for status in statuses.iter().filter(|status| status.upgrade_available) {
+        if !mod_ids.is_empty() && !mod_ids.contains(&status.mod_id) {
+            continue;
+        }
+        if let Some(latest) = status.metadata.version.clone() {
+            profile.mod_version_pins.insert(status.mod_id.clone(), latest);
+            accepted.push(status.mod_id.clone());
+        }
+    }
+    accepted
+}
+
 pub fn collect_optional_mod_ids(profile: &ServerProfile, packages: &[ModPackage]) -> Vec<String> {
     let mut ids = Vec::new();
     for package_id in profile.optional_package_ids.iter() {
@@ -50,6 +172,16 @@ pub fn collect_optional_mod_ids(profile: &ServerProfile, packages: &[ModPackage]
     ids
 }
 
+/// Workshop details for a mod from the most recently resolved profile that covers it.
+pub fn known_mod_metadata(profiles: &[ServerProfile], mod_id: &str) -> ModMetadata {
+    profiles
+        .iter()
+        .filter(|profile| profile.mod_metadata.contains_key(mod_id))
+        .max_by(|left, right| left.last_resolved_at.cmp(&right.last_resolved_at))
+        .and_then(|profile| profile.mod_metadata.get(mod_id).cloned())
+        .unwrap_or_default()
+}
+
 pub fn parse_mod_id_input(input: &str) -> Option<String> {
     let trimmed = input.trim();
     if trimmed.contains("/workshop/") {
@@ -111,6 +243,24 @@ pub fn effective_path_value(override_value: &Option<String>, fallback: &str) ->
     clean_path_input(raw)
 }
 
+pub fn profile_branch(profile: &ServerProfile, settings: &AppSettings) -> Option<String> {
+    effective_branch(profile.steam_branch_override.as_deref(), &settings.steam_branch)
+}
+
+pub fn work_dir_branches(profiles: &[ServerProfile], settings: &AppSettings) -> Vec<WorkDirBranch> {
+    profiles
+        .iter()
+        .map(|profile| WorkDirBranch {
+            profile_id: profile.profile_id.clone(),
+            work_dir: effective_path_value(
+                &profile.reforger_server_work_dir_override,
+                &settings.reforger_server_work_dir,
+            ),
+            branch: profile_branch(profile, settings),
+        })
+        .collect()
+}
+
 pub fn update_list_selection(current: Option<Vec<String>>, action: &str, item_id: &str) -> Vec<String> {
     let mut selected = current.unwrap_or_default();
     match action {
@@ -157,14 +307,150 @@ pub fn current_datetime() -> String {
     now.format(&format).unwrap_or_else(|_| "n/a".to_string())
 }
 
-pub fn format_duration(started_at: u64) -> String {
-    let now = std::time::SystemTime::now()
+pub fn current_epoch_seconds() -> u64 {
+    std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .map(|duration| duration.as_secs())
-        .unwrap_or(0);
+        .unwrap_or(0)
+}
+
+pub fn format_duration(started_at: u64) -> String {
+    let now = current_epoch_seconds();
     let total = now.saturating_sub(started_at);
     let hours = total / 3600;
     let minutes = (total % 3600) / 60;
     let seconds = total % 60;
     format!("{hours}h {minutes}m {seconds}s")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ROOT: &str = "59727DAE364DEADB";
+    const DEP: &str = "5AAAC70D754245DD";
+    const OPTIONAL: &str = "5965550F24A0C152";
+
+    fn metadata(name: &str, version: &str) -> ModMetadata {
+        ModMetadata {
+            name: Some(name.to_string()),
+            version: Some(version.to_string()),
+            ..ModMetadata::default()
+        }
+    }
+
+    fn profile() -> ServerProfile {
+        serde_json::from_value(json!({
+            "profile_id": "profile-1",
+            "display_name": "Coop",
+            "workshop_url": format!("https://reforger.armaplatform.com/workshop/{ROOT}-Coop"),
+            "root_mod_id": ROOT,
+            "dependency_mod_ids": [DEP],
+            "optional_mod_ids": [OPTIONAL, DEP],
+            "mod_metadata": {
+                ROOT: metadata("Coop", "1.2.0"),
+                DEP: metadata("Some Mod", "1.0.15"),
+            },
+            "mod_version_pins": { ROOT: "1.1.0", DEP: "1.0.15" },
+        }))
+        .expect("profile")
+    }
+
+    fn mods() -> Vec<ModEntry> {
+        vec![ModEntry {
+            mod_id: OPTIONAL.to_string(),
+            name: "Where Am I".to_string(),
+            metadata: metadata("Where Am I", "2.0.0"),
+        }]
+    }
+
+    #[test]
+    fn config_mods_write_only_pinned_versions() {
+        let mut profile = profile();
+        let mod_ids = vec![ROOT.to_string(), DEP.to_string(), OPTIONAL.to_string()];
+        let versions = |profile: &ServerProfile| -> Vec<Option<String>> {
+            config_mods(profile, &mod_ids, &mods())
+                .into_iter()
+                .map(|entry| entry.version)
+                .collect()
+        };
+
+        let entries = config_mods(&profile, &mod_ids, &mods());
+        assert_eq!(entries[0].name.as_deref(), Some("Coop"));
+        assert_eq!(entries[2].name.as_deref(), Some("Where Am I"));
+        assert_eq!(versions(&profile), vec![Some("1.1.0".to_string()), Some("1.0.15".to_string()), None]);
+
+        profile.pin_mod_versions = true;
+        assert_eq!(versions(&profile)[2], None);
+    }
+
+    #[test]
+    fn pin_resolved_versions_only_adds_missing_pins() {
+        let mut profile = profile();
+        profile.mod_metadata.insert(OPTIONAL.to_string(), metadata("Where Am I", "2.0.0"));
+        assert!(!pin_resolved_versions(&mut profile));
+        assert!(!profile.mod_version_pins.contains_key(OPTIONAL));
+
+        profile.pin_mod_versions = true;
+        assert!(pin_resolved_versions(&mut profile));
+        assert_eq!(profile.mod_version_pins[ROOT], "1.1.0");
+        assert_eq!(profile.mod_version_pins[OPTIONAL], "2.0.0");
+        assert!(!pin_resolved_versions(&mut profile));
+    }
+
+    #[test]
+    fn pending_upgrades_compare_pins_with_resolved_versions() {
+        let mut profile = profile();
+        let statuses = mod_version_statuses(&profile, &[], &mods());
+
+        let roles: Vec<(&str, ModRole)> = statuses
+            .iter()
+            .map(|status| (status.mod_id.as_str(), status.role))
+            .collect();
+        assert_eq!(
+            roles,
+            vec![(ROOT, ModRole::Root), (DEP, ModRole::Dependency), (OPTIONAL, ModRole::Optional)]
+        );
+        let pending: Vec<bool> = statuses.iter().map(|status| status.upgrade_available).collect();
+        assert_eq!(pending, vec![true, false, false]);
+
+        assert!(accept_upgrades(&mut profile, &statuses, &[DEP.to_string()]).is_empty());
+        assert_eq!(accept_upgrades(&mut profile, &statuses, &[]), vec![ROOT.to_string()]);
+        assert_eq!(profile.mod_version_pins[ROOT], "1.2.0");
+        assert!(mod_version_statuses(&profile, &[], &mods())
+            .iter()
+            .all(|status| !status.upgrade_available));
+    }
+
+    #[test]
+    fn re_resolved_versions_wait_for_the_upgrade_to_be_accepted() {
+        let mut profile = profile();
+        profile.mod_version_pins.clear();
+        profile.pin_mod_versions = true;
+        pin_resolved_versions(&mut profile);
+        let mod_ids = vec![ROOT.to_string(), DEP.to_string()];
+        let versions = |profile: &ServerProfile| -> Vec<Option<String>> {
+            config_mods(profile, &mod_ids, &[])
+                .into_iter()
+                .map(|entry| entry.version)
+                .collect()
+        };
+        let pinned = vec![Some("1.2.0".to_string()), Some("1.0.15".to_string())];
+        assert_eq!(versions(&profile), pinned);
+
+        profile.mod_metadata.insert(DEP.to_string(), metadata("Some Mod", "1.0.16"));
+        assert!(!pin_resolved_versions(&mut profile));
+        assert_eq!(versions(&profile), pinned);
+        let statuses = mod_version_statuses(&profile, &[], &[]);
+        let pending: Vec<&str> = statuses
+            .iter()
+            .filter(|status| status.upgrade_available)
+            .map(|status| status.mod_id.as_str())
+            .collect();
+        assert_eq!(pending, vec![DEP]);
+
+        assert_eq!(accept_upgrades(&mut profile, &statuses, &[]), vec![DEP.to_string()]);
+        assert_eq!(versions(&profile)[1].as_deref(), Some("1.0.16"));
+    }
+}
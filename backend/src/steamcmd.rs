@@ -0,0 +1,708 @@
+use crate::storage::{path_key, paths_equal};
+use std::{
+    collections::{HashMap, VecDeque},
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    process::Command,
+    sync::{broadcast, mpsc, Mutex, OwnedMutexGuard},
+    task::JoinHandle,
+};
+
+pub const REFORGER_SERVER_APP_ID: u32 = 1874900;
+const MAX_LOG_LINES: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, schemars::JsonSchema)]
+pub struct UpdateProgress {
+    pub state: String,
+    pub percent: f32,
+}
+
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct UpdateResult {
+    pub success: bool,
+    pub message: String,
+    pub work_dir: String,
+    pub finished_at: u64,
+}
+
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct SteamcmdStatus {
+    pub running: bool,
+    pub work_dir: Option<String>,
+    pub progress: Option<UpdateProgress>,
+    pub last_result: Option<UpdateResult>,
+}
+
+/// A started update: its own output lines and the final result.
+pub struct UpdateJob {
+    pub output: mpsc::UnboundedReceiver<String>,
+    pub result: JoinHandle<UpdateResult>,
+}
+
+#[derive(Clone)]
+pub struct SteamcmdManager {
+    inner: Arc<Mutex<UpdateInner>>,
+    sender: broadcast::Sender<String>,
+    work_dir_locks: Arc<std::sync::Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>>,
+}
+
+struct UpdateInner {
+    running: bool,
+    work_dir: Option<String>,
+    progress: Option<UpdateProgress>,
+    last_result: Option<UpdateResult>,
+    buffer: VecDeque<String>,
+    job_output: Option<mpsc::UnboundedSender<String>>,
+}
+
+#[derive(Default)]
+struct OutputSummary {
+    success_line: Option<String>,
+    error_line: Option<String>,
+}
+
+impl Default for SteamcmdManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SteamcmdManager {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(200);
+        Self {
+            inner: Arc::new(Mutex::new(UpdateInner {
+                running: false,
+                work_dir: None,
+                progress: None,
+                last_result: None,
+                buffer: VecDeque::new(),
+                job_output: None,
+            })),
+            sender,
+            work_dir_locks: Arc::new(std::sync::Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<String> {
+        self.sender.subscribe()
+    }
+
+    pub async fn status(&self) -> SteamcmdStatus {
+        let inner = self.inner.lock().await;
+        SteamcmdStatus {
+            running: inner.running,
+            work_dir: inner.work_dir.clone(),
+            progress: inner.progress.clone(),
+            last_result: inner.last_result.clone(),
+        }
+    }
+
+    pub async fn tail(&self, limit: usize) -> Vec<String> {
+        let inner = self.inner.lock().await;
+        let start = inner.buffer.len().saturating_sub(limit);
+        inner.buffer.iter().skip(start).cloned().collect()
+    }
+
+    pub async fn is_updating(&self, work_dir: &str) -> bool {
+        let inner = self.inner.lock().await;
+        inner.running
+            && inner
+                .work_dir
+                .as_deref()
+                .map(|current| paths_equal(current, work_dir))
+                .unwrap_or(false)
+    }
+
+    /// Serializes work on one server install: hold the guard from checking that no server or
+    /// update uses the work dir until the update or server launch has been started.
+    pub async fn lock_work_dir(&self, work_dir: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.work_dir_locks.lock().expect("work dir locks");
+            Arc::clone(locks.entry(path_key(work_dir)).or_default())
+        };
+        lock.lock_owned().await
+    }
+
+    /// Claims the updater and runs SteamCMD in the background; only one update runs at a time.
+    pub async fn start(
+        &self,
+        steamcmd_dir: &str,
+        work_dir: &str,
+        branch: Option<&str>,
+    ) -> Result<UpdateJob, String> {
+        let (output_tx, output) = mpsc::unbounded_channel();
+        {
+            let mut inner = self.inner.lock().await;
+            if inner.running {
+                return Err("a SteamCMD update is already running".to_string());
+            }
+            inner.running = true;
+            inner.work_dir = Some(work_dir.to_string());
+            inner.progress = None;
+            inner.buffer.clear();
+            inner.job_output = Some(output_tx);
+        }
+
+        let manager = self.clone();
+        let steamcmd_dir = steamcmd_dir.to_string();
+        let work_dir = work_dir.to_string();
+        let branch = branch.map(str::to_string);
+        let result = tokio::spawn(async move {
+            let result = match manager
+                .execute(&steamcmd_dir, &work_dir, branch.as_deref())
+                .await
+            {
+                Ok(message) => UpdateResult {
+                    success: true,
+                    message,
+                    work_dir: work_dir.clone(),
+                    finished_at: current_epoch_seconds(),
+                },
+                Err(message) => UpdateResult {
+                    success: false,
+                    message,
+                    work_dir: work_dir.clone(),
+                    finished_at: current_epoch_seconds(),
+                },
+            };
+            let outcome = if result.success { "succeeded" } else { "failed" };
+            manager
+                .push_line(format!("[arssm] SteamCMD update {outcome}: {}", result.message))
+                .await;
+
+            let mut inner = manager.inner.lock().await;
+            inner.running = false;
+            inner.job_output = None;
+            inner.last_result = Some(result.clone());
+            result
+        });
+        Ok(UpdateJob { output, result })
+    }
+
+    async fn execute(
+        &self,
+        steamcmd_dir: &str,
+        work_dir: &str,
+        branch: Option<&str>,
+    ) -> Result<String, String> {
+        let executable = steamcmd_executable(Path::new(steamcmd_dir));
+        if tokio::fs::metadata(&executable).await.is_err() {
+            return Err(format!("SteamCMD not found at {}", executable.display()));
+        }
+        tokio::fs::create_dir_all(work_dir)
+            .await
+            .map_err(|err| format!("failed to create work dir: {err}"))?;
+
+        self.push_line(format!(
+            "[arssm] running SteamCMD for app {REFORGER_SERVER_APP_ID} ({} branch) in {work_dir}",
+            branch.unwrap_or("public")
+        ))
+        .await;
+        let mut child = Command::new(&executable)
+            .current_dir(steamcmd_dir)
+            .args(update_args(work_dir, branch))
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("failed to start SteamCMD: {err}"))?;
+
+        let stderr_task = child.stderr.take().map(|stderr| {
+            let manager = self.clone();
+            tokio::spawn(async move {
+                let mut summary = OutputSummary::default();
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    manager.handle_line(line, &mut summary).await;
+                }
+                summary
+            })
+        });
+
+        let mut summary = OutputSummary::default();
+        if let Some(stdout) = child.stdout.take() {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                self.handle_line(line, &mut summary).await;
+            }
+        }
+        if let Some(task) = stderr_task {
+            if let Ok(stderr_summary) = task.await {
+                summary.error_line = summary.error_line.or(stderr_summary.error_line);
+            }
+        }
+
+        let status = child
+            .wait()
+            .await
+            .map_err(|err| format!("failed to wait for SteamCMD: {err}"))?;
+        if let Some(error) = summary.error_line {
+            return Err(error);
+        }
+        if let Some(success) = summary.success_line {
+            return Ok(success);
+        }
+        if status.success() {
+            Ok("SteamCMD finished".to_string())
+        } else {
+            Err(format!("SteamCMD exited with {status}"))
+        }
+    }
+
+    async fn handle_line(&self, line: String, summary: &mut OutputSummary) {
+        let line = line.trim_end_matches('\r').to_string();
+        if let Some(progress) = parse_progress(&line) {
+            self.inner.lock().await.progress = Some(progress);
+        }
+        if is_success_line(&line) {
+            summary.success_line = Some(line.trim().to_string());
+        } else if is_error_line(&line) && summary.error_line.is_none() {
+            summary.error_line = Some(line.trim().to_string());
+        }
+        self.push_line(line).await;
+    }
+
+    async fn push_line(&self, line: String) {
+        {
+            let mut inner = self.inner.lock().await;
+            if inner.buffer.len() >= MAX_LOG_LINES {
+                inner.buffer.pop_front();
+            }
+            inner.buffer.push_back(line.clone());
+            if let Some(output) = &inner.job_output {
+                let _ = output.send(line.clone());
+            }
+        }
+        let _ = self.sender.send(line);
+    }
+}
+
+pub fn steamcmd_executable(steamcmd_dir: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        return steamcmd_dir.join("steamcmd.exe");
+    }
+    let script = steamcmd_dir.join("steamcmd.sh");
+    if script.exists() {
+        script
+    } else {
+        steamcmd_dir.join("steamcmd")
+    }
+}
+
+pub fn update_args(work_dir: &str, branch: Option<&str>) -> Vec<OsString> {
+    let mut args: Vec<OsString> = [
+        "+force_install_dir",
+        work_dir,
+        "+login",
+        "anonymous",
+        "+app_update",
+        &REFORGER_SERVER_APP_ID.to_string(),
+    ]
+    .into_iter()
+    .map(OsString::from)
+    .collect();
+    if let Some(branch) = branch {
+        args.push(OsString::from("-beta"));
+        args.push(OsString::from(branch));
+    }
+    args.push(OsString::from("validate"));
+    args.push(OsString::from("+quit"));
+    args
+}
+
+/// Empty and `public` both mean the default branch.
+pub fn normalize_branch(branch: &str) -> Option<String> {
+    let branch = branch.trim();
+    if branch.is_empty() || branch.eq_ignore_ascii_case("public") {
+        None
+    } else {
+        Some(branch.to_string())
+    }
+}
+
+pub fn effective_branch(profile_override: Option<&str>, global: &str) -> Option<String> {
+    match profile_override.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => normalize_branch(value),
+        None => normalize_branch(global),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct InstalledBuild {
+    pub build_id: String,
+    pub branch: Option<String>,
+    pub last_updated: Option<u64>,
+}
+
+pub fn appmanifest_path(work_dir: &str) -> PathBuf {
+    PathBuf::from(work_dir)
+        .join("steamapps")
+        .join(format!("appmanifest_{REFORGER_SERVER_APP_ID}.acf"))
+}
+
+pub async fn read_installed_build(work_dir: &str) -> Result<Option<InstalledBuild>, String> {
+    let path = appmanifest_path(work_dir);
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => Ok(parse_appmanifest(&contents)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(format!("failed to read {}: {err}", path.display())),
+    }
+}
+
+pub fn parse_appmanifest(contents: &str) -> Option<InstalledBuild> {
+    let values = parse_key_values(contents);
+    let build_id = values.get("appstate.buildid")?.clone();
+    let branch = values
+        .get("appstate.userconfig.betakey")
+        .or_else(|| values.get("appstate.mountedconfig.betakey"))
+        .and_then(|value| normalize_branch(value));
+    let last_updated = values
+        .get("appstate.lastupdated")
+        .and_then(|value| value.parse().ok());
+    Some(InstalledBuild {
+        build_id,
+        branch,
+        last_updated,
+    })
+}
+
+/// Flattens Valve KeyValues text into lowercase dotted paths.
+fn parse_key_values(contents: &str) -> HashMap<String, String> {
+    enum Token {
+        Text(String),
+        Open,
+        Close,
+    }
+
+    let mut tokens = Vec::new();
+    let mut chars = contents.chars();
+    while let Some(current) = chars.next() {
+        match current {
+            '"' => {
+                let mut text = String::new();
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => text.extend(chars.next()),
+                        '"' => break,
+                        other => text.push(other),
+                    }
+                }
+                tokens.push(Token::Text(text));
+            }
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            _ => {}
+        }
+    }
+
+    let mut values = HashMap::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut key: Option<String> = None;
+    for token in tokens {
+        match token {
+            Token::Text(text) => match key.take() {
+                Some(name) => {
+                    let mut full = path.clone();
+                    full.push(name);
+                    values.insert(full.join(".").to_lowercase(), text);
+                }
+                None => key = Some(text),
+            },
+            Token::Open => path.extend(key.take()),
+            Token::Close => {
+                path.pop();
+            }
+        }
+    }
+    values
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkDirBranch {
+    pub profile_id: String,
+    pub work_dir: String,
+    pub branch: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct BranchConflict {
+    pub work_dir: String,
+    pub profiles: Vec<(String, String)>,
+}
+
+impl BranchConflict {
+    pub fn describe(&self) -> String {
+        let profiles: Vec<String> = self
+            .profiles
+            .iter()
+            .map(|(profile_id, branch)| format!("{profile_id} ({branch})"))
+            .collect();
+        format!(
+            "Profiles share the work dir {} but want different branches: {}",
+            self.work_dir,
+            profiles.join(", ")
+        )
+    }
+}
+
+pub fn branch_conflicts(entries: &[WorkDirBranch]) -> Vec<BranchConflict> {
+    let mut groups: Vec<Vec<&WorkDirBranch>> = Vec::new();
+    for entry in entries {
+        match groups
+            .iter_mut()
+            .find(|group| paths_equal(&group[0].work_dir, &entry.work_dir))
+        {
+            Some(group) => group.push(entry),
+            None => groups.push(vec![entry]),
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|group| group.iter().any(|entry| entry.branch != group[0].branch))
+        .map(|group| BranchConflict {
+            work_dir: group[0].work_dir.clone(),
+            profiles: group
+                .iter()
+                .map(|entry| {
+                    (
+                        entry.profile_id.clone(),
+                        entry.branch.clone().unwrap_or_else(|| "public".to_string()),
+                    )
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Parses lines like ` Update state (0x61) downloading, progress: 45.67 (1234 / 5678)`.
+pub fn parse_progress(line: &str) -> Option<UpdateProgress> {
+    let (head, tail) = line.split_once("progress:")?;
+    let percent = tail.split_whitespace().next()?.parse::<f32>().ok()?;
+    let state = head
+        .rsplit_once(')')
+        .map(|(_, state)| state)
+        .unwrap_or(head)
+        .trim()
+        .trim_end_matches(',')
+        .to_string();
+    Some(UpdateProgress { state, percent })
+}
+
+fn is_success_line(line: &str) -> bool {
+    line.trim_start().starts_with("Success! App")
+}
+
+fn is_error_line(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("Error!") || line.starts_with("ERROR!") || line.contains("FAILED")
+}
+
+fn current_epoch_seconds() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_lines_are_parsed() {
+        let progress =
+            parse_progress(" Update state (0x61) downloading, progress: 45.67 (1234 / 5678)")
+                .expect("progress");
+        assert_eq!(progress.state, "downloading");
+        assert!((progress.percent - 45.67).abs() < f32::EPSILON);
+        assert!(parse_progress("Loading Steam API...OK").is_none());
+    }
+
+    #[test]
+    fn update_args_install_the_reforger_server() {
+        let args = update_args("/srv/reforger", None);
+        let args: Vec<&str> = args.iter().map(|arg| arg.to_str().unwrap()).collect();
+        assert_eq!(
+            args,
+            [
+                "+force_install_dir",
+                "/srv/reforger",
+                "+login",
+                "anonymous",
+                "+app_update",
+                "1874900",
+                "validate",
+                "+quit"
+            ]
+        );
+    }
+
+    #[test]
+    fn beta_branch_is_passed_to_app_update() {
+        let args = update_args("/srv/reforger", Some("experimental"));
+        let args: Vec<&str> = args.iter().map(|arg| arg.to_str().unwrap()).collect();
+        assert_eq!(
+            &args[4..],
+            ["+app_update", "1874900", "-beta", "experimental", "validate", "+quit"]
+        );
+        assert_eq!(effective_branch(Some("public"), "experimental"), None);
+        assert_eq!(effective_branch(None, "experimental").as_deref(), Some("experimental"));
+        assert_eq!(effective_branch(Some(" "), ""), None);
+    }
+
+    #[test]
+    fn appmanifest_build_and_branch_are_read() {
+        let manifest = r#""AppState"
+{
+	"appid"		"1874900"
+	"name"		"Arma Reforger Server"
+	"buildid"		"16123456"
+	"LastUpdated"		"1760000000"
+	"UserConfig"
+	{
+		"BetaKey"		"experimental"
+	}
+	"MountedConfig"
+	{
+		"BetaKey"		"experimental"
+	}
+}"#;
+        let build = parse_appmanifest(manifest).expect("build");
+        assert_eq!(build.build_id, "16123456");
+        assert_eq!(build.branch.as_deref(), Some("experimental"));
+        assert_eq!(build.last_updated, Some(1760000000));
+        assert!(parse_appmanifest("\"AppState\" { }").is_none());
+    }
+
+    #[test]
+    fn shared_work_dirs_with_different_branches_conflict() {
+        let entry = |profile_id: &str, work_dir: &str, branch: Option<&str>| WorkDirBranch {
+            profile_id: profile_id.to_string(),
+            work_dir: work_dir.to_string(),
+            branch: branch.map(str::to_string),
+        };
+        let conflicts = branch_conflicts(&[
+            entry("pve", "/srv/reforger", None),
+            entry("event", "/srv/reforger/", Some("experimental")),
+            entry("pvp", "/srv/reforger", None),
+            entry("test", "/srv/experimental", Some("experimental")),
+        ]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].profiles.len(), 3);
+        assert!(conflicts[0].describe().contains("event (experimental)"));
+    }
+
+    #[cfg(unix)]
+    fn fake_steamcmd(name: &str, script: &str) -> (PathBuf, PathBuf) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!(
+            "arssm-steamcmd-{name}-{}-{}",
+            std::process::id(),
+            current_epoch_seconds()
+        ));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let path = dir.join("steamcmd.sh");
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).expect("write script");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .expect("chmod script");
+        (dir.clone(), dir.join("server"))
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn successful_update_reports_progress_and_result() {
+        let (steamcmd_dir, work_dir) = fake_steamcmd(
+            "ok",
+            "echo ' Update state (0x61) downloading, progress: 12.50 (1 / 8)'\n\
+             echo ' Update state (0x81) verifying install, progress: 100.00 (8 / 8)'\n\
+             echo \"Success! App '1874900' fully installed.\"",
+        );
+        let manager = SteamcmdManager::new();
+        let mut receiver = manager.subscribe();
+        let mut job = manager
+            .start(&steamcmd_dir.to_string_lossy(), &work_dir.to_string_lossy(), None)
+            .await
+            .expect("start");
+        assert!(manager.is_updating(&work_dir.to_string_lossy()).await);
+
+        let result = job.result.await.expect("job");
+        assert!(result.success, "{}", result.message);
+        assert_eq!(result.message, "Success! App '1874900' fully installed.");
+        let status = manager.status().await;
+        assert!(!status.running);
+        assert_eq!(status.progress.expect("progress").percent, 100.0);
+        assert!(receiver.recv().await.expect("line").contains("running SteamCMD"));
+        assert!(job.output.try_recv().expect("job line").contains("running SteamCMD"));
+        assert!(work_dir.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failed_update_reports_error_line() {
+        let (steamcmd_dir, work_dir) = fake_steamcmd(
+            "fail",
+            "echo \"Error! App '1874900' state is 0x202 after update job.\"\nexit 8",
+        );
+        let manager = SteamcmdManager::new();
+        let result = manager
+            .start(&steamcmd_dir.to_string_lossy(), &work_dir.to_string_lossy(), None)
+            .await
+            .expect("start")
+            .result
+            .await
+            .expect("job");
+        assert!(!result.success);
+        assert!(result.message.contains("0x202"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn only_one_update_runs_at_a_time() {
+        let (steamcmd_dir, work_dir) = fake_steamcmd("busy", "sleep 1");
+        let manager = SteamcmdManager::new();
+        let job = manager
+            .start(&steamcmd_dir.to_string_lossy(), &work_dir.to_string_lossy(), None)
+            .await
+            .expect("start");
+        assert!(manager
+            .start(&steamcmd_dir.to_string_lossy(), &work_dir.to_string_lossy(), None)
+            .await
+            .is_err());
+        assert!(job.result.await.expect("job").success);
+    }
+
+    #[tokio::test]
+    async fn work_dir_lock_is_shared_by_equal_paths() {
+        let manager = SteamcmdManager::new();
+        let guard = manager.lock_work_dir("/srv/reforger").await;
+        let blocked = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            manager.lock_work_dir("/srv/reforger/"),
+        )
+        .await;
+        assert!(blocked.is_err());
+        let _other = manager.lock_work_dir("/srv/other").await;
+        drop(guard);
+        let _again = manager.lock_work_dir("/srv/reforger").await;
+    }
+
+    #[tokio::test]
+    async fn missing_steamcmd_fails() {
+        let dir = std::env::temp_dir().join(format!("arssm-steamcmd-missing-{}", std::process::id()));
+        let result = SteamcmdManager::new()
+            .start(&dir.to_string_lossy(), &dir.join("server").to_string_lossy(), None)
+            .await
+            .expect("start")
+            .result
+            .await
+            .expect("job");
+        assert!(!result.success);
+        assert!(result.message.contains("not found"));
+    }
+}
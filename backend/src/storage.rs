@@ -1,8 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
-use crate::models::{ModEntry, ModPackage, ServerProfile};
+use crate::models::{BanRecord, ModEntry, ModPackage, ServerProfile};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct AppSettings {
     pub steamcmd_dir: String,
     pub reforger_server_exe: String,
@@ -14,6 +14,146 @@ pub struct AppSettings {
     pub server_json_defaults: serde_json::Value,
     #[serde(default)]
     pub server_json_enabled: std::collections::HashMap<String, bool>,
+    #[serde(default)]
+    pub dependency_change_action: DependencyChangeAction,
+    #[serde(default = "default_stop_grace_period_secs")]
+    pub stop_grace_period_secs: u64,
+    #[serde(default)]
+    pub steam_branch: String,
+    #[serde(default)]
+    pub login_limits: LoginLimits,
+    #[serde(default)]
+    pub web_server: WebServerSettings,
+    #[serde(default)]
+    pub workshop_cache: WorkshopCacheSettings,
+}
+
+fn default_stop_grace_period_secs() -> u64 {
+    30
+}
+
+/// Listener settings, read once at startup. Empty certificate paths mean the
+/// generated self-signed certificate is used.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct WebServerSettings {
+    pub bind_address: String,
+    pub port: u16,
+    pub tls_cert_path: String,
+    pub tls_key_path: String,
+    /// Serve plain HTTP, e.g. behind a local reverse proxy that terminates TLS.
+    pub plain_http: bool,
+}
+
+impl Default for WebServerSettings {
+    fn default() -> Self {
+        Self {
+            bind_address: "0.0.0.0".to_string(),
+            port: 3000,
+            tls_cert_path: String::new(),
+            tls_key_path: String::new(),
+            plain_http: false,
+        }
+    }
+}
+
+impl WebServerSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.bind_address.trim().parse::<std::net::IpAddr>().is_err() {
+            return Err(format!("bind address {} is not an IP address", self.bind_address));
+        }
+        if self.port == 0 {
+            return Err("port must be between 1 and 65535".to_string());
+        }
+        if self.tls_cert_path.trim().is_empty() != self.tls_key_path.trim().is_empty() {
+            return Err("TLS certificate and key paths must be set together".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Caching of fetched workshop pages, applied without a restart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct WorkshopCacheSettings {
+    /// Cached pages younger than this are used without asking the workshop.
+    pub ttl_secs: u64,
+    /// Resolve from cached pages only and never contact the workshop.
+    pub offline: bool,
+}
+
+impl Default for WorkshopCacheSettings {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 6 * 60 * 60,
+            offline: false,
+        }
+    }
+}
+
+/// Failed-login limits applied per source address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct LoginLimits {
+    /// Failures within `window_secs` that trigger a lockout.
+    pub max_failures: u32,
+    pub window_secs: u64,
+    pub lockout_secs: u64,
+    /// Delay before checking the next attempt after the first failure; doubles per failure.
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for LoginLimits {
+    fn default() -> Self {
+        Self {
+            max_failures: 5,
+            window_secs: 15 * 60,
+            lockout_secs: 15 * 60,
+            base_delay_ms: 500,
+            max_delay_ms: 8_000,
+        }
+    }
+}
+
+impl LoginLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_failures == 0 {
+            return Err("login max_failures must be at least 1".to_string());
+        }
+        if self.window_secs == 0 || self.lockout_secs == 0 {
+            return Err("login window_secs and lockout_secs must be positive".to_string());
+        }
+        if self.base_delay_ms > self.max_delay_ms {
+            return Err("login base_delay_ms must not exceed max_delay_ms".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyChangeAction {
+    #[default]
+    Warn,
+    Block,
+}
+
+impl DependencyChangeAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Warn => "warn",
+            Self::Block => "block",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "warn" => Some(Self::Warn),
+            "block" => Some(Self::Block),
+            _ => None,
+        }
+    }
 }
 
 impl Default for AppSettings {
@@ -29,6 +169,12 @@ impl Default for AppSettings {
                 active_profile_id: None,
                 server_json_defaults: serde_json::Value::Null,
                 server_json_enabled: std::collections::HashMap::new(),
+                dependency_change_action: DependencyChangeAction::default(),
+                stop_grace_period_secs: default_stop_grace_period_secs(),
+                steam_branch: String::new(),
+                login_limits: LoginLimits::default(),
+                web_server: WebServerSettings::default(),
+                workshop_cache: WorkshopCacheSettings::default(),
             }
         } else {
             let data_dir = data_dir();
@@ -44,11 +190,21 @@ impl Default for AppSettings {
                 active_profile_id: None,
                 server_json_defaults: serde_json::Value::Null,
                 server_json_enabled: std::collections::HashMap::new(),
+                dependency_change_action: DependencyChangeAction::default(),
+                stop_grace_period_secs: default_stop_grace_period_secs(),
+                steam_branch: String::new(),
+                login_limits: LoginLimits::default(),
+                web_server: WebServerSettings::default(),
+                workshop_cache: WorkshopCacheSettings::default(),
             }
         }
     }
 }
 impl AppSettings {
+    pub fn stop_grace_period(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.stop_grace_period_secs)
+    }
+
     pub fn validate(&self) -> Result<(), String> {
         for (field, value) in [
             ("steamcmd_dir", &self.steamcmd_dir),
@@ -60,7 +216,8 @@ impl AppSettings {
                 return Err(format!("{field} must not be empty"));
             }
         }
-        Ok(())
+        self.login_limits.validate()?;
+        self.web_server.validate()
     }
 }
 
@@ -87,6 +244,16 @@ pub fn data_dir() -> PathBuf {
     PathBuf::from("arssm-data")
 }
 
+/// Canonical form of a path for comparisons; falls back to the cleaned-up path if it does not exist.
+pub fn path_key(path: &str) -> PathBuf {
+    let path = Path::new(path.trim());
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.components().collect())
+}
+
+pub fn paths_equal(left: &str, right: &str) -> bool {
+    path_key(left) == path_key(right)
+}
+
 pub fn settings_path() -> PathBuf {
     base_dir().join("settings.json")
 }
@@ -99,6 +266,10 @@ pub fn profile_path(profile_id: &str) -> PathBuf {
     profiles_dir().join(format!("{profile_id}.json"))
 }
 
+pub fn bans_path(profile_id: &str) -> PathBuf {
+    profiles_dir().join(profile_id).join("bans.json")
+}
+
 pub fn generated_config_path(work_dir: &str, profile_id: &str) -> PathBuf {
     PathBuf::from(work_dir)
         .join("configs")
@@ -114,6 +285,10 @@ pub fn packages_path() -> PathBuf {
     base_dir().join("packages.json")
 }
 
+pub fn workshop_cache_dir() -> PathBuf {
+    base_dir().join("workshop_cache")
+}
+
 pub fn logs_dir() -> PathBuf {
     base_dir().join("logs")
 }
@@ -242,6 +417,35 @@ pub async fn delete_profile(profile_id: &str) -> Result<(), String> {
     Ok(())
 }
 
+pub async fn load_bans(profile_id: &str) -> Result<Vec<BanRecord>, String> {
+    let path = bans_path(profile_id);
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|err| format!("failed to parse bans: {err}")),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(format!("failed to read bans: {err}")),
+    }
+}
+
+pub async fn save_bans(profile_id: &str, bans: &[BanRecord]) -> Result<(), String> {
+    let path = bans_path(profile_id);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|err| format!("failed to create bans dir: {err}"))?;
+    }
+    let data = serde_json::to_string_pretty(bans)
+        .map_err(|err| format!("failed to serialize bans: {err}"))?;
+    let tmp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp_path, data)
+        .await
+        .map_err(|err| format!("failed to write temp bans: {err}"))?;
+    // Renaming over the old file keeps either the old or the new bans on a crash.
+    tokio::fs::rename(&tmp_path, &path)
+        .await
+        .map_err(|err| format!("failed to move bans into place: {err}"))
+}
+
 pub async fn load_mods() -> Result<Vec<ModEntry>, String> {
     let path = mods_path();
     match tokio::fs::read_to_string(&path).await {
@@ -0,0 +1,146 @@
+use crate::forms::AuditQuery;
+use crate::security::audit::AuditEntry;
+use crate::services::format_resolve_timestamp;
+use crate::views::layout::{breadcrumb, render_layout};
+use crate::views::settings::render_settings_tabs;
+
+pub fn render_audit_page(
+    entries: &[AuditEntry],
+    query: &AuditQuery,
+    limit: usize,
+    error: Option<&str>,
+) -> String {
+    let notice = error
+        .map(|value| format!("<p class=\"text-danger\">{}</p>", html_escape::encode_text(value)))
+        .unwrap_or_default();
+    let value = |field: &Option<String>| {
+        html_escape::encode_double_quoted_attribute(field.as_deref().unwrap_or("")).to_string()
+    };
+
+    let mut rows = String::new();
+    for entry in entries {
+        rows.push_str(&format!(
+            r#"<tr>
+              <td class="arssm-text text-nowrap">{time}</td>
+              <td class="arssm-text">{actor}</td>
+              <td class="arssm-text">{ip}</td>
+              <td><code>{action}</code></td>
+              <td class="arssm-text">{target}</td>
+              <td>{changes}</td>
+            </tr>"#,
+            time = format_resolve_timestamp(Some(&entry.timestamp.to_string())).unwrap_or_default(),
+            actor = html_escape::encode_text(&entry.actor),
+            ip = html_escape::encode_text(entry.source_ip.as_deref().unwrap_or("n/a")),
+            action = html_escape::encode_text(&entry.action),
+            target = html_escape::encode_text(&entry.target),
+            changes = render_changes(entry),
+        ));
+    }
+    if rows.is_empty() {
+        rows.push_str("<tr><td colspan=\"6\" class=\"arssm-text\">Keine Einträge.</td></tr>");
+    }
+    let truncated = if entries.len() >= limit {
+        format!("<p class=\"text-muted small\">Showing the newest {limit} matches; the export contains all of them.</p>")
+    } else {
+        String::new()
+    };
+
+    let export_query = serde_urlencoded::to_string([
+        ("actor", query.actor.as_deref().unwrap_or("")),
+        ("action", query.action.as_deref().unwrap_or("")),
+        ("target", query.target.as_deref().unwrap_or("")),
+        ("since", query.since.as_deref().unwrap_or("")),
+        ("until", query.until.as_deref().unwrap_or("")),
+    ])
+    .unwrap_or_default();
+    let export_query = html_escape::encode_double_quoted_attribute(&export_query).to_string();
+
+    let content = format!(
+        r#"<h1 class="h3 mb-3">Settings</h1>
+        {notice}
+        {tabs}
+        <h2 class="h5">Audit log</h2>
+        <p class="text-muted">Administrative actions from profiles, packages, settings and server control. Times are UTC.</p>
+        <form method="get" action="/settings/audit" class="row g-2 align-items-end mb-3">
+          <div class="col-md-2">
+            <label class="form-label" for="audit-actor">Actor</label>
+            <input class="form-control form-control-sm arssm-input" id="audit-actor" name="actor" value="{actor}">
+          </div>
+          <div class="col-md-2">
+            <label class="form-label" for="audit-action">Action</label>
+            <input class="form-control form-control-sm arssm-input" id="audit-action" name="action" value="{action}" placeholder="e.g. profile or run.stop">
+          </div>
+          <div class="col-md-2">
+            <label class="form-label" for="audit-target">Target</label>
+            <input class="form-control form-control-sm arssm-input" id="audit-target" name="target" value="{target}">
+          </div>
+          <div class="col-md-2">
+            <label class="form-label" for="audit-since">From</label>
+            <input class="form-control form-control-sm arssm-input" id="audit-since" name="since" type="date" value="{since}">
+          </div>
+          <div class="col-md-2">
+            <label class="form-label" for="audit-until">To</label>
+            <input class="form-control form-control-sm arssm-input" id="audit-until" name="until" type="date" value="{until}">
+          </div>
+          <div class="col-md-2 d-flex gap-2">
+            <button class="btn btn-sm btn-arssm-primary" type="submit">Filter</button>
+            <a class="btn btn-sm btn-arssm-secondary" href="/settings/audit">Reset</a>
+          </div>
+        </form>
+        <div class="d-flex gap-2 mb-3">
+          <a class="btn btn-sm btn-arssm-secondary" href="/api/audit/export?{export_query}&amp;format=jsonl">Export JSONL</a>
+          <a class="btn btn-sm btn-arssm-secondary" href="/api/audit/export?{export_query}&amp;format=csv">Export CSV</a>
+        </div>
+        {truncated}
+        <table class="table table-sm align-middle arssm-table">
+          <thead>
+            <tr>
+              <th>Time</th>
+              <th>Actor</th>
+              <th>Source</th>
+              <th>Action</th>
+              <th>Target</th>
+              <th>Changes</th>
+            </tr>
+          </thead>
+          <tbody>{rows}</tbody>
+        </table>"#,
+        tabs = render_settings_tabs("audit"),
+        actor = value(&query.actor),
+        action = value(&query.action),
+        target = value(&query.target),
+        since = value(&query.since),
+        until = value(&query.until),
+    );
+
+    render_layout(
+        "ARSSM Audit",
+        "settings",
+        vec![
+            breadcrumb("Settings", Some("/settings".to_string())),
+            breadcrumb("Audit", None),
+        ],
+        &content,
+    )
+}
+
+fn render_changes(entry: &AuditEntry) -> String {
+    let side = |label: &str, value: &Option<serde_json::Value>| {
+        value
+            .as_ref()
+            .filter(|value| !value.as_object().is_some_and(|map| map.is_empty()))
+            .map(|value| {
+                let pretty = serde_json::to_string_pretty(value).unwrap_or_default();
+                format!(
+                    "<div class=\"small text-muted\">{label}</div><pre class=\"small mb-1\">{}</pre>",
+                    html_escape::encode_text(&pretty)
+                )
+            })
+            .unwrap_or_default()
+    };
+    let body = side("Before", &entry.before) + &side("After", &entry.after);
+    if body.is_empty() {
+        return String::new();
+    }
+    format!("<details><summary class=\"small\">Details</summary>{body}</details>")
+}
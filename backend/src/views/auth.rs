@@ -0,0 +1,9 @@
+use crate::views::layout::template_env;
+use minijinja::context;
+
+pub fn render_login_page(error: Option<&str>, next: &str) -> String {
+    template_env()
+        .get_template("layouts/login.html")
+        .and_then(|template| template.render(context! { error => error, next => next }))
+        .unwrap_or_else(|err| format!("Template error: {err}"))
+}
@@ -1,13 +1,42 @@
 use crate::views::layout::{breadcrumb, render_layout};
+use backend::models::ServerProfile;
 use backend::runner::RunStatus;
 
-pub fn render_dashboard_page(profile_count: usize, package_count: usize, settings_status: &str) -> String {
+pub fn render_dashboard_page(
+    profiles: &[ServerProfile],
+    package_count: usize,
+    settings_status: &str,
+    warnings: &[String],
+) -> String {
+    let mut status_cards = String::new();
+    for profile in profiles {
+        status_cards.push_str(&format!(
+            r#"<div class="col-lg-6">
+            <div id="server-status-card-{id}" hx-get="/partials/server-status-card?profile_id={id}" hx-trigger="load, every 5s" hx-swap="outerHTML"></div>
+          </div>"#,
+            id = html_escape::encode_double_quoted_attribute(&profile.profile_id),
+        ));
+    }
+    if status_cards.is_empty() {
+        status_cards.push_str(
+            r#"<div class="col-lg-6">
+            <div class="card card-body">
+              <h2 class="h6 text-uppercase text-muted">Server Status</h2>
+              <p class="mb-0 text-muted">No profiles yet.</p>
+            </div>
+          </div>"#,
+        );
+    }
+
+    let warnings = warnings
+        .iter()
+        .map(|warning| format!("<p class=\"text-warning mb-1\">{}</p>", html_escape::encode_text(warning)))
+        .collect::<String>();
+
     let content = format!(
         r#"<h1 class="h3 mb-3">Dashboard</h1>
+        {warnings}
         <div class="row g-3">
-          <div class="col-lg-6">
-            <div id="server-status-card" hx-get="/partials/server-status-card" hx-trigger="load, every 5s" hx-swap="outerHTML"></div>
-          </div>
           <div class="col-md-6 col-lg-3">
             <div class="card card-body">
               <h2 class="h6 text-uppercase text-muted">Profile</h2>
@@ -22,8 +51,12 @@ pub fn render_dashboard_page(profile_count: usize, package_count: usize, setting
               <p class="small text-muted mb-0">Optional Mods verfügbar</p>
             </div>
           </div>
+        </div>
+        <div class="row g-3 mt-1">
+          {status_cards}
         </div>"#,
-        profile_count = profile_count,
+        status_cards = status_cards,
+        profile_count = profiles.len(),
         package_count = package_count,
         settings_status = html_escape::encode_text(settings_status),
     );
@@ -38,38 +71,82 @@ pub fn render_dashboard_page(profile_count: usize, package_count: usize, setting
 
 pub fn render_server_status_card(
     status: &RunStatus,
-    active_profile_name: Option<&str>,
+    profile_name: Option<&str>,
+    build: Option<&str>,
     message: Option<&str>,
 ) -> String {
-    let run_state = if status.running { "running" } else { "stopped" };
-    let profile_name = active_profile_name.unwrap_or("none");
+    let run_state = if status.running && status.server_info.is_some() {
+        "running (joinable)"
+    } else if status.running {
+        "running (not joinable yet)"
+    } else if status.restarting {
+        "restarting"
+    } else {
+        "stopped"
+    };
+    let last_exit = status
+        .last_exit
+        .as_ref()
+        .map(|exit| exit.describe())
+        .unwrap_or_else(|| "n/a".to_string());
+    let profile_name = profile_name.unwrap_or(status.profile_id.as_str());
     let notice = message
-        .map(|value| format!("<p class=\"text-warning mb-2\">{value}</p>"))
+        .map(|value| format!("<p class=\"text-warning mb-2\">{}</p>", html_escape::encode_text(value)))
+        .unwrap_or_default();
+    let card_id = format!("server-status-card-{}", status.profile_id);
+    let server_info = status
+        .server_info
+        .as_ref()
+        .map(|info| {
+            format!(
+                "<p class=\"mb-1\"><strong>Server:</strong> {name} · {map} · {players}/{max_players} players</p>",
+                name = html_escape::encode_text(&info.name),
+                map = html_escape::encode_text(&info.map),
+                players = info.players,
+                max_players = info.max_players,
+            )
+        })
+        .unwrap_or_default();
+    let build = build
+        .map(|value| format!("<p class=\"mb-1\"><strong>Build:</strong> {}</p>", html_escape::encode_text(value)))
         .unwrap_or_default();
 
+    let mut actions = String::new();
+    for (action, label, class) in [
+        ("start", "Start", "btn-arssm-primary"),
+        ("stop", "Stop", "btn-arssm-danger"),
+        ("restart", "Restart", "btn-arssm-secondary"),
+    ] {
+        actions.push_str(&format!(
+            r##"<form method="post" action="/partials/server-status-card" hx-post="/partials/server-status-card" hx-target="#{card_id}" hx-swap="outerHTML">
+              <input type="hidden" name="action" value="{action}">
+              <input type="hidden" name="profile_id" value="{profile_id}">
+              <button class="btn btn-sm {class}" type="submit">{label}</button>
+            </form>"##,
+            card_id = html_escape::encode_double_quoted_attribute(&card_id),
+            profile_id = html_escape::encode_double_quoted_attribute(&status.profile_id),
+        ));
+    }
+
     format!(
-        r##"<div id="server-status-card" class="card card-body">
+        r#"<div id="{card_id}" class="card card-body">
           <h2 class="h6 text-uppercase text-muted">Server Status</h2>
           {notice}
           <p class="mb-1"><strong>Status:</strong> {run_state}</p>
-          <p class="mb-3"><strong>Aktives Profil:</strong> {profile_name}</p>
+          <p class="mb-1"><strong>Profil:</strong> {profile_name}</p>
+          {server_info}
+          {build}
+          <p class="mb-3 small text-muted">Restarts: {restart_count} · Last exit: {last_exit}</p>
           <div class="d-flex flex-wrap gap-2">
-            <form method="post" action="/partials/server-status-card" hx-post="/partials/server-status-card" hx-target="#server-status-card" hx-swap="outerHTML">
-              <input type="hidden" name="action" value="start">
-              <button class="btn btn-sm btn-arssm-primary" type="submit">Start</button>
-            </form>
-            <form method="post" action="/partials/server-status-card" hx-post="/partials/server-status-card" hx-target="#server-status-card" hx-swap="outerHTML">
-              <input type="hidden" name="action" value="stop">
-              <button class="btn btn-sm btn-arssm-danger" type="submit">Stop</button>
-            </form>
-            <form method="post" action="/partials/server-status-card" hx-post="/partials/server-status-card" hx-target="#server-status-card" hx-swap="outerHTML">
-              <input type="hidden" name="action" value="restart">
-              <button class="btn btn-sm btn-arssm-secondary" type="submit">Restart</button>
-            </form>
+            {actions}
           </div>
-        </div>"##,
+        </div>"#,
+        card_id = html_escape::encode_double_quoted_attribute(&card_id),
         notice = notice,
         run_state = run_state,
         profile_name = html_escape::encode_text(profile_name),
+        restart_count = status.restart_count,
+        last_exit = html_escape::encode_text(&last_exit),
+        actions = actions,
     )
 }
@@ -1,3 +1,5 @@
+use backend::models::ModMetadata;
+
 pub fn render_hidden_ids(name: &str, ids: &[String]) -> String {
     let joined = ids.join(",");
     format!(
@@ -6,3 +8,57 @@ pub fn render_hidden_ids(name: &str, ids: &[String]) -> String {
         value = html_escape::encode_text(&joined),
     )
 }
+
+/// A mod's workshop name with its ID and known details, or the bare ID when
+/// nothing has been resolved for it.
+pub fn render_mod_label(mod_id: &str, metadata: Option<&ModMetadata>) -> String {
+    let id = format!("<code>{}</code>", html_escape::encode_text(mod_id));
+    let Some(metadata) = metadata.filter(|metadata| !metadata.is_empty()) else {
+        return id;
+    };
+
+    let mut details = Vec::new();
+    if let Some(version) = metadata.version.as_deref() {
+        details.push(format!("v{version}"));
+    }
+    if let Some(author) = metadata.author.as_deref() {
+        details.push(format!("by {author}"));
+    }
+    if let Some(size) = metadata.size_bytes {
+        details.push(format_size(size));
+    }
+    if let Some(updated_at) = metadata.updated_at.as_deref() {
+        // ISO 8601 timestamps are shortened to their date.
+        details.push(format!("updated {}", updated_at.split('T').next().unwrap_or(updated_at)));
+    }
+    if let Some(game_version) = metadata.game_version.as_deref() {
+        details.push(format!("game {game_version}"));
+    }
+
+    let name = metadata
+        .name
+        .as_deref()
+        .map(|name| format!("<strong>{}</strong> ", html_escape::encode_text(name)))
+        .unwrap_or_default();
+    let details = if details.is_empty() {
+        String::new()
+    } else {
+        format!(
+            r#" <span class="text-muted small">{}</span>"#,
+            html_escape::encode_text(&details.join(" · "))
+        )
+    };
+    format!("{name}{id}{details}")
+}
+
+pub fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else if bytes < 1024 * 1024 * 1024 {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.1} GiB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+    }
+}
@@ -28,6 +28,7 @@ pub fn render_layout(title: &str, active: &str, breadcrumbs: Vec<Breadcrumb>, co
         NavItem { label: "Server / Profile".to_string(), href: "/server".to_string(), key: "server".to_string() },
         NavItem { label: "Pakete / Mods".to_string(), href: "/packages".to_string(), key: "packages".to_string() },
         NavItem { label: "Run / Logs".to_string(), href: "/run-logs".to_string(), key: "run".to_string() },
+        NavItem { label: "Spieler".to_string(), href: "/players".to_string(), key: "players".to_string() },
         NavItem { label: "Settings".to_string(), href: "/settings".to_string(), key: "settings".to_string() },
     ];
 
@@ -1,8 +1,14 @@
+pub mod audit;
+pub mod auth;
 pub mod dashboard;
 pub mod health;
 pub mod helpers;
 pub mod layout;
 pub mod packages;
+pub mod players;
 pub mod profiles;
 pub mod run;
 pub mod settings;
+pub mod tokens;
+pub mod users;
+pub mod workshop;
@@ -1,3 +1,4 @@
+use crate::views::helpers::render_mod_label;
 use crate::views::layout::{breadcrumb, render_layout};
 use backend::models::{ModEntry, ModPackage};
 
@@ -14,7 +15,7 @@ pub fn render_packages_page(
     for entry in mods {
         mod_rows.push_str(&format!(
             r#"<tr>
-              <td class="arssm-text">{mod_id}</td>
+              <td class="arssm-text">{label}</td>
               <td class="arssm-text">{name}</td>
               <td class="d-flex gap-2">
                 <form method="post" action="/packages/mods/{mod_id}/edit" class="d-flex gap-2">
@@ -29,6 +30,7 @@ pub fn render_packages_page(
             </tr>"#,
             mod_id = html_escape::encode_text(&entry.mod_id),
             name = html_escape::encode_text(&entry.name),
+            label = render_mod_label(&entry.mod_id, Some(&entry.metadata)),
         ));
     }
     if mod_rows.is_empty() {
@@ -76,7 +78,7 @@ pub fn render_packages_page(
               <table class="table table-sm arssm-table">
                 <thead>
                   <tr>
-                    <th>Mod ID</th>
+                    <th>Mod</th>
                     <th>Name</th>
                     <th>Actions</th>
                   </tr>
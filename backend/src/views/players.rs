@@ -0,0 +1,179 @@
+use crate::routes::players::PlayersOverview;
+use crate::services::format_resolve_timestamp;
+use crate::views::layout::{breadcrumb, render_layout};
+use backend::models::ServerProfile;
+
+pub fn render_players_page(
+    profiles: &[ServerProfile],
+    overview: Option<&PlayersOverview>,
+    message: Option<&str>,
+) -> String {
+    let notice = message
+        .map(|value| format!("<p class=\"text-success\">{}</p>", html_escape::encode_text(value)))
+        .unwrap_or_default();
+    let active_profile_id = overview.map(|overview| overview.profile_id.as_str());
+
+    let mut options = String::new();
+    for profile in profiles {
+        let selected = if active_profile_id == Some(profile.profile_id.as_str()) {
+            "selected"
+        } else {
+            ""
+        };
+        options.push_str(&format!(
+            r#"<option value="{id}" {selected}>{name}</option>"#,
+            id = html_escape::encode_text(&profile.profile_id),
+            name = html_escape::encode_text(&profile.display_name),
+        ));
+    }
+    if options.is_empty() {
+        options.push_str("<option value=\"\">No profiles available</option>");
+    }
+
+    let body = match overview {
+        Some(overview) => render_overview(overview),
+        None => "<p class=\"text-muted\">No active profile configured.</p>".to_string(),
+    };
+
+    let content = format!(
+        r#"<h1 class="h3 mb-3">Spieler</h1>
+        {notice}
+        <form method="get" action="/players" class="card card-body mb-3">
+          <label class="form-label" for="profile_id">Profile</label>
+          <select class="form-select arssm-input" id="profile_id" name="profile_id" onchange="this.form.submit()">{options}</select>
+        </form>
+        {body}"#,
+    );
+
+    render_layout(
+        "ARSSM Spieler",
+        "players",
+        vec![breadcrumb("Spieler", None)],
+        &content,
+    )
+}
+
+fn render_overview(overview: &PlayersOverview) -> String {
+    let profile_id = html_escape::encode_text(&overview.profile_id);
+    let hidden_profile = format!(r#"<input type="hidden" name="profile_id" value="{profile_id}">"#);
+
+    let players_section = if !overview.running {
+        "<p class=\"text-muted\">Server is not running.</p>".to_string()
+    } else if let Some(error) = overview.rcon_error.as_deref() {
+        format!(
+            "<p class=\"text-danger\">RCON unavailable: {}</p>",
+            html_escape::encode_text(error)
+        )
+    } else {
+        let mut rows = String::new();
+        for player in &overview.players {
+            rows.push_str(&format!(
+                r#"<tr>
+                  <td class="arssm-text">{id}</td>
+                  <td class="arssm-text">{name}{lobby}</td>
+                  <td class="arssm-text">{ping}</td>
+                  <td class="arssm-text"><code>{guid}</code></td>
+                  <td>
+                    <form method="post" action="/players/kick" class="d-flex gap-2 mb-1">
+                      {hidden_profile}
+                      <input type="hidden" name="player_id" value="{id}">
+                      <input class="form-control form-control-sm arssm-input" name="reason" placeholder="Reason">
+                      <button class="btn btn-sm btn-arssm-secondary" type="submit">Kick</button>
+                    </form>
+                    <form method="post" action="/players/ban" class="d-flex gap-2">
+                      {hidden_profile}
+                      <input type="hidden" name="player_id" value="{id}">
+                      <input class="form-control form-control-sm arssm-input" name="duration_minutes" type="number" min="0" value="0" title="Minutes, 0 = permanent">
+                      <input class="form-control form-control-sm arssm-input" name="reason" placeholder="Reason">
+                      <button class="btn btn-sm btn-arssm-danger" type="submit">Ban</button>
+                    </form>
+                  </td>
+                </tr>"#,
+                id = player.id,
+                name = html_escape::encode_text(&player.name),
+                lobby = if player.in_lobby { " <span class=\"text-muted\">(Lobby)</span>" } else { "" },
+                ping = player
+                    .ping
+                    .map(|ping| ping.to_string())
+                    .unwrap_or_else(|| "n/a".to_string()),
+                guid = html_escape::encode_text(&player.guid),
+            ));
+        }
+        if rows.is_empty() {
+            rows.push_str("<tr><td colspan=\"5\" class=\"arssm-text\">No players connected.</td></tr>");
+        }
+        format!(
+            r#"<form method="post" action="/players/broadcast" class="d-flex gap-2 mb-3">
+              {hidden_profile}
+              <input class="form-control arssm-input" name="message" placeholder="Message to all players">
+              <button class="btn btn-arssm-primary" type="submit">Broadcast</button>
+            </form>
+            <table class="table table-sm arssm-table">
+              <thead>
+                <tr>
+                  <th>ID</th>
+                  <th>Name</th>
+                  <th>Ping</th>
+                  <th>GUID</th>
+                  <th>Actions</th>
+                </tr>
+              </thead>
+              <tbody>{rows}</tbody>
+            </table>"#
+        )
+    };
+
+    let mut ban_rows = String::new();
+    for ban in &overview.bans {
+        let banned_at = ban.banned_at.to_string();
+        let expires = ban
+            .expires_at
+            .map(|expires_at| expires_at.to_string())
+            .and_then(|expires_at| format_resolve_timestamp(Some(&expires_at)))
+            .unwrap_or_else(|| "permanent".to_string());
+        ban_rows.push_str(&format!(
+            r#"<tr>
+              <td class="arssm-text">{name}</td>
+              <td class="arssm-text"><code>{guid}</code></td>
+              <td class="arssm-text">{reason}</td>
+              <td class="arssm-text">{banned_at}{banned_by}</td>
+              <td class="arssm-text">{expires}</td>
+            </tr>"#,
+            name = html_escape::encode_text(&ban.name),
+            guid = html_escape::encode_text(&ban.guid),
+            reason = html_escape::encode_text(&ban.reason),
+            banned_at = format_resolve_timestamp(Some(&banned_at)).unwrap_or_default(),
+            banned_by = ban
+                .banned_by
+                .as_deref()
+                .map(|user| format!(" <span class=\"text-muted\">by {}</span>", html_escape::encode_text(user)))
+                .unwrap_or_default(),
+        ));
+    }
+    if ban_rows.is_empty() {
+        ban_rows.push_str("<tr><td colspan=\"5\" class=\"arssm-text\">No active bans.</td></tr>");
+    }
+
+    format!(
+        r#"<div class="card card-body mb-3">
+          <h2 class="h6 text-uppercase text-muted">Connected players</h2>
+          {players_section}
+        </div>
+        <div class="card card-body">
+          <h2 class="h6 text-uppercase text-muted">Bans</h2>
+          <p class="text-muted small">Bans are stored per profile and re-applied via RCON after each start.</p>
+          <table class="table table-sm arssm-table">
+            <thead>
+              <tr>
+                <th>Name</th>
+                <th>GUID</th>
+                <th>Reason</th>
+                <th>Banned</th>
+                <th>Expires</th>
+              </tr>
+            </thead>
+            <tbody>{ban_rows}</tbody>
+          </table>
+        </div>"#
+    )
+}
@@ -1,8 +1,8 @@
 use crate::services::{format_resolve_timestamp, scenario_display_name};
-use crate::views::helpers::render_hidden_ids;
+use crate::views::helpers::{render_hidden_ids, render_mod_label};
 use crate::views::layout::{breadcrumb, render_layout};
 use backend::defaults::{flatten_defaults, get_json_path, value_to_string};
-use backend::models::{ModPackage, ServerProfile};
+use backend::models::{ModPackage, ModVersionStatus, RestartPolicy, ServerProfile};
 
 pub fn render_profiles_page(
     profiles: &[ServerProfile],
@@ -90,6 +90,8 @@ pub fn render_profile_detail(profile: &ServerProfile, active_profile_id: Option<
           <dd class="col-sm-9 arssm-text">{url}</dd>
           <dt class="col-sm-3">Selected scenario</dt>
           <dd class="col-sm-9">{scenario_name}</dd>
+          <dt class="col-sm-3">Root mod</dt>
+          <dd class="col-sm-9">{root_mod}</dd>
           <dt class="col-sm-3">Active</dt>
           <dd class="col-sm-9">{active_badge}</dd>
           <dt class="col-sm-3">Last resolved</dt>
@@ -97,6 +99,7 @@ pub fn render_profile_detail(profile: &ServerProfile, active_profile_id: Option<
         </dl>
         <a class="btn btn-arssm-secondary me-2" href="/server/{id}/workshop">Workshop resolve</a>
         <a class="btn btn-arssm-primary me-2" href="/server/{id}/config-preview">Config preview</a>
+        <a class="btn btn-arssm-secondary me-2" href="/server/{id}/versions">Mod versions</a>
         <a class="btn btn-arssm-secondary me-2" href="/server/{id}/edit">Edit</a>
         <form class="d-inline" method="post" action="/server/{id}/activate">
           <button class="btn btn-arssm-secondary" type="submit">Set active</button>
@@ -110,6 +113,11 @@ pub fn render_profile_detail(profile: &ServerProfile, active_profile_id: Option<
                 .unwrap_or_else(|| "Not selected".to_string())
                 .as_str()
         ),
+        root_mod = profile
+            .root_mod_id
+            .as_deref()
+            .map(|id| render_mod_label(id, profile.mod_metadata.get(id)))
+            .unwrap_or_else(|| "Not resolved yet".to_string()),
         active_badge = active_badge,
         last_resolved = html_escape::encode_text(
             &format_resolve_timestamp(profile.last_resolved_at.as_deref())
@@ -277,6 +285,23 @@ pub fn render_profile_edit(
             <label class="form-label" for="optional_mod_ids">Optional mod IDs (one per line)</label>
             <textarea class="form-control arssm-input" id="optional_mod_ids" name="optional_mod_ids" rows="4">{optional_mods}</textarea>
           </div>
+          <div class="mb-3">
+            <label class="form-label" for="restart_policy">Automatic restart</label>
+            <select class="form-select arssm-input" id="restart_policy" name="restart_policy">
+              {restart_options}
+            </select>
+            <div class="form-text text-muted">Restarts use exponential backoff and stop after repeated crashes.</div>
+          </div>
+          <div class="form-check mb-3">
+            <input class="form-check-input" type="checkbox" id="update_before_start" name="update_before_start" {update_checked}>
+            <label class="form-check-label" for="update_before_start">Update server via SteamCMD before start</label>
+            <div class="form-text text-muted">Runs a SteamCMD validate/update of the work dir first and only starts if it succeeds.</div>
+          </div>
+          <div class="form-check mb-3">
+            <input class="form-check-input" type="checkbox" id="pin_mod_versions" name="pin_mod_versions" {pin_checked}>
+            <label class="form-check-label" for="pin_mod_versions">Pin mod versions in server.json</label>
+            <div class="form-text text-muted">Writes the last resolved version of each mod into the config, so the server does not pick up updates until the next resolve.</div>
+          </div>
           <p class="text-muted mb-3">Last resolved: {last_resolved}</p>
           <div class="d-flex gap-2">
             <button class="btn btn-arssm-primary" type="submit">Save</button>
@@ -296,6 +321,9 @@ pub fn render_profile_edit(
         last_resolved = html_escape::encode_text(&last_resolved),
         selected_hidden = render_hidden_ids("optional_package_ids", &profile.optional_package_ids),
         optional_mods = html_escape::encode_text(&optional_mods),
+        restart_options = render_restart_policy_options(profile.restart_policy),
+        update_checked = if profile.update_before_start { "checked" } else { "" },
+        pin_checked = if profile.pin_mod_versions { "checked" } else { "" },
     );
 
     let paths_content = format!(
@@ -318,6 +346,11 @@ pub fn render_profile_edit(
             <label class="form-label" for="profile_dir_base_override">Profile base directory</label>
             <input class="form-control arssm-input" id="profile_dir_base_override" name="profile_dir_base_override" value="{profile_dir_base}">
           </div>
+          <div class="mb-3">
+            <label class="form-label" for="steam_branch_override">Steam branch</label>
+            <input class="form-control arssm-input" id="steam_branch_override" name="steam_branch_override" value="{steam_branch}" placeholder="public">
+            <div class="form-text text-muted">Beta branch used by SteamCMD updates, e.g. <code>experimental</code>. Use <code>public</code> to force the default branch.</div>
+          </div>
           <button class="btn btn-arssm-primary" type="submit">Save paths</button>
         </form>"#,
         id = html_escape::encode_text(&profile.profile_id),
@@ -325,6 +358,7 @@ pub fn render_profile_edit(
         reforger_server_exe = html_escape::encode_text(profile.reforger_server_exe_override.as_deref().unwrap_or("")),
         reforger_server_work_dir = html_escape::encode_text(profile.reforger_server_work_dir_override.as_deref().unwrap_or("")),
         profile_dir_base = html_escape::encode_text(profile.profile_dir_base_override.as_deref().unwrap_or("")),
+        steam_branch = html_escape::encode_double_quoted_attribute(profile.steam_branch_override.as_deref().unwrap_or("")),
     );
 
     let overrides_content = render_profile_overrides_form(profile, settings);
@@ -357,6 +391,22 @@ pub fn render_profile_edit(
     )
 }
 
+fn render_restart_policy_options(current: RestartPolicy) -> String {
+    let mut options = String::new();
+    for (policy, label) in [
+        (RestartPolicy::Never, "Never"),
+        (RestartPolicy::OnCrash, "On crash"),
+        (RestartPolicy::Always, "Always"),
+    ] {
+        options.push_str(&format!(
+            r#"<option value="{value}" {selected}>{label}</option>"#,
+            value = policy.as_str(),
+            selected = if policy == current { "selected" } else { "" },
+        ));
+    }
+    options
+}
+
 pub fn render_profile_overrides_form(
     profile: &ServerProfile,
     settings: &backend::storage::AppSettings,
@@ -500,14 +550,18 @@ pub fn render_new_profile_resolve(
     let mut dependency_list = String::new();
     let mut dependency_count = 0usize;
     let mut errors = String::new();
+    let mut root_display = String::new();
+    let mut mod_metadata = String::new();
     if let Some(result) = resolved {
         root_id = result.root_id.clone();
+        root_display = render_mod_label(&result.root_id, result.metadata.get(&result.root_id));
         dependency_ids = result.dependency_ids.join(",");
         scenario_ids = result.scenarios.join("\n");
+        mod_metadata = serde_json::to_string(&result.metadata).unwrap_or_default();
         for dep_id in result.dependency_ids.iter() {
             dependency_list.push_str(&format!(
                 "<li>{}</li>",
-                html_escape::encode_text(dep_id)
+                render_mod_label(dep_id, result.metadata.get(dep_id))
             ));
         }
         dependency_count = result.dependency_ids.len();
@@ -538,13 +592,14 @@ pub fn render_new_profile_resolve(
             <input type="hidden" name="root_mod_id" value="{root_id}">
             <input type="hidden" name="dependency_mod_ids" value="{dependency_ids}">
             <input type="hidden" name="scenario_ids" value="{scenario_ids}">
+            <input type="hidden" name="mod_metadata" value="{mod_metadata}">
             <div class="mb-3">
               <label class="form-label" for="selected_scenario_id_path">Scenario</label>
               <select class="form-select arssm-input" id="selected_scenario_id_path" name="selected_scenario_id_path">
                 {scenario_options}
               </select>
             </div>
-            <p class="mb-1"><strong>Root mod ID:</strong> {root_id_display}</p>
+            <p class="mb-1"><strong>Root mod:</strong> {root_display}</p>
             <p class="text-muted mb-2">{dependency_count} dependencies resolved.</p>
             <details>
               <summary>Show dependency list</summary>
@@ -568,7 +623,7 @@ pub fn render_new_profile_resolve(
         </div>"##,
         notice = notice,
         root_id = html_escape::encode_text(&root_id),
-        root_id_display = html_escape::encode_text(&root_id),
+        mod_metadata = html_escape::encode_double_quoted_attribute(&mod_metadata),
         dependency_ids = html_escape::encode_text(&dependency_ids),
         scenario_ids = html_escape::encode_text(&scenario_ids),
         scenario_options = scenario_options,
@@ -578,6 +633,150 @@ pub fn render_new_profile_resolve(
     )
 }
 
+pub fn render_profile_versions(
+    profile: &ServerProfile,
+    statuses: &[ModVersionStatus],
+    message: Option<&str>,
+) -> String {
+    let notice = message
+        .map(|value| format!("<p class=\"text-success\">{}</p>", html_escape::encode_text(value)))
+        .unwrap_or_default();
+    let id = html_escape::encode_text(&profile.profile_id).to_string();
+
+    let mut pending_rows = String::new();
+    let mut rows = String::new();
+    for status in statuses {
+        let mod_id = html_escape::encode_double_quoted_attribute(&status.mod_id);
+        let label = render_mod_label(&status.mod_id, Some(&status.metadata));
+        let latest = status.metadata.version.as_deref().unwrap_or("unknown");
+        let pinned = status.pinned_version.as_deref().unwrap_or("");
+        if status.upgrade_available {
+            pending_rows.push_str(&format!(
+                r#"<tr>
+                  <td class="arssm-text">{label}</td>
+                  <td>{pinned}</td>
+                  <td>{latest}</td>
+                  <td>
+                    <form method="post" action="/server/{id}/versions/accept">
+                      <input type="hidden" name="mod_id" value="{mod_id}">
+                      <button class="btn btn-sm btn-arssm-primary" type="submit">Accept</button>
+                    </form>
+                  </td>
+                </tr>"#,
+                pinned = html_escape::encode_text(pinned),
+                latest = html_escape::encode_text(latest),
+            ));
+        }
+        let badge = if status.upgrade_available {
+            r#" <span class="badge text-bg-warning">upgrade</span>"#
+        } else {
+            ""
+        };
+        rows.push_str(&format!(
+            r#"<tr>
+              <td class="arssm-text">{label}</td>
+              <td>{role}</td>
+              <td>{latest}{badge}</td>
+              <td>
+                <form method="post" action="/server/{id}/versions/pin" class="d-flex gap-2">
+                  <input type="hidden" name="mod_id" value="{mod_id}">
+                  <input class="form-control form-control-sm arssm-input" name="version" value="{pinned}" placeholder="{placeholder}">
+                  <button class="btn btn-sm btn-arssm-secondary" type="submit">Save</button>
+                </form>
+              </td>
+            </tr>"#,
+            role = status.role.label(),
+            latest = html_escape::encode_text(latest),
+            pinned = html_escape::encode_double_quoted_attribute(pinned),
+            placeholder = html_escape::encode_double_quoted_attribute(
+                status.metadata.version.as_deref().unwrap_or("not pinned")
+            ),
+        ));
+    }
+    if rows.is_empty() {
+        rows.push_str("<tr><td colspan=\"4\" class=\"arssm-text\">Resolve the workshop to list the mods.</td></tr>");
+    }
+
+    let pending = if pending_rows.is_empty() {
+        "<p class=\"text-muted mb-0\">No pending upgrades.</p>".to_string()
+    } else {
+        format!(
+            r#"<div class="table-responsive">
+              <table class="table table-sm align-middle arssm-table">
+                <thead>
+                  <tr>
+                    <th>Mod</th>
+                    <th>Pinned</th>
+                    <th>Latest</th>
+                    <th></th>
+                  </tr>
+                </thead>
+                <tbody>
+                  {pending_rows}
+                </tbody>
+              </table>
+            </div>
+            <form method="post" action="/server/{id}/versions/accept">
+              <button class="btn btn-arssm-primary" type="submit">Accept all</button>
+            </form>"#
+        )
+    };
+    let unpinned_hint = if profile.pin_mod_versions {
+        "Mods without a pin are pinned to their resolved version by the next resolve."
+    } else {
+        "Mods without a pin are left to the server, which downloads their newest version."
+    };
+
+    let content = format!(
+        r#"<h1 class="h3 mb-3">Mod versions</h1>
+        {notice}
+        <div class="card card-body mb-4">
+          <h2 class="h5">Pending upgrades</h2>
+          <p class="text-muted">Pinned mods whose latest resolved version differs. Accepting moves the pin and rewrites server.json.</p>
+          {pending}
+        </div>
+        <div class="card card-body mb-4">
+          <h2 class="h5">Pins</h2>
+          <p class="text-muted">Leave the version empty to remove a pin. {unpinned_hint}</p>
+          <div class="table-responsive">
+            <table class="table table-sm align-middle arssm-table">
+              <thead>
+                <tr>
+                  <th>Mod</th>
+                  <th>Role</th>
+                  <th>Latest</th>
+                  <th>Pinned version</th>
+                </tr>
+              </thead>
+              <tbody>
+                {rows}
+              </tbody>
+            </table>
+          </div>
+          <p class="text-muted mb-0">Last resolved: {last_resolved}</p>
+        </div>
+        <form class="d-inline" method="post" action="/server/{id}/versions/check">
+          <button class="btn btn-arssm-secondary" type="submit">Check for updates</button>
+        </form>
+        <a class="btn btn-arssm-secondary ms-2" href="/server/{id}">Back to profile</a>"#,
+        last_resolved = html_escape::encode_text(
+            &format_resolve_timestamp(profile.last_resolved_at.as_deref())
+                .unwrap_or_else(|| "Not resolved yet".to_string())
+        ),
+    );
+
+    render_layout(
+        "ARSSM Mod Versions",
+        "server",
+        vec![
+            breadcrumb("Server / Profile", Some("/server".to_string())),
+            breadcrumb(&profile.display_name, Some(format!("/server/{}", profile.profile_id))),
+            breadcrumb("Mod versions", None),
+        ],
+        &content,
+    )
+}
+
 pub fn render_workshop_page(
     profile: &ServerProfile,
     resolved: Option<&backend::workshop::WorkshopResolveResult>,
@@ -623,12 +822,13 @@ pub fn render_workshop_panel(
     resolved: Option<&backend::workshop::WorkshopResolveResult>,
     message: Option<&str>,
 ) -> String {
-    let (root_id, scenarios, dependency_ids, errors) = if let Some(result) = resolved {
+    let (root_id, scenarios, dependency_ids, errors, metadata) = if let Some(result) = resolved {
         (
             Some(result.root_id.clone()),
             result.scenarios.clone(),
             result.dependency_ids.clone(),
             result.errors.clone(),
+            &result.metadata,
         )
     } else {
         (
@@ -636,6 +836,7 @@ pub fn render_workshop_panel(
             profile.scenarios.clone(),
             profile.dependency_mod_ids.clone(),
             Vec::new(),
+            &profile.mod_metadata,
         )
     };
 
@@ -677,10 +878,11 @@ pub fn render_workshop_panel(
     let dependency_count = dependency_ids.len();
     let root_display = root_id
         .as_deref()
-        .unwrap_or("Not resolved yet");
+        .map(|id| render_mod_label(id, metadata.get(id)))
+        .unwrap_or_else(|| "Not resolved yet".to_string());
     let mut dependency_list = String::new();
     for id in dependency_ids {
-        dependency_list.push_str(&format!("<li>{}</li>", html_escape::encode_text(&id)));
+        dependency_list.push_str(&format!("<li>{}</li>", render_mod_label(&id, metadata.get(&id))));
     }
     if dependency_list.is_empty() {
         dependency_list.push_str("<li>No dependencies resolved.</li>");
@@ -719,7 +921,7 @@ pub fn render_workshop_panel(
 
         <div class="card card-body mb-4">
           <h2 class="h5">Dependencies</h2>
-          <p class="mb-1"><strong>Root mod ID:</strong> <span class="arssm-text">{root_display}</span></p>
+          <p class="mb-1"><strong>Root mod:</strong> <span class="arssm-text">{root_display}</span></p>
           <p class="text-muted">{dependency_count} dependencies resolved.</p>
           <details>
             <summary>Show dependency list</summary>
@@ -738,7 +940,7 @@ pub fn render_workshop_panel(
         scenario_options = scenario_options,
         dependency_count = dependency_count,
         dependency_list = dependency_list,
-        root_display = html_escape::encode_text(root_display),
+        root_display = root_display,
         error_list = error_list,
     )
 }
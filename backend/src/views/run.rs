@@ -1,13 +1,19 @@
 use crate::views::layout::{breadcrumb, render_layout};
 use backend::models::ServerProfile;
 
-pub fn render_run_logs_page(profiles: &[ServerProfile]) -> String {
+pub fn render_run_logs_page(profiles: &[ServerProfile], active_profile_id: Option<&str>) -> String {
     let mut options = String::new();
     for profile in profiles {
+        let selected = if active_profile_id == Some(profile.profile_id.as_str()) {
+            "selected"
+        } else {
+            ""
+        };
         options.push_str(&format!(
-            r#"<option value="{id}">{name}</option>"#,
+            r#"<option value="{id}" {selected}>{name}</option>"#,
             id = html_escape::encode_text(&profile.profile_id),
             name = html_escape::encode_text(&profile.display_name),
+            selected = selected,
         ));
     }
 
@@ -38,6 +44,16 @@ pub fn render_run_logs_page(profiles: &[ServerProfile]) -> String {
             <pre class="arssm-log p-3" id="log-output" style="height: 360px; overflow-y: auto;"></pre>
           </div>
         </div>
+        <div class="card mt-3">
+          <div class="card-header">RCON Console</div>
+          <div class="card-body">
+            <form class="d-flex gap-2 mb-3" id="rcon-form">
+              <input class="form-control arssm-input" id="rcon-command" placeholder="e.g. #status" autocomplete="off">
+              <button class="btn btn-arssm-secondary" type="submit">Send</button>
+            </form>
+            <pre class="arssm-log p-3" id="rcon-output" style="height: 200px; overflow-y: auto;"></pre>
+          </div>
+        </div>
         <script>
           const statusText = document.getElementById('status-text');
           const logOutput = document.getElementById('log-output');
@@ -48,12 +64,88 @@ pub fn render_run_logs_page(profiles: &[ServerProfile]) -> String {
             logOutput.scrollTop = logOutput.scrollHeight;
           }}
 
+          function profileQuery() {{
+            return '?profile_id=' + encodeURIComponent(profileSelect.value);
+          }}
+
           async function refreshStatus() {{
-            const response = await fetch('/api/run/status');
+            const response = await fetch('/api/run/status' + profileQuery());
+            if (!response.ok) {{
+              statusText.textContent = 'unknown';
+              return;
+            }}
             const data = await response.json();
             statusText.textContent = data.running ? ('running (pid ' + data.pid + ')') : 'stopped';
           }}
 
+          let eventSource = null;
+          async function connectLogs() {{
+            if (eventSource) {{
+              eventSource.close();
+            }}
+            logOutput.textContent = '';
+            if (!profileSelect.value) {{
+              return;
+            }}
+            const response = await fetch('/api/run/logs/tail' + profileQuery() + '&n=200');
+            if (response.ok) {{
+              const data = await response.json();
+              data.lines.forEach(appendLine);
+            }}
+            eventSource = new EventSource('/api/run/logs/stream' + profileQuery());
+            eventSource.onmessage = (event) => {{
+              appendLine(event.data);
+            }};
+          }}
+
+          const rconOutput = document.getElementById('rcon-output');
+          let rconSource = null;
+          function appendRcon(line) {{
+            rconOutput.textContent += line + '\n';
+            rconOutput.scrollTop = rconOutput.scrollHeight;
+          }}
+
+          function disconnectRcon() {{
+            if (rconSource) {{
+              rconSource.close();
+              rconSource = null;
+            }}
+          }}
+
+          document.getElementById('rcon-form').addEventListener('submit', async (event) => {{
+            event.preventDefault();
+            const input = document.getElementById('rcon-command');
+            const command = input.value.trim();
+            if (!command) {{
+              return;
+            }}
+            appendRcon('> ' + command);
+            const response = await fetch('/api/rcon/command', {{
+              method: 'POST',
+              headers: {{ 'Content-Type': 'application/json' }},
+              body: JSON.stringify({{ profile_id: profileSelect.value, command }})
+            }});
+            if (!response.ok) {{
+              appendRcon('error: ' + await response.text());
+              return;
+            }}
+            const data = await response.json();
+            appendRcon(data.response);
+            input.value = '';
+            if (!rconSource) {{
+              rconSource = new EventSource('/api/rcon/messages/stream' + profileQuery());
+              rconSource.onmessage = (message) => appendRcon(message.data);
+              rconSource.onerror = disconnectRcon;
+            }}
+          }});
+
+          profileSelect.addEventListener('change', () => {{
+            disconnectRcon();
+            rconOutput.textContent = '';
+            connectLogs();
+            refreshStatus();
+          }});
+
           document.getElementById('start-btn').addEventListener('click', async () => {{
             const profile_id = profileSelect.value;
             const response = await fetch('/api/run/start', {{
@@ -64,21 +156,32 @@ pub fn render_run_logs_page(profiles: &[ServerProfile]) -> String {
             if (!response.ok) {{
               const text = await response.text();
               alert(text);
+            }} else {{
+              const data = await response.json();
+              const notices = data.warnings || [];
+              if (data.dependency_changes) {{
+                const changes = data.dependency_changes;
+                notices.unshift('Dependencies changed since last resolve. Added: ' + (changes.added_mod_ids.join(', ') || 'none')
+                  + '. Removed: ' + (changes.removed_mod_ids.join(', ') || 'none')
+                  + '. Vanished scenarios: ' + (changes.vanished_scenarios.join(', ') || 'none') + '.');
+              }}
+              notices.forEach(appendLine);
             }}
             refreshStatus();
           }});
 
           document.getElementById('stop-btn').addEventListener('click', async () => {{
-            await fetch('/api/run/stop', {{ method: 'POST' }});
+            statusText.textContent = 'stopping...';
+            const response = await fetch('/api/run/stop' + profileQuery(), {{ method: 'POST' }});
+            if (!response.ok) {{
+              alert(await response.text());
+            }}
             refreshStatus();
           }});
 
-          const eventSource = new EventSource('/api/run/logs/stream');
-          eventSource.onmessage = (event) => {{
-            appendLine(event.data);
-          }};
-
+          connectLogs();
           refreshStatus();
+          setInterval(refreshStatus, 5000);
         </script>"#,
         options = options,
     );
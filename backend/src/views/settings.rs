@@ -1,20 +1,20 @@
+use crate::security::tls::CertificateSummary;
+use crate::services::format_resolve_timestamp;
 use crate::views::layout::{breadcrumb, render_layout};
 use backend::defaults::flatten_defaults;
-use backend::storage::AppSettings;
+use backend::storage::{AppSettings, DependencyChangeAction};
 
-pub fn render_settings_page(settings: &AppSettings, tab: Option<&str>, message: Option<&str>) -> String {
+pub fn render_settings_page(
+    settings: &AppSettings,
+    certificate: Option<&CertificateSummary>,
+    tab: Option<&str>,
+    message: Option<&str>,
+) -> String {
     let notice = message
         .map(|value| format!("<p class=\"text-success\">{value}</p>"))
         .unwrap_or_default();
     let active_tab = tab.unwrap_or("paths");
-    let tabs = format!(
-        r#"<ul class="nav nav-tabs mb-3">
-          <li class="nav-item"><a class="nav-link {paths_active}" href="/settings?tab=paths">Pfade</a></li>
-          <li class="nav-item"><a class="nav-link {defaults_active}" href="/settings?tab=defaults">server.json Defaults</a></li>
-        </ul>"#,
-        paths_active = if active_tab == "paths" { "active" } else { "" },
-        defaults_active = if active_tab == "defaults" { "active" } else { "" },
-    );
+    let tabs = render_settings_tabs(active_tab);
 
     let paths_content = format!(
         r#"<form method="post" action="/settings">
@@ -37,26 +37,158 @@ pub fn render_settings_page(settings: &AppSettings, tab: Option<&str>, message:
             <input class="form-control arssm-input" id="profile_dir_base" name="profile_dir_base" value="{profile_dir_base}">
             <div class="form-text text-muted">Profile runtime data is stored under <code>&lt;base&gt;/&lt;profile_id&gt;</code>.</div>
           </div>
+          <div class="mb-3">
+            <label class="form-label" for="dependency_change_action">Dependency changes on start</label>
+            <select class="form-select arssm-input" id="dependency_change_action" name="dependency_change_action">
+              <option value="warn" {warn_selected}>Warn, update and start</option>
+              <option value="block" {block_selected}>Block start</option>
+            </select>
+            <div class="form-text text-muted">The workshop is re-resolved before every start and compared to the last resolve.</div>
+          </div>
+          <div class="mb-3">
+            <label class="form-label" for="stop_grace_period_secs">Stop grace period (seconds)</label>
+            <input class="form-control arssm-input" id="stop_grace_period_secs" name="stop_grace_period_secs" type="number" min="0" value="{stop_grace_period_secs}">
+            <div class="form-text text-muted">Stop requests the server to shut down and kills it only if it is still running after this period.</div>
+          </div>
+          <div class="mb-3">
+            <label class="form-label" for="steam_branch">Steam branch</label>
+            <input class="form-control arssm-input" id="steam_branch" name="steam_branch" value="{steam_branch}" placeholder="public">
+            <div class="form-text text-muted">Beta branch installed by SteamCMD updates, e.g. <code>experimental</code>. Leave empty for the public branch; profiles can override it.</div>
+          </div>
+          <h2 class="h6 text-uppercase text-muted mt-4">Login protection</h2>
+          <p class="form-text text-muted">Failed logins are counted per source address. Each failure delays the next attempt (doubling up to the maximum); too many failures within the window lock the address out.</p>
+          <div class="row g-3 mb-3">
+            <div class="col-md-4">
+              <label class="form-label" for="login_max_failures">Max failures</label>
+              <input class="form-control arssm-input" id="login_max_failures" name="login_max_failures" type="number" min="1" value="{login_max_failures}">
+            </div>
+            <div class="col-md-4">
+              <label class="form-label" for="login_window_secs">Window (seconds)</label>
+              <input class="form-control arssm-input" id="login_window_secs" name="login_window_secs" type="number" min="1" value="{login_window_secs}">
+            </div>
+            <div class="col-md-4">
+              <label class="form-label" for="login_lockout_secs">Lockout (seconds)</label>
+              <input class="form-control arssm-input" id="login_lockout_secs" name="login_lockout_secs" type="number" min="1" value="{login_lockout_secs}">
+            </div>
+            <div class="col-md-4">
+              <label class="form-label" for="login_base_delay_ms">Initial delay (ms)</label>
+              <input class="form-control arssm-input" id="login_base_delay_ms" name="login_base_delay_ms" type="number" min="0" value="{login_base_delay_ms}">
+            </div>
+            <div class="col-md-4">
+              <label class="form-label" for="login_max_delay_ms">Max delay (ms)</label>
+              <input class="form-control arssm-input" id="login_max_delay_ms" name="login_max_delay_ms" type="number" min="0" value="{login_max_delay_ms}">
+            </div>
+          </div>
+          <h2 class="h6 text-uppercase text-muted mt-4">Web server</h2>
+          <p class="form-text text-muted">Applied on the next start. <code>--bind</code>, <code>--port</code>, <code>--tls-cert</code>, <code>--tls-key</code> and <code>--plain-http</code> (or <code>ARSSM_BIND</code>, <code>ARSSM_PORT</code>, <code>ARSSM_TLS_CERT</code>, <code>ARSSM_TLS_KEY</code>, <code>ARSSM_PLAIN_HTTP</code>) override these values.</p>
+          <div class="row g-3 mb-3">
+            <div class="col-md-8">
+              <label class="form-label" for="web_bind_address">Bind address</label>
+              <input class="form-control arssm-input" id="web_bind_address" name="web_bind_address" value="{web_bind_address}" placeholder="0.0.0.0">
+            </div>
+            <div class="col-md-4">
+              <label class="form-label" for="web_port">Port</label>
+              <input class="form-control arssm-input" id="web_port" name="web_port" type="number" min="1" max="65535" value="{web_port}">
+            </div>
+            <div class="col-md-6">
+              <label class="form-label" for="web_tls_cert_path">TLS certificate (PEM)</label>
+              <input class="form-control arssm-input" id="web_tls_cert_path" name="web_tls_cert_path" value="{web_tls_cert_path}" placeholder="Leave empty for the self-signed certificate">
+            </div>
+            <div class="col-md-6">
+              <label class="form-label" for="web_tls_key_path">TLS private key (PEM)</label>
+              <input class="form-control arssm-input" id="web_tls_key_path" name="web_tls_key_path" value="{web_tls_key_path}">
+            </div>
+          </div>
+          <div class="form-text text-muted mb-2">Certificate files are checked every 30 seconds and reloaded without a restart when they change.</div>
+          <div class="form-check mb-3">
+            <input class="form-check-input" type="checkbox" id="web_plain_http" name="web_plain_http" {web_plain_http_checked}>
+            <label class="form-check-label" for="web_plain_http">Plain HTTP (only behind a local reverse proxy that terminates TLS)</label>
+          </div>
+          {certificate_info}
           <button class="btn btn-arssm-primary" type="submit">Save</button>
         </form>
         <hr>
         <h2 class="h5">SteamCMD Update</h2>
-        <p class="text-muted">Placeholder action for MVP2.</p>
+        <p class="text-muted">Installs or updates the dedicated server (app 1874900) into the Reforger server work dir. Refused while a server runs from that directory.</p>
         <button class="btn btn-arssm-secondary" id="steamcmd-update">Run update</button>
         <p class="mt-2" id="steamcmd-status"></p>
+        <div class="progress mb-2" style="height: 6px;">
+          <div class="progress-bar" id="steamcmd-progress" role="progressbar" style="width: 0%"></div>
+        </div>
+        <pre class="arssm-log p-3" id="steamcmd-log" style="height: 240px; overflow-y: auto;"></pre>
         <script>
+          const steamcmdStatus = document.getElementById('steamcmd-status');
+          const steamcmdLog = document.getElementById('steamcmd-log');
+          const steamcmdProgress = document.getElementById('steamcmd-progress');
+          let steamcmdSource = null;
+
+          function appendSteamcmdLine(line) {{
+            steamcmdLog.textContent += line + '\n';
+            steamcmdLog.scrollTop = steamcmdLog.scrollHeight;
+          }}
+
+          async function watchSteamcmd() {{
+            const response = await fetch('/api/steamcmd/status');
+            const data = await response.json();
+            if (data.running) {{
+              steamcmdStatus.textContent = 'Running...';
+              setTimeout(watchSteamcmd, 2000);
+              return;
+            }}
+            if (steamcmdSource) {{
+              steamcmdSource.close();
+              steamcmdSource = null;
+            }}
+            if (data.last_result) {{
+              steamcmdStatus.textContent = (data.last_result.success ? 'Succeeded: ' : 'Failed: ') + data.last_result.message;
+            }}
+          }}
+
           document.getElementById('steamcmd-update').addEventListener('click', async () => {{
-            const status = document.getElementById('steamcmd-status');
-            status.textContent = 'Running...';
+            steamcmdLog.textContent = '';
+            steamcmdProgress.style.width = '0%';
+            steamcmdSource = new EventSource('/api/steamcmd/stream');
+            steamcmdSource.onmessage = (event) => appendSteamcmdLine(event.data);
+            steamcmdSource.addEventListener('progress', (event) => {{
+              const progress = JSON.parse(event.data);
+              appendSteamcmdLine(progress.line);
+              steamcmdProgress.style.width = progress.percent + '%';
+              steamcmdStatus.textContent = progress.state + ' ' + progress.percent.toFixed(1) + '%';
+            }});
             const response = await fetch('/api/steamcmd/update', {{ method: 'POST' }});
+            if (!response.ok) {{
+              steamcmdStatus.textContent = await response.text();
+              steamcmdSource.close();
+              steamcmdSource = null;
+              return;
+            }}
             const data = await response.json();
-            status.textContent = data.message;
+            steamcmdStatus.textContent = data.message;
+            data.warnings.forEach((warning) => appendSteamcmdLine('[warning] ' + warning));
+            watchSteamcmd();
           }});
+
+          watchSteamcmd();
         </script>"#,
         steamcmd_dir = html_escape::encode_text(&settings.steamcmd_dir),
         reforger_server_exe = html_escape::encode_text(&settings.reforger_server_exe),
         reforger_server_work_dir = html_escape::encode_text(&settings.reforger_server_work_dir),
         profile_dir_base = html_escape::encode_text(&settings.profile_dir_base),
+        warn_selected = if settings.dependency_change_action == DependencyChangeAction::Warn { "selected" } else { "" },
+        block_selected = if settings.dependency_change_action == DependencyChangeAction::Block { "selected" } else { "" },
+        stop_grace_period_secs = settings.stop_grace_period_secs,
+        steam_branch = html_escape::encode_double_quoted_attribute(&settings.steam_branch),
+        login_max_failures = settings.login_limits.max_failures,
+        login_window_secs = settings.login_limits.window_secs,
+        login_lockout_secs = settings.login_limits.lockout_secs,
+        login_base_delay_ms = settings.login_limits.base_delay_ms,
+        login_max_delay_ms = settings.login_limits.max_delay_ms,
+        web_bind_address = html_escape::encode_double_quoted_attribute(&settings.web_server.bind_address),
+        web_port = settings.web_server.port,
+        web_tls_cert_path = html_escape::encode_double_quoted_attribute(&settings.web_server.tls_cert_path),
+        web_tls_key_path = html_escape::encode_double_quoted_attribute(&settings.web_server.tls_key_path),
+        web_plain_http_checked = if settings.web_server.plain_http { "checked" } else { "" },
+        certificate_info = render_certificate_info(certificate),
     );
 
     let defaults_content = render_defaults_form(settings);
@@ -83,6 +215,55 @@ pub fn render_settings_page(settings: &AppSettings, tab: Option<&str>, message:
     )
 }
 
+fn render_certificate_info(certificate: Option<&CertificateSummary>) -> String {
+    let Some(certificate) = certificate else {
+        return r#"<div class="card card-body mb-3"><span class="arssm-text">Served over plain HTTP; TLS is terminated by the reverse proxy.</span></div>"#.to_string();
+    };
+    let details = match &certificate.self_signed {
+        Some(generated) => {
+            let mut names = vec!["localhost".to_string()];
+            names.extend(generated.hostname.clone());
+            names.extend(generated.ip_addresses.iter().map(|ip| ip.to_string()));
+            format!(
+                r#"<div class="form-text text-muted">Self-signed, valid until {valid_until}; renewed automatically 30 days before expiry or when the host's addresses change.</div>
+                <div class="form-text text-muted">Covers: {names}</div>"#,
+                valid_until = format_resolve_timestamp(Some(&generated.not_after.to_string()))
+                    .unwrap_or_else(|| "n/a".to_string()),
+                names = html_escape::encode_text(&names.join(", ")),
+            )
+        }
+        None => String::new(),
+    };
+    format!(
+        r#"<div class="card card-body mb-3">
+          <div class="form-label">Active certificate <code>{path}</code></div>
+          <div class="arssm-text">SHA-256 fingerprint: <code id="tls-fingerprint">{fingerprint}</code></div>
+          <div class="form-text text-muted">Compare it with the fingerprint your browser shows for this site before trusting the certificate.</div>
+          {details}
+        </div>"#,
+        path = html_escape::encode_text(&certificate.path.to_string_lossy()),
+        fingerprint = certificate.fingerprint,
+    )
+}
+
+pub fn render_settings_tabs(active_tab: &str) -> String {
+    let mut items = String::new();
+    for (key, href, label) in [
+        ("paths", "/settings?tab=paths", "Pfade"),
+        ("defaults", "/settings?tab=defaults", "server.json Defaults"),
+        ("users", "/settings/users", "Benutzer"),
+        ("tokens", "/settings/tokens", "API Tokens"),
+        ("workshop-cache", "/settings/workshop-cache", "Workshop Cache"),
+        ("audit", "/settings/audit", "Audit"),
+    ] {
+        items.push_str(&format!(
+            r#"<li class="nav-item"><a class="nav-link {active}" href="{href}">{label}</a></li>"#,
+            active = if active_tab == key { "active" } else { "" },
+        ));
+    }
+    format!(r#"<ul class="nav nav-tabs mb-3">{items}</ul>"#)
+}
+
 pub fn render_defaults_form(settings: &AppSettings) -> String {
     let fields = flatten_defaults(&settings.server_json_defaults);
     let mut disabled_keys = Vec::new();
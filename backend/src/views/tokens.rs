@@ -0,0 +1,127 @@
+use crate::security::tokens::{ApiToken, TokenScope};
+use crate::services::format_resolve_timestamp;
+use crate::views::layout::{breadcrumb, render_layout};
+use crate::views::settings::render_settings_tabs;
+
+pub fn render_tokens_page(
+    tokens: &[ApiToken],
+    now: u64,
+    message: Option<&str>,
+    new_secret: Option<&str>,
+) -> String {
+    let notice = message
+        .map(|value| format!("<p class=\"text-success\">{}</p>", html_escape::encode_text(value)))
+        .unwrap_or_default();
+    let secret = new_secret
+        .map(|value| {
+            format!(
+                r#"<div class="card card-body mb-3">
+                  <label class="form-label" for="new-token">New token</label>
+                  <input class="form-control arssm-input" id="new-token" value="{value}" readonly onclick="this.select()">
+                  <div class="form-text text-muted">Use it as <code>Authorization: Bearer &lt;token&gt;</code> on <code>/api</code> routes.</div>
+                </div>"#,
+                value = html_escape::encode_double_quoted_attribute(value),
+            )
+        })
+        .unwrap_or_default();
+
+    let timestamp = |value: Option<u64>, fallback: &str| {
+        value
+            .and_then(|value| format_resolve_timestamp(Some(&value.to_string())))
+            .unwrap_or_else(|| fallback.to_string())
+    };
+    let mut rows = String::new();
+    for token in tokens {
+        let scopes = token
+            .scopes
+            .iter()
+            .map(|scope| format!("<span class=\"badge text-bg-secondary me-1\">{}</span>", scope.as_str()))
+            .collect::<String>();
+        let expires = if token.is_expired(now) {
+            "expired".to_string()
+        } else {
+            timestamp(token.expires_at, "never")
+        };
+        rows.push_str(&format!(
+            r#"<tr>
+              <td class="arssm-text">{name}</td>
+              <td>{scopes}</td>
+              <td class="arssm-text">{created} <span class="text-muted">by {created_by}</span></td>
+              <td class="arssm-text">{expires}</td>
+              <td class="arssm-text">{last_used}</td>
+              <td>
+                <form method="post" action="/settings/tokens/{id}/revoke">
+                  <button class="btn btn-sm btn-arssm-danger" type="submit">Revoke</button>
+                </form>
+              </td>
+            </tr>"#,
+            name = html_escape::encode_text(&token.name),
+            created = timestamp(Some(token.created_at), "n/a"),
+            created_by = html_escape::encode_text(&token.created_by),
+            last_used = timestamp(token.last_used_at, "never"),
+            id = html_escape::encode_double_quoted_attribute(&token.id),
+        ));
+    }
+    if rows.is_empty() {
+        rows.push_str("<tr><td colspan=\"6\" class=\"arssm-text\">No API tokens.</td></tr>");
+    }
+
+    let mut scope_inputs = String::new();
+    for scope in TokenScope::ALL {
+        let field = format!("scope_{}", scope.as_str().replace('-', "_"));
+        scope_inputs.push_str(&format!(
+            r#"<div class="form-check">
+              <input class="form-check-input" type="checkbox" id="{field}" name="{field}">
+              <label class="form-check-label" for="{field}"><code>{scope}</code> – {description}</label>
+            </div>"#,
+            scope = scope.as_str(),
+            description = scope.describe(),
+        ));
+    }
+
+    let content = format!(
+        r#"<h1 class="h3 mb-3">Settings</h1>
+        {notice}
+        {tabs}
+        {secret}
+        <h2 class="h5">API Tokens</h2>
+        <p class="text-muted">Tokens are stored hashed and only work on <code>/api</code> routes.</p>
+        <table class="table table-sm align-middle arssm-table">
+          <thead>
+            <tr>
+              <th>Name</th>
+              <th>Scopes</th>
+              <th>Created</th>
+              <th>Expires</th>
+              <th>Last used</th>
+              <th></th>
+            </tr>
+          </thead>
+          <tbody>{rows}</tbody>
+        </table>
+        <form method="post" action="/settings/tokens/create" class="card card-body">
+          <h2 class="h6 text-uppercase text-muted">Create token</h2>
+          <div class="mb-3">
+            <label class="form-label" for="token-name">Name</label>
+            <input class="form-control arssm-input" id="token-name" name="name" placeholder="e.g. weekly-restart">
+          </div>
+          <div class="mb-3">{scope_inputs}</div>
+          <div class="mb-3">
+            <label class="form-label" for="expires_days">Expires after (days)</label>
+            <input class="form-control arssm-input" id="expires_days" name="expires_days" type="number" min="1" placeholder="Leer lassen für kein Ablaufdatum">
+          </div>
+          <button class="btn btn-arssm-primary" type="submit">Create token</button>
+        </form>"#,
+        tabs = render_settings_tabs("tokens"),
+    );
+
+    render_layout(
+        "ARSSM API Tokens",
+        "settings",
+        vec![
+            breadcrumb("Settings", Some("/settings".to_string())),
+            breadcrumb("API Tokens", None),
+        ],
+        &content,
+    )
+}
@@ -0,0 +1,129 @@
+use crate::security::users::{Role, UserAccount};
+use crate::services::format_resolve_timestamp;
+use crate::views::layout::{breadcrumb, render_layout};
+use crate::views::settings::render_settings_tabs;
+
+pub fn render_users_page(
+    users: &[UserAccount],
+    bootstrap_username: &str,
+    current_username: &str,
+    message: Option<&str>,
+) -> String {
+    let notice = message
+        .map(|value| format!("<p class=\"text-success\">{}</p>", html_escape::encode_text(value)))
+        .unwrap_or_default();
+
+    let mut rows = format!(
+        r#"<tr>
+          <td class="arssm-text">{name}</td>
+          <td class="arssm-text">admin</td>
+          <td class="arssm-text">n/a</td>
+          <td class="text-muted small">Bootstrap-Account aus <code>credentials.json</code>; zurücksetzen mit <code>reset-credentials</code>.</td>
+        </tr>"#,
+        name = html_escape::encode_text(bootstrap_username),
+    );
+    for user in users {
+        let username = html_escape::encode_double_quoted_attribute(&user.username);
+        let created = format_resolve_timestamp(Some(&user.created_at.to_string())).unwrap_or_default();
+        let delete_form = if user.username == current_username {
+            String::new()
+        } else {
+            format!(
+                r#"<form method="post" action="/settings/users/{username}/delete">
+                  <button class="btn btn-sm btn-arssm-danger" type="submit">Delete</button>
+                </form>"#
+            )
+        };
+        rows.push_str(&format!(
+            r#"<tr>
+              <td class="arssm-text">{name}</td>
+              <td>
+                <form method="post" action="/settings/users/{username}/role" class="d-flex gap-2">
+                  <select class="form-select form-select-sm arssm-input" name="role">{role_options}</select>
+                  <button class="btn btn-sm btn-arssm-secondary" type="submit">Save</button>
+                </form>
+              </td>
+              <td class="arssm-text">{created}</td>
+              <td class="d-flex flex-wrap gap-2">
+                <form method="post" action="/settings/users/{username}/password" class="d-flex gap-2">
+                  <input class="form-control form-control-sm arssm-input" name="password" type="password" placeholder="New password" autocomplete="new-password">
+                  <button class="btn btn-sm btn-arssm-secondary" type="submit">Set password</button>
+                </form>
+                {delete_form}
+              </td>
+            </tr>"#,
+            name = html_escape::encode_text(&user.username),
+            role_options = render_role_options(user.role),
+        ));
+    }
+
+    let mut role_help = String::new();
+    for role in Role::ALL {
+        role_help.push_str(&format!(
+            "<li><strong>{}</strong>: {}</li>",
+            role.as_str(),
+            role.describe()
+        ));
+    }
+
+    let content = format!(
+        r#"<h1 class="h3 mb-3">Settings</h1>
+        {notice}
+        {tabs}
+        <h2 class="h5">Benutzer</h2>
+        <ul class="text-muted small">{role_help}</ul>
+        <table class="table table-sm align-middle arssm-table">
+          <thead>
+            <tr>
+              <th>Username</th>
+              <th>Role</th>
+              <th>Created</th>
+              <th>Actions</th>
+            </tr>
+          </thead>
+          <tbody>{rows}</tbody>
+        </table>
+        <form method="post" action="/settings/users/add" class="card card-body">
+          <h2 class="h6 text-uppercase text-muted">Add user</h2>
+          <div class="row g-2">
+            <div class="col-md-4">
+              <input class="form-control arssm-input" name="username" placeholder="Username" autocomplete="off">
+            </div>
+            <div class="col-md-4">
+              <input class="form-control arssm-input" name="password" type="password" placeholder="Password (min. 8 characters)" autocomplete="new-password">
+            </div>
+            <div class="col-md-2">
+              <select class="form-select arssm-input" name="role">{new_role_options}</select>
+            </div>
+            <div class="col-md-2">
+              <button class="btn btn-arssm-primary w-100" type="submit">Add</button>
+            </div>
+          </div>
+        </form>"#,
+        tabs = render_settings_tabs("users"),
+        new_role_options = render_role_options(Role::Viewer),
+    );
+
+    render_layout(
+        "ARSSM Benutzer",
+        "settings",
+        vec![
+            breadcrumb("Settings", Some("/settings".to_string())),
+            breadcrumb("Benutzer", None),
+        ],
+        &content,
+    )
+}
+
+fn render_role_options(current: Role) -> String {
+    Role::ALL
+        .iter()
+        .map(|role| {
+            format!(
+                r#"<option value="{value}" {selected}>{value}</option>"#,
+                value = role.as_str(),
+                selected = if *role == current { "selected" } else { "" },
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,99 @@
+use crate::services::format_resolve_timestamp;
+use crate::views::helpers::format_size;
+use crate::views::layout::{breadcrumb, render_layout};
+use crate::views::settings::render_settings_tabs;
+use backend::workshop_cache::WorkshopCacheStatus;
+
+pub fn render_workshop_cache_page(status: &WorkshopCacheStatus, message: Option<&str>) -> String {
+    let notice = message
+        .map(|value| format!("<p class=\"text-success\">{}</p>", html_escape::encode_text(value)))
+        .unwrap_or_default();
+    let offline_banner = if status.settings.offline {
+        r#"<div class="alert alert-warning">Offline mode is on: workshop resolves use cached pages only and fail for anything not cached.</div>"#
+    } else {
+        ""
+    };
+
+    let mut rows = String::new();
+    for entry in &status.entries {
+        rows.push_str(&format!(
+            r#"<tr>
+              <td class="arssm-text text-break"><a href="{url_attr}" target="_blank" rel="noopener">{url}</a></td>
+              <td class="arssm-text text-nowrap">{fetched}</td>
+              <td class="arssm-text text-nowrap">{size}</td>
+              <td>{state}</td>
+            </tr>"#,
+            url_attr = html_escape::encode_double_quoted_attribute(&entry.url),
+            url = html_escape::encode_text(&entry.url),
+            fetched = format_resolve_timestamp(Some(&entry.fetched_at.to_string()))
+                .unwrap_or_else(|| "n/a".to_string()),
+            size = format_size(entry.size_bytes),
+            state = if entry.expired {
+                r#"<span class="badge text-bg-secondary">expired</span>"#
+            } else {
+                r#"<span class="badge text-bg-success">fresh</span>"#
+            },
+        ));
+    }
+    if rows.is_empty() {
+        rows.push_str("<tr><td colspan=\"4\" class=\"arssm-text\">No cached pages.</td></tr>");
+    }
+
+    let content = format!(
+        r#"<h1 class="h3 mb-3">Settings</h1>
+        {notice}
+        {tabs}
+        {offline_banner}
+        <form method="post" action="/settings/workshop-cache" class="card card-body mb-3">
+          <h2 class="h6 text-uppercase text-muted">Workshop cache</h2>
+          <p class="form-text text-muted">Fetched workshop pages are kept on disk. Pages younger than the TTL are used without a request; older ones are revalidated and still used when the workshop cannot be reached.</p>
+          <div class="mb-3">
+            <label class="form-label" for="ttl_secs">TTL (seconds)</label>
+            <input class="form-control arssm-input" id="ttl_secs" name="ttl_secs" type="number" min="0" value="{ttl_secs}">
+            <div class="form-text text-muted"><code>0</code> revalidates every page on each resolve.</div>
+          </div>
+          <div class="form-check mb-3">
+            <input class="form-check-input" type="checkbox" id="offline" name="offline" {offline_checked}>
+            <label class="form-check-label" for="offline">Offline mode (resolve from the cache only)</label>
+          </div>
+          <button class="btn btn-arssm-primary" type="submit">Save</button>
+        </form>
+        <div class="d-flex align-items-center gap-2 mb-2">
+          <h2 class="h5 mb-0 me-auto">Cached pages <span class="text-muted">({count}, {total})</span></h2>
+          <form method="post" action="/settings/workshop-cache/purge">
+            <input type="hidden" name="scope" value="expired">
+            <button class="btn btn-sm btn-arssm-secondary" type="submit">Purge expired</button>
+          </form>
+          <form method="post" action="/settings/workshop-cache/purge" onsubmit="return confirm('Remove all cached workshop pages?');">
+            <input type="hidden" name="scope" value="all">
+            <button class="btn btn-sm btn-arssm-danger" type="submit">Purge all</button>
+          </form>
+        </div>
+        <table class="table table-sm align-middle arssm-table">
+          <thead>
+            <tr>
+              <th>URL</th>
+              <th>Fetched</th>
+              <th>Size</th>
+              <th></th>
+            </tr>
+          </thead>
+          <tbody>{rows}</tbody>
+        </table>"#,
+        tabs = render_settings_tabs("workshop-cache"),
+        ttl_secs = status.settings.ttl_secs,
+        offline_checked = if status.settings.offline { "checked" } else { "" },
+        count = status.entries.len(),
+        total = format_size(status.total_bytes),
+    );
+
+    render_layout(
+        "ARSSM Workshop Cache",
+        "settings",
+        vec![
+            breadcrumb("Settings", Some("/settings".to_string())),
+            breadcrumb("Workshop Cache", None),
+        ],
+        &content,
+    )
+}
@@ -1,48 +1,143 @@
+use crate::models::{ModMetadata, ServerProfile};
 use serde::{Deserialize, Serialize};
 use scraper::{Html, Selector};
+use sha2::{Digest, Sha256};
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{BTreeMap, HashSet},
     sync::Arc,
+    time::Duration,
 };
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::Instant;
 
 const WORKSHOP_BASE_URL: &str = "https://reforger.armaplatform.com";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Default limit for `RateLimitedFetcher` in front of the workshop site.
+pub const DEFAULT_REQUESTS_PER_SECOND: u32 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct WorkshopResolveRequest {
     pub url: String,
     pub max_depth: Option<usize>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct WorkshopResolveResult {
     pub root_id: String,
     pub root_url: String,
     pub scenarios: Vec<String>,
     pub dependency_ids: Vec<String>,
     pub errors: Vec<String>,
+    /// Workshop details of the root and each resolved dependency, by mod ID.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, ModMetadata>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct DependencyDiff {
+    pub added_mod_ids: Vec<String>,
+    pub removed_mod_ids: Vec<String>,
+    pub vanished_scenarios: Vec<String>,
+}
+
+impl DependencyDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_mod_ids.is_empty()
+            && self.removed_mod_ids.is_empty()
+            && self.vanished_scenarios.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.added_mod_ids.is_empty() {
+            parts.push(format!("added mods: {}", self.added_mod_ids.join(", ")));
+        }
+        if !self.removed_mod_ids.is_empty() {
+            parts.push(format!("removed mods: {}", self.removed_mod_ids.join(", ")));
+        }
+        if !self.vanished_scenarios.is_empty() {
+            parts.push(format!(
+                "vanished scenarios: {}",
+                self.vanished_scenarios.join(", ")
+            ));
+        }
+        if parts.is_empty() {
+            "no changes".to_string()
+        } else {
+            parts.join("; ")
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct WorkshopRootPage {
     pub workshop_id: String,
     pub dependency_urls: Vec<String>,
+    pub metadata: ModMetadata,
+}
+
+/// Outcome of a conditional fetch with a previously seen ETag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchedPage {
+    Modified { html: String, etag: Option<String> },
+    NotModified,
 }
 
 #[async_trait::async_trait]
 pub trait WorkshopFetcher: Send + Sync {
     async fn fetch_html(&self, url: &str) -> Result<String, String>;
+
+    /// Fetches `url` unless it still matches `etag`. Fetchers without support for
+    /// conditional requests always return the full page.
+    async fn fetch_if_modified(&self, url: &str, etag: Option<&str>) -> Result<FetchedPage, String> {
+        let _ = etag;
+        Ok(FetchedPage::Modified {
+            html: self.fetch_html(url).await?,
+            etag: None,
+        })
+    }
+}
+
+/// Limits for fetching dependency pages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResolveOptions {
+    /// Dependency pages fetched at the same time within one BFS level.
+    pub concurrency: usize,
+    /// Extra attempts after a transient error.
+    pub max_retries: u32,
+    /// Backoff before the first retry; doubles per attempt, with jitter.
+    pub retry_base_delay: Duration,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            max_retries: 3,
+            retry_base_delay: Duration::from_millis(500),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct WorkshopResolver {
     fetcher: Arc<dyn WorkshopFetcher>,
+    options: ResolveOptions,
 }
 
 impl WorkshopResolver {
     pub fn new(fetcher: Arc<dyn WorkshopFetcher>) -> Self {
-        Self { fetcher }
+        Self::with_options(fetcher, ResolveOptions::default())
     }
 
+    pub fn with_options(fetcher: Arc<dyn WorkshopFetcher>, options: ResolveOptions) -> Self {
+        Self { fetcher, options }
+    }
+
+    /// Walks the dependency graph breadth first. Pages of one level are fetched
+    /// concurrently, but results are applied in queue order, so `dependency_ids`
+    /// and `errors` come out exactly as a sequential walk would produce them.
     pub async fn resolve(
         &self,
         url: &str,
@@ -51,39 +146,42 @@ impl WorkshopResolver {
         let root_id = extract_workshop_id_from_url(url)
             .ok_or_else(|| "failed to extract workshop id from url".to_string())?;
 
-        let root_html = self.fetcher.fetch_html(url).await?;
-        let root_page = parse_root_page(&root_html, Some(&root_id))?;
-
         let scenarios_url = format!("{url}/scenarios");
-        let scenarios_html = self.fetcher.fetch_html(&scenarios_url).await?;
-        let scenarios = parse_scenarios_page(&scenarios_html);
+        let (root_html, scenarios_html) = tokio::join!(
+            self.fetch_with_retry(url),
+            self.fetch_with_retry(&scenarios_url)
+        );
+        let root_page = parse_root_page(&root_html?, Some(&root_id))?;
+        let scenarios = parse_scenarios_page(&scenarios_html?);
 
         let mut dependency_ids = Vec::new();
         let mut errors = Vec::new();
+        let mut metadata = BTreeMap::new();
         let mut visited_ids = HashSet::new();
         let mut visited_urls = HashSet::new();
 
         visited_ids.insert(root_id.clone());
         visited_urls.insert(url.to_string());
+        if !root_page.metadata.is_empty() {
+            metadata.insert(root_id.clone(), root_page.metadata.clone());
+        }
 
-        if max_depth > 0 {
-            let mut queue = VecDeque::new();
-            for dep_url in root_page.dependency_urls.iter() {
-                queue.push_back((dep_url.clone(), 1usize));
-            }
-
-            while let Some((dep_url, depth)) = queue.pop_front() {
-                if depth > max_depth {
-                    continue;
-                }
-                if visited_urls.contains(&dep_url) {
-                    continue;
-                }
-                visited_urls.insert(dep_url.clone());
-
-                let dep_id_hint = extract_workshop_id_from_url(&dep_url);
+        let mut level = if max_depth > 0 {
+            root_page.dependency_urls.clone()
+        } else {
+            Vec::new()
+        };
+        let mut depth = 1;
+        while !level.is_empty() {
+            let urls: Vec<String> = level
+                .into_iter()
+                .filter(|dep_url| visited_urls.insert(dep_url.clone()))
+                .collect();
+            let pages = self.fetch_level(&urls).await;
 
-                let dep_html = match self.fetcher.fetch_html(&dep_url).await {
+            let mut next_level = Vec::new();
+            for (dep_url, html) in urls.iter().zip(pages) {
+                let dep_html = match html {
                     Ok(html) => html,
                     Err(err) => {
                         errors.push(format!("failed to fetch dependency {dep_url}: {err}"));
@@ -91,6 +189,7 @@ impl WorkshopResolver {
                     }
                 };
 
+                let dep_id_hint = extract_workshop_id_from_url(dep_url);
                 let dep_page = match parse_root_page(&dep_html, dep_id_hint.as_deref()) {
                     Ok(page) => page,
                     Err(err) => {
@@ -101,16 +200,22 @@ impl WorkshopResolver {
 
                 if visited_ids.insert(dep_page.workshop_id.clone()) {
                     dependency_ids.push(dep_page.workshop_id.clone());
+                    if !dep_page.metadata.is_empty() {
+                        metadata.insert(dep_page.workshop_id.clone(), dep_page.metadata.clone());
+                    }
                 }
 
                 if depth < max_depth {
-                    for next_url in dep_page.dependency_urls.iter() {
-                        if !visited_urls.contains(next_url) {
-                            queue.push_back((next_url.clone(), depth + 1));
-                        }
-                    }
+                    next_level.extend(
+                        dep_page
+                            .dependency_urls
+                            .into_iter()
+                            .filter(|next_url| !visited_urls.contains(next_url)),
+                    );
                 }
             }
+            level = next_level;
+            depth += 1;
         }
 
         Ok(WorkshopResolveResult {
@@ -119,14 +224,107 @@ impl WorkshopResolver {
             scenarios,
             dependency_ids,
             errors,
+            metadata,
         })
     }
+
+    /// Reads the workshop details of single mods without following their
+    /// dependencies. Pages that fail are reported in the returned errors.
+    pub async fn fetch_metadata(
+        &self,
+        mod_ids: &[String],
+    ) -> (BTreeMap<String, ModMetadata>, Vec<String>) {
+        let urls: Vec<String> = mod_ids
+            .iter()
+            .map(|mod_id| format!("{WORKSHOP_BASE_URL}/workshop/{mod_id}"))
+            .collect();
+        let pages = self.fetch_level(&urls).await;
+
+        let mut metadata = BTreeMap::new();
+        let mut errors = Vec::new();
+        for (mod_id, html) in mod_ids.iter().zip(pages) {
+            match html.and_then(|html| parse_root_page(&html, Some(mod_id))) {
+                Ok(page) if !page.metadata.is_empty() => {
+                    metadata.insert(mod_id.clone(), page.metadata);
+                }
+                Ok(_) => {}
+                Err(err) => errors.push(format!("failed to fetch mod {mod_id}: {err}")),
+            }
+        }
+        (metadata, errors)
+    }
+
+    /// Fetches all pages of a level with at most `concurrency` requests in flight,
+    /// returning the results in the order of `urls`.
+    async fn fetch_level(&self, urls: &[String]) -> Vec<Result<String, String>> {
+        let permits = Arc::new(Semaphore::new(self.options.concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for (index, dep_url) in urls.iter().enumerate() {
+            let resolver = self.clone();
+            let permits = permits.clone();
+            let dep_url = dep_url.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                (index, resolver.fetch_with_retry(&dep_url).await)
+            });
+        }
+
+        let mut results: Vec<Result<String, String>> =
+            vec![Err("fetch task did not finish".to_string()); urls.len()];
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((index, result)) => results[index] = result,
+                Err(err) => tracing::error!("workshop fetch task failed: {err}"),
+            }
+        }
+        results
+    }
+
+    async fn fetch_with_retry(&self, url: &str) -> Result<String, String> {
+        let mut attempt = 0;
+        loop {
+            match self.fetcher.fetch_html(url).await {
+                Ok(html) => return Ok(html),
+                Err(err) if attempt < self.options.max_retries && is_transient_error(&err) => {
+                    let delay = retry_delay(self.options.retry_base_delay, attempt);
+                    tracing::debug!("retrying {url} in {delay:?}: {err}");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// Network failures, rate limiting and server errors are worth retrying; other
+/// failures (missing pages, offline mode) would fail again the same way.
+pub fn is_transient_error(err: &str) -> bool {
+    if let Some(status) = err.strip_prefix("request failed: status ") {
+        let code = status.split_whitespace().next().and_then(|code| code.parse::<u16>().ok());
+        return matches!(code, Some(429) | Some(500..=599));
+    }
+    err.starts_with("request failed:") || err.starts_with("failed to read response:")
+}
+
+/// Exponential backoff with jitter: a random delay between half and all of
+/// `base * 2^attempt`, so concurrent retries do not hit the site in lockstep.
+fn retry_delay(base: Duration, attempt: u32) -> Duration {
+    let full = base.saturating_mul(2u32.saturating_pow(attempt));
+    let half = full / 2;
+    half + full.mul_f64(rand::random::<f64>() * 0.5)
 }
 
 pub struct ReqwestFetcher {
     client: reqwest::Client,
 }
 
+impl Default for ReqwestFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ReqwestFetcher {
     pub fn new() -> Self {
         Self {
@@ -154,6 +352,158 @@ impl WorkshopFetcher for ReqwestFetcher {
             .await
             .map_err(|err| format!("failed to read response: {err}"))
     }
+
+    async fn fetch_if_modified(&self, url: &str, etag: Option<&str>) -> Result<FetchedPage, String> {
+        let mut request = self.client.get(url);
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|err| format!("request failed: {err}"))?;
+
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(FetchedPage::NotModified);
+        }
+        if !response.status().is_success() {
+            return Err(format!("request failed: status {}", response.status()));
+        }
+
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let html = response
+            .text()
+            .await
+            .map_err(|err| format!("failed to read response: {err}"))?;
+        Ok(FetchedPage::Modified { html, etag })
+    }
+}
+
+/// Spaces the requests of the wrapped fetcher evenly, shared by all resolves
+/// using it. Wrap the network fetcher so cached pages are not throttled.
+pub struct RateLimitedFetcher {
+    inner: Arc<dyn WorkshopFetcher>,
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimitedFetcher {
+    /// `0` requests per second disables the limit.
+    pub fn new(inner: Arc<dyn WorkshopFetcher>, requests_per_second: u32) -> Self {
+        let interval = if requests_per_second == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / requests_per_second
+        };
+        Self {
+            inner,
+            interval,
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn wait_for_slot(&self) {
+        if self.interval.is_zero() {
+            return;
+        }
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+#[async_trait::async_trait]
+impl WorkshopFetcher for RateLimitedFetcher {
+    async fn fetch_html(&self, url: &str) -> Result<String, String> {
+        self.wait_for_slot().await;
+        self.inner.fetch_html(url).await
+    }
+
+    async fn fetch_if_modified(&self, url: &str, etag: Option<&str>) -> Result<FetchedPage, String> {
+        self.wait_for_slot().await;
+        self.inner.fetch_if_modified(url, etag).await
+    }
+}
+
+/// Order-independent so reordered dependency pages do not count as a change.
+pub fn compute_resolve_hash(root_id: &str, dependency_ids: &[String], scenarios: &[String]) -> String {
+    let mut dependencies = dependency_ids.to_vec();
+    dependencies.sort();
+    dependencies.dedup();
+    let mut scenarios = scenarios.to_vec();
+    scenarios.sort();
+    scenarios.dedup();
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"root\n");
+    hasher.update(root_id.as_bytes());
+    hasher.update(b"\ndependencies\n");
+    for id in dependencies.iter() {
+        hasher.update(id.as_bytes());
+        hasher.update(b"\n");
+    }
+    hasher.update(b"scenarios\n");
+    for scenario in scenarios.iter() {
+        hasher.update(scenario.as_bytes());
+        hasher.update(b"\n");
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+pub fn result_resolve_hash(result: &WorkshopResolveResult) -> String {
+    compute_resolve_hash(&result.root_id, &result.dependency_ids, &result.scenarios)
+}
+
+pub fn profile_resolve_hash(profile: &ServerProfile) -> Option<String> {
+    let root_id = profile.root_mod_id.as_deref()?;
+    Some(compute_resolve_hash(
+        root_id,
+        &profile.dependency_mod_ids,
+        &profile.scenarios,
+    ))
+}
+
+pub fn diff_resolve_result(profile: &ServerProfile, result: &WorkshopResolveResult) -> DependencyDiff {
+    let mut previous_mods: Vec<&String> = profile.root_mod_id.iter().collect();
+    previous_mods.extend(profile.dependency_mod_ids.iter());
+    let mut current_mods = vec![&result.root_id];
+    current_mods.extend(result.dependency_ids.iter());
+
+    let added_mod_ids = current_mods
+        .iter()
+        .filter(|id| !previous_mods.contains(id))
+        .map(|id| (*id).clone())
+        .collect();
+    let removed_mod_ids = previous_mods
+        .iter()
+        .filter(|id| !current_mods.contains(id))
+        .map(|id| (*id).clone())
+        .collect();
+    let vanished_scenarios = profile
+        .scenarios
+        .iter()
+        .filter(|scenario| !result.scenarios.contains(scenario))
+        .cloned()
+        .collect();
+
+    DependencyDiff {
+        added_mod_ids: dedupe_preserve_order(added_mod_ids),
+        removed_mod_ids: dedupe_preserve_order(removed_mod_ids),
+        vanished_scenarios,
+    }
 }
 
 pub fn parse_root_page(html: &str, expected_id: Option<&str>) -> Result<WorkshopRootPage, String> {
@@ -161,13 +511,16 @@ pub fn parse_root_page(html: &str, expected_id: Option<&str>) -> Result<Workshop
 
     let mut workshop_id = expected_id.map(|value| value.to_string());
     let mut dependencies = Vec::new();
+    let mut metadata = ModMetadata::default();
 
     if let Some(value) = extract_embedded_json(&document) {
         if workshop_id.is_none() {
             workshop_id = extract_string(&value, &["workshopId", "id"]);
         }
         dependencies = extract_string_list(&value, &["dependencies"]);
+        metadata = extract_embedded_metadata(&value);
     }
+    fill_metadata_from_html(&document, &mut metadata);
 
     if workshop_id.is_none() {
         workshop_id = extract_workshop_id_from_html(html);
@@ -187,6 +540,7 @@ pub fn parse_root_page(html: &str, expected_id: Option<&str>) -> Result<Workshop
     Ok(WorkshopRootPage {
         workshop_id,
         dependency_urls,
+        metadata,
     })
 }
 
@@ -304,6 +658,98 @@ fn extract_dependency_urls_from_section(document: &Html) -> Option<Vec<String>>
     None
 }
 
+fn extract_embedded_metadata(value: &serde_json::Value) -> ModMetadata {
+    let author = value.get("author").and_then(|author| match author {
+        serde_json::Value::String(name) => Some(name.clone()),
+        other => extract_string(other, &["username", "name"]),
+    });
+    let size_bytes = ["size", "fileSize", "sizeBytes"]
+        .iter()
+        .filter_map(|key| value.get(*key))
+        .find_map(|size| match size {
+            serde_json::Value::Number(number) => number.as_u64(),
+            serde_json::Value::String(text) => parse_size(text),
+            _ => None,
+        });
+    ModMetadata {
+        name: extract_string(value, &["name", "title"]).and_then(non_empty),
+        author: author
+            .or_else(|| extract_string(value, &["authorName"]))
+            .and_then(non_empty),
+        version: extract_string(value, &["version", "currentVersion"]).and_then(non_empty),
+        size_bytes,
+        updated_at: extract_string(value, &["updatedAt", "lastUpdated", "lastModified"]).and_then(non_empty),
+        game_version: extract_string(value, &["gameVersion"]).and_then(non_empty),
+    }
+}
+
+/// Fills gaps from the rendered page: the title from `<h1>`, everything else from
+/// label/value pairs such as `Version` followed by `1.0.15`.
+fn fill_metadata_from_html(document: &Html, metadata: &mut ModMetadata) {
+    if metadata.name.is_none() {
+        let selector = Selector::parse("h1").expect("title selector");
+        metadata.name = document
+            .select(&selector)
+            .next()
+            .map(|node| node.text().collect::<String>().trim().to_string())
+            .and_then(non_empty);
+    }
+
+    let texts: Vec<&str> = document
+        .root_element()
+        .text()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .collect();
+    let value_after = |labels: &[&str]| {
+        texts
+            .windows(2)
+            .find(|pair| labels.iter().any(|label| pair[0].eq_ignore_ascii_case(label)))
+            .map(|pair| pair[1].to_string())
+    };
+
+    if metadata.author.is_none() {
+        metadata.author = value_after(&["Author", "by"]);
+    }
+    if metadata.version.is_none() {
+        metadata.version = value_after(&["Version", "Current version"]);
+    }
+    if metadata.size_bytes.is_none() {
+        metadata.size_bytes = value_after(&["Version size", "Size"]).and_then(|text| parse_size(&text));
+    }
+    if metadata.updated_at.is_none() {
+        metadata.updated_at = value_after(&["Last modified", "Last updated", "Updated"]);
+    }
+    if metadata.game_version.is_none() {
+        metadata.game_version = value_after(&["Game version"]);
+    }
+}
+
+/// Parses sizes like `3.47 MB` or `812 KiB`; decimal units are powers of 1000.
+fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .unwrap_or(text.len());
+    let number: f64 = text[..split].replace(',', "").parse().ok()?;
+    let factor: f64 = match text[split..].trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1.0,
+        "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "KIB" => 1024.0,
+        "MIB" => 1024.0 * 1024.0,
+        "GIB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * factor).round() as u64)
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let trimmed = value.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
 fn normalize_dependency_urls(urls: Vec<String>) -> Vec<String> {
     urls.into_iter()
         .map(|url| {
@@ -0,0 +1,235 @@
+use crate::storage::WorkshopCacheSettings;
+use crate::workshop::{FetchedPage, WorkshopFetcher};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedPage {
+    url: String,
+    fetched_at: u64,
+    #[serde(default)]
+    etag: Option<String>,
+    html: String,
+}
+
+#[derive(Debug, Clone, Serialize, schemars::JsonSchema)]
+pub struct CacheEntryInfo {
+    pub url: String,
+    pub fetched_at: u64,
+    pub etag: Option<String>,
+    pub size_bytes: u64,
+    /// Older than the TTL; revalidated on the next fetch.
+    pub expired: bool,
+}
+
+#[derive(Debug, Clone, Serialize, schemars::JsonSchema)]
+pub struct WorkshopCacheStatus {
+    pub settings: WorkshopCacheSettings,
+    pub total_bytes: u64,
+    /// Newest first.
+    pub entries: Vec<CacheEntryInfo>,
+}
+
+/// Workshop pages stored as one JSON file per URL, shared between the caching
+/// fetcher and the settings page.
+#[derive(Clone)]
+pub struct WorkshopCache {
+    dir: PathBuf,
+    settings: Arc<Mutex<WorkshopCacheSettings>>,
+}
+
+impl WorkshopCache {
+    pub fn new(dir: PathBuf, settings: WorkshopCacheSettings) -> Self {
+        Self {
+            dir,
+            settings: Arc::new(Mutex::new(settings)),
+        }
+    }
+
+    pub fn settings(&self) -> WorkshopCacheSettings {
+        *self.settings.lock().expect("workshop cache settings lock")
+    }
+
+    pub fn set_settings(&self, settings: WorkshopCacheSettings) {
+        *self.settings.lock().expect("workshop cache settings lock") = settings;
+    }
+
+    pub async fn status(&self) -> Result<WorkshopCacheStatus, String> {
+        let settings = self.settings();
+        let now = current_epoch_seconds();
+        let mut entries = Vec::new();
+        for (path, size_bytes) in self.files().await? {
+            let Some(page) = read_page(&path).await else {
+                continue;
+            };
+            entries.push(CacheEntryInfo {
+                expired: is_expired(&settings, page.fetched_at, now),
+                url: page.url,
+                fetched_at: page.fetched_at,
+                etag: page.etag,
+                size_bytes,
+            });
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.fetched_at));
+        Ok(WorkshopCacheStatus {
+            settings,
+            total_bytes: entries.iter().map(|entry| entry.size_bytes).sum(),
+            entries,
+        })
+    }
+
+    /// Removes cached pages, or only the expired ones, and returns how many were removed.
+    pub async fn purge(&self, expired_only: bool) -> Result<usize, String> {
+        let settings = self.settings();
+        let now = current_epoch_seconds();
+        let mut removed = 0;
+        for (path, _) in self.files().await? {
+            if expired_only {
+                // Unreadable entries are useless either way and go with the expired ones.
+                if let Some(page) = read_page(&path).await {
+                    if !is_expired(&settings, page.fetched_at, now) {
+                        continue;
+                    }
+                }
+            }
+            tokio::fs::remove_file(&path)
+                .await
+                .map_err(|err| format!("failed to remove {}: {err}", path.display()))?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    async fn get(&self, url: &str) -> Option<CachedPage> {
+        read_page(&self.entry_path(url))
+            .await
+            .filter(|page| page.url == url)
+    }
+
+    async fn put(&self, page: &CachedPage) -> Result<(), String> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|err| format!("failed to create {}: {err}", self.dir.display()))?;
+        let data = serde_json::to_string(page)
+            .map_err(|err| format!("failed to serialize cached page: {err}"))?;
+        let path = self.entry_path(&page.url);
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, data)
+            .await
+            .map_err(|err| format!("failed to write {}: {err}", tmp_path.display()))?;
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .map_err(|err| format!("failed to move {} into place: {err}", path.display()))
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        let key: String = Sha256::digest(url.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        self.dir.join(format!("{key}.json"))
+    }
+
+    async fn files(&self) -> Result<Vec<(PathBuf, u64)>, String> {
+        let mut reader = match tokio::fs::read_dir(&self.dir).await {
+            Ok(reader) => reader,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(format!("failed to read {}: {err}", self.dir.display())),
+        };
+        let mut files = Vec::new();
+        while let Some(entry) = reader
+            .next_entry()
+            .await
+            .map_err(|err| format!("failed to read {}: {err}", self.dir.display()))?
+        {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let size = entry.metadata().await.map(|meta| meta.len()).unwrap_or(0);
+            files.push((path, size));
+        }
+        Ok(files)
+    }
+}
+
+/// Serves workshop pages from a `WorkshopCache`. Fresh pages are returned as is,
+/// expired ones are revalidated with their ETag, and any cached page is used when
+/// the workshop cannot be reached. In offline mode only cached pages are used.
+pub struct CachingFetcher {
+    inner: Arc<dyn WorkshopFetcher>,
+    cache: WorkshopCache,
+}
+
+impl CachingFetcher {
+    pub fn new(inner: Arc<dyn WorkshopFetcher>, cache: WorkshopCache) -> Self {
+        Self { inner, cache }
+    }
+}
+
+#[async_trait::async_trait]
+impl WorkshopFetcher for CachingFetcher {
+    async fn fetch_html(&self, url: &str) -> Result<String, String> {
+        let settings = self.cache.settings();
+        let cached = self.cache.get(url).await;
+        if settings.offline {
+            return cached
+                .map(|page| page.html)
+                .ok_or_else(|| format!("offline mode: {url} is not cached"));
+        }
+
+        let now = current_epoch_seconds();
+        if let Some(page) = cached.as_ref() {
+            if !is_expired(&settings, page.fetched_at, now) {
+                return Ok(page.html.clone());
+            }
+        }
+
+        let etag = cached.as_ref().and_then(|page| page.etag.as_deref());
+        let page = match self.inner.fetch_if_modified(url, etag).await {
+            Ok(FetchedPage::Modified { html, etag }) => CachedPage {
+                url: url.to_string(),
+                fetched_at: now,
+                etag,
+                html,
+            },
+            Ok(FetchedPage::NotModified) => match cached {
+                Some(page) => CachedPage {
+                    fetched_at: now,
+                    ..page
+                },
+                None => return Err(format!("{url} reported not modified but is not cached")),
+            },
+            Err(err) => match cached {
+                Some(page) => {
+                    tracing::warn!("workshop unavailable, using cached copy of {url}: {err}");
+                    return Ok(page.html);
+                }
+                None => return Err(err),
+            },
+        };
+
+        if let Err(err) = self.cache.put(&page).await {
+            tracing::warn!("failed to cache {url}: {err}");
+        }
+        Ok(page.html)
+    }
+}
+
+fn is_expired(settings: &WorkshopCacheSettings, fetched_at: u64, now: u64) -> bool {
+    now.saturating_sub(fetched_at) >= settings.ttl_secs
+}
+
+async fn read_page(path: &Path) -> Option<CachedPage> {
+    let contents = tokio::fs::read_to_string(path).await.ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn current_epoch_seconds() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
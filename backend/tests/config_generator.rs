@@ -1,7 +1,7 @@
-use backend::config_gen::generate_server_config;
+use backend::config_gen::{apply_game_overrides, generate_server_config, ConfigMod};
 use backend::defaults::{apply_default_server_json_settings, apply_profile_overrides};
 use backend::storage::AppSettings;
-use backend::models::ServerProfile;
+use backend::models::{RestartPolicy, ServerProfile};
 
 #[test]
 fn overwrites_scenario_and_mods() {
@@ -23,6 +23,35 @@ fn overwrites_scenario_and_mods() {
     assert_eq!(mod_ids, vec!["AAA", "BBB"]);
 }
 
+#[test]
+fn writes_known_mod_names_and_versions() {
+    let mut config = generate_server_config("{TEST}Missions/Example.conf", &[], None)
+        .expect("config generation failed");
+    let mods = vec![
+        ConfigMod {
+            mod_id: "AAA".to_string(),
+            name: Some("Some Mod".to_string()),
+            version: Some("1.0.15".to_string()),
+        },
+        ConfigMod {
+            mod_id: "BBB".to_string(),
+            name: Some(" ".to_string()),
+            version: None,
+        },
+        ConfigMod::new("AAA"),
+    ];
+    apply_game_overrides(&mut config, "{TEST}Missions/Example.conf", &mods, None)
+        .expect("apply game overrides");
+
+    assert_eq!(
+        config["game"]["mods"],
+        serde_json::json!([
+            { "modId": "AAA", "name": "Some Mod", "version": "1.0.15" },
+            { "modId": "BBB" }
+        ])
+    );
+}
+
 #[test]
 fn applies_settings_defaults_and_profile_overrides() {
     let mut settings = AppSettings::default();
@@ -44,10 +73,13 @@ fn applies_settings_defaults_and_profile_overrides() {
         optional_mod_ids: Vec::new(),
         optional_package_ids: Vec::new(),
         load_session_save: false,
+        restart_policy: RestartPolicy::Never,
+        update_before_start: false,
         steamcmd_dir_override: None,
         reforger_server_exe_override: None,
         reforger_server_work_dir_override: None,
         profile_dir_base_override: None,
+        steam_branch_override: None,
         server_json_overrides: serde_json::json!({
             "game": { "maxPlayers": 24 }
         }),
@@ -57,6 +89,9 @@ fn applies_settings_defaults_and_profile_overrides() {
         generated_config_path: None,
         last_resolved_at: None,
         last_resolve_hash: None,
+        mod_metadata: Default::default(),
+        pin_mod_versions: false,
+        mod_version_pins: Default::default(),
     };
 
     let mut config = generate_server_config(
@@ -0,0 +1,94 @@
+use backend::models::{RestartPolicy, ServerProfile};
+use backend::workshop::{
+    compute_resolve_hash, diff_resolve_result, profile_resolve_hash, result_resolve_hash,
+    WorkshopResolveResult,
+};
+
+fn profile(root: &str, deps: &[&str], scenarios: &[&str]) -> ServerProfile {
+    ServerProfile {
+        profile_id: "test".to_string(),
+        display_name: "Test".to_string(),
+        workshop_url: "url".to_string(),
+        root_mod_id: Some(root.to_string()),
+        selected_scenario_id_path: scenarios.first().map(|value| value.to_string()),
+        scenarios: scenarios.iter().map(|value| value.to_string()).collect(),
+        dependency_mod_ids: deps.iter().map(|value| value.to_string()).collect(),
+        optional_mod_ids: Vec::new(),
+        optional_package_ids: Vec::new(),
+        load_session_save: false,
+        restart_policy: RestartPolicy::Never,
+        update_before_start: false,
+        steamcmd_dir_override: None,
+        reforger_server_exe_override: None,
+        reforger_server_work_dir_override: None,
+        profile_dir_base_override: None,
+        steam_branch_override: None,
+        server_json_overrides: serde_json::json!({}),
+        server_json_override_enabled: std::collections::HashMap::new(),
+        generated_config_path: None,
+        last_resolved_at: None,
+        last_resolve_hash: None,
+        mod_metadata: Default::default(),
+        pin_mod_versions: false,
+        mod_version_pins: Default::default(),
+    }
+}
+
+fn result(root: &str, deps: &[&str], scenarios: &[&str]) -> WorkshopResolveResult {
+    WorkshopResolveResult {
+        root_id: root.to_string(),
+        root_url: "url".to_string(),
+        scenarios: scenarios.iter().map(|value| value.to_string()).collect(),
+        dependency_ids: deps.iter().map(|value| value.to_string()).collect(),
+        errors: Vec::new(),
+        metadata: Default::default(),
+    }
+}
+
+#[test]
+fn resolve_hash_ignores_order() {
+    let first = compute_resolve_hash(
+        "ROOT",
+        &["AAA".to_string(), "BBB".to_string()],
+        &["S1".to_string(), "S2".to_string()],
+    );
+    let second = compute_resolve_hash(
+        "ROOT",
+        &["BBB".to_string(), "AAA".to_string()],
+        &["S2".to_string(), "S1".to_string()],
+    );
+    assert_eq!(first, second);
+    assert_eq!(first.len(), 64);
+}
+
+#[test]
+fn resolve_hash_changes_with_dependencies() {
+    let stored = profile("ROOT", &["AAA"], &["S1"]);
+    let resolved = result("ROOT", &["AAA", "CCC"], &["S1"]);
+    assert_ne!(profile_resolve_hash(&stored), Some(result_resolve_hash(&resolved)));
+
+    let unchanged = result("ROOT", &["AAA"], &["S1"]);
+    assert_eq!(profile_resolve_hash(&stored), Some(result_resolve_hash(&unchanged)));
+}
+
+#[test]
+fn diff_reports_added_removed_and_vanished() {
+    let stored = profile("ROOT", &["AAA", "BBB"], &["S1", "S2"]);
+    let resolved = result("ROOT", &["BBB", "CCC"], &["S2"]);
+
+    let diff = diff_resolve_result(&stored, &resolved);
+    assert_eq!(diff.added_mod_ids, vec!["CCC"]);
+    assert_eq!(diff.removed_mod_ids, vec!["AAA"]);
+    assert_eq!(diff.vanished_scenarios, vec!["S1"]);
+    assert!(!diff.is_empty());
+}
+
+#[test]
+fn diff_is_empty_when_unchanged() {
+    let stored = profile("ROOT", &["AAA"], &["S1"]);
+    let resolved = result("ROOT", &["AAA"], &["S1", "S2"]);
+
+    let diff = diff_resolve_result(&stored, &resolved);
+    assert!(diff.is_empty());
+    assert_eq!(diff.summary(), "no changes");
+}
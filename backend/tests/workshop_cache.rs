@@ -0,0 +1,130 @@
+use backend::storage::WorkshopCacheSettings;
+use backend::workshop::{FetchedPage, WorkshopFetcher, WorkshopResolver};
+use backend::workshop_cache::{CachingFetcher, WorkshopCache};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const ROOT_URL: &str = "https://reforger.armaplatform.com/workshop/595F2BF2F44836FB-RHS-StatusQuo";
+
+fn read_fixture(name: &str) -> String {
+    std::fs::read_to_string(format!("tests/fixtures/{name}")).expect("fixture missing")
+}
+
+/// Serves the fixtures with a fixed ETag and counts full and conditional fetches.
+#[derive(Default)]
+struct CountingFetcher {
+    down: AtomicBool,
+    full: AtomicUsize,
+    not_modified: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl WorkshopFetcher for CountingFetcher {
+    async fn fetch_html(&self, url: &str) -> Result<String, String> {
+        match url.strip_prefix(ROOT_URL) {
+            Some("") => Ok(read_fixture("workshop_root_with_deps.html")),
+            Some("/scenarios") => Ok(read_fixture("workshop_scenarios.html")),
+            _ if url.contains("5AAAC70D754245DD") => Ok(read_fixture("workshop_dep_5AAA.html")),
+            _ if url.contains("5C9758250C8C56F1") => Ok(read_fixture("workshop_dep_5C97.html")),
+            _ => Err("unknown url".to_string()),
+        }
+    }
+
+    async fn fetch_if_modified(&self, url: &str, etag: Option<&str>) -> Result<FetchedPage, String> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err("request failed: connection refused".to_string());
+        }
+        if etag == Some("\"v1\"") {
+            self.not_modified.fetch_add(1, Ordering::SeqCst);
+            return Ok(FetchedPage::NotModified);
+        }
+        self.full.fetch_add(1, Ordering::SeqCst);
+        Ok(FetchedPage::Modified {
+            html: self.fetch_html(url).await?,
+            etag: Some("\"v1\"".to_string()),
+        })
+    }
+}
+
+fn setup(name: &str, ttl_secs: u64) -> (Arc<CountingFetcher>, WorkshopCache, WorkshopResolver) {
+    let dir = std::env::temp_dir().join(format!("arssm-workshop-cache-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let inner = Arc::new(CountingFetcher::default());
+    let cache = WorkshopCache::new(
+        dir,
+        WorkshopCacheSettings {
+            ttl_secs,
+            offline: false,
+        },
+    );
+    let resolver = WorkshopResolver::new(Arc::new(CachingFetcher::new(inner.clone(), cache.clone())));
+    (inner, cache, resolver)
+}
+
+#[tokio::test]
+async fn fresh_pages_are_served_from_the_cache() {
+    let (inner, cache, resolver) = setup("fresh", 3600);
+    let first = resolver.resolve(ROOT_URL, 2).await.expect("resolve");
+    assert_eq!(inner.full.load(Ordering::SeqCst), 4);
+
+    let second = resolver.resolve(ROOT_URL, 2).await.expect("resolve");
+    assert_eq!(inner.full.load(Ordering::SeqCst), 4);
+    assert_eq!(second.dependency_ids, first.dependency_ids);
+    assert_eq!(second.scenarios, first.scenarios);
+
+    let status = cache.status().await.expect("status");
+    assert_eq!(status.entries.len(), 4);
+    assert!(status.entries.iter().all(|entry| !entry.expired));
+    assert!(status.total_bytes > 0);
+
+    assert_eq!(cache.purge(true).await.expect("purge"), 0);
+    assert_eq!(cache.purge(false).await.expect("purge"), 4);
+    assert!(cache.status().await.expect("status").entries.is_empty());
+}
+
+#[tokio::test]
+async fn expired_pages_are_revalidated_and_survive_an_outage() {
+    let (inner, cache, resolver) = setup("expired", 0);
+    resolver.resolve(ROOT_URL, 2).await.expect("resolve");
+    assert_eq!(inner.full.load(Ordering::SeqCst), 4);
+
+    resolver.resolve(ROOT_URL, 2).await.expect("resolve");
+    assert_eq!(inner.full.load(Ordering::SeqCst), 4);
+    assert_eq!(inner.not_modified.load(Ordering::SeqCst), 4);
+
+    inner.down.store(true, Ordering::SeqCst);
+    let result = resolver.resolve(ROOT_URL, 2).await.expect("resolve from stale cache");
+    assert_eq!(result.dependency_ids.len(), 2);
+    assert!(result.errors.is_empty());
+
+    cache.purge(true).await.expect("purge");
+    assert!(resolver.resolve(ROOT_URL, 2).await.is_err());
+}
+
+#[tokio::test]
+async fn offline_mode_uses_only_cached_pages() {
+    let (inner, cache, resolver) = setup("offline", 3600);
+    cache.set_settings(WorkshopCacheSettings {
+        ttl_secs: 3600,
+        offline: true,
+    });
+    let err = resolver.resolve(ROOT_URL, 2).await.expect_err("nothing cached");
+    assert!(err.contains("offline mode"));
+    assert_eq!(inner.full.load(Ordering::SeqCst), 0);
+
+    cache.set_settings(WorkshopCacheSettings {
+        ttl_secs: 3600,
+        offline: false,
+    });
+    resolver.resolve(ROOT_URL, 2).await.expect("resolve");
+
+    // Expired pages are used as they are rather than revalidated.
+    cache.set_settings(WorkshopCacheSettings {
+        ttl_secs: 0,
+        offline: true,
+    });
+    let result = resolver.resolve(ROOT_URL, 2).await.expect("resolve offline");
+    assert_eq!(result.scenarios.len(), 2);
+    assert_eq!(inner.full.load(Ordering::SeqCst), 4);
+    assert_eq!(inner.not_modified.load(Ordering::SeqCst), 0);
+}
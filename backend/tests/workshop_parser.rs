@@ -1,3 +1,4 @@
+use backend::models::ModMetadata;
 use backend::workshop::{
     extract_workshop_id_from_html,
     extract_workshop_id_from_url,
@@ -106,4 +107,50 @@ async fn resolves_dependencies_recursively() {
     assert_eq!(result.dependency_ids.len(), 2);
     assert!(result.dependency_ids.contains(&"5AAAC70D754245DD".to_string()));
     assert!(result.dependency_ids.contains(&"5C9758250C8C56F1".to_string()));
+
+    assert_eq!(result.metadata.len(), 1);
+    let some_mod = &result.metadata["5AAAC70D754245DD"];
+    assert_eq!(some_mod.name.as_deref(), Some("Some Mod"));
+    assert_eq!(some_mod.version.as_deref(), Some("1.0.15"));
+}
+
+#[test]
+fn parses_metadata_from_embedded_state() {
+    let html = read_fixture("workshop_metadata.html");
+    let parsed = parse_root_page(&html, None).expect("parse failed");
+
+    assert_eq!(parsed.workshop_id, "595F2BF2F44836FB");
+    assert_eq!(
+        parsed.metadata,
+        ModMetadata {
+            name: Some("RHS - Status Quo".to_string()),
+            author: Some("Red Hammer Studios".to_string()),
+            version: Some("0.10.4321".to_string()),
+            size_bytes: Some(2_254_857_830),
+            updated_at: Some("2024-11-29T17:42:10.000Z".to_string()),
+            game_version: Some("1.2.0.102".to_string()),
+        }
+    );
+}
+
+#[test]
+fn parses_metadata_from_page_html() {
+    let html = read_fixture("workshop_metadata_html.html");
+    let parsed = parse_root_page(&html, None).expect("parse failed");
+
+    assert_eq!(parsed.workshop_id, "5AAAC70D754245DD");
+    assert_eq!(
+        parsed.metadata,
+        ModMetadata {
+            name: Some("Some Mod".to_string()),
+            author: Some("someone".to_string()),
+            version: Some("1.0.15".to_string()),
+            size_bytes: Some(3_470_000),
+            updated_at: Some("2024-12-01".to_string()),
+            game_version: Some("1.2.0.102".to_string()),
+        }
+    );
+
+    let bare = parse_root_page(&read_fixture("workshop_root_with_deps.html"), None).expect("parse failed");
+    assert!(bare.metadata.is_empty());
 }
@@ -0,0 +1,186 @@
+use backend::workshop::{RateLimitedFetcher, ResolveOptions, WorkshopFetcher, WorkshopResolver};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const BASE: &str = "https://reforger.armaplatform.com/workshop/";
+
+fn id(n: u32) -> String {
+    format!("{n:016X}")
+}
+
+fn url(n: u32) -> String {
+    format!("{BASE}{}-Mod-{n}", id(n))
+}
+
+/// A synthetic workshop: item ids mapped to their dependency ids.
+struct Graph(HashMap<u32, Vec<u32>>);
+
+impl Graph {
+    fn page(&self, n: u32) -> String {
+        let dependencies: Vec<String> = self.0[&n].iter().map(|dep| url(*dep)).collect();
+        format!(
+            r#"<html><script id="__WORKSHOP_STATE__" type="application/json">{}</script></html>"#,
+            serde_json::json!({ "workshopId": id(n), "dependencies": dependencies })
+        )
+    }
+
+    /// The dependency order of the original, strictly sequential walk.
+    fn sequential_order(&self, root: u32, max_depth: usize) -> Vec<String> {
+        let mut order = Vec::new();
+        let mut visited = HashSet::from([root]);
+        let mut queue: VecDeque<(u32, usize)> = self.0[&root].iter().map(|dep| (*dep, 1)).collect();
+        let mut seen_ids = HashSet::from([root]);
+        while let Some((n, depth)) = queue.pop_front() {
+            if depth > max_depth || !visited.insert(n) {
+                continue;
+            }
+            if seen_ids.insert(n) {
+                order.push(id(n));
+            }
+            if depth < max_depth {
+                queue.extend(self.0[&n].iter().filter(|dep| !visited.contains(dep)).map(|dep| (*dep, depth + 1)));
+            }
+        }
+        order
+    }
+}
+
+/// Serves a `Graph` with per-page latency and scripted failures, recording attempts
+/// and the highest number of requests in flight.
+struct FakeWorkshop {
+    graph: Graph,
+    latency: fn(u32) -> Duration,
+    failures: HashMap<u32, (usize, &'static str)>,
+    attempts: Mutex<HashMap<String, usize>>,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+impl FakeWorkshop {
+    fn new(graph: Graph, latency: fn(u32) -> Duration) -> Self {
+        Self {
+            graph,
+            latency,
+            failures: HashMap::new(),
+            attempts: Mutex::new(HashMap::new()),
+            in_flight: AtomicUsize::new(0),
+            max_in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    fn attempts(&self, n: u32) -> usize {
+        self.attempts.lock().unwrap().get(&url(n)).copied().unwrap_or(0)
+    }
+}
+
+#[async_trait::async_trait]
+impl WorkshopFetcher for FakeWorkshop {
+    async fn fetch_html(&self, page_url: &str) -> Result<String, String> {
+        let attempt = {
+            let mut attempts = self.attempts.lock().unwrap();
+            let count = attempts.entry(page_url.to_string()).or_insert(0);
+            *count += 1;
+            *count
+        };
+        if let Some(scenarios_of) = page_url.strip_suffix("/scenarios") {
+            assert_eq!(scenarios_of, url(0));
+            return Ok(format!("Scenario ID {{{}}}Missions/Coop.conf", id(0)));
+        }
+        let n = self
+            .graph
+            .0
+            .keys()
+            .copied()
+            .find(|n| url(*n) == page_url)
+            .ok_or_else(|| "request failed: status 404 Not Found".to_string())?;
+
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep((self.latency)(n)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        match self.failures.get(&n) {
+            Some((failing_attempts, err)) if attempt <= *failing_attempts => Err(err.to_string()),
+            _ => Ok(self.graph.page(n)),
+        }
+    }
+}
+
+fn options(concurrency: usize) -> ResolveOptions {
+    ResolveOptions {
+        concurrency,
+        max_retries: 3,
+        retry_base_delay: Duration::from_millis(2),
+    }
+}
+
+/// Root 0 with twelve direct dependencies that share deeper ones, point back to
+/// the root and to each other.
+fn modpack() -> Graph {
+    let mut graph = HashMap::new();
+    graph.insert(0, (1..=12).collect());
+    for n in 1..=12u32 {
+        graph.insert(n, vec![100 + n % 5, 100 + (n + 2) % 5, 0, 13 - n]);
+    }
+    for n in 100..105u32 {
+        graph.insert(n, vec![200 + n % 3, 7]);
+    }
+    for n in 200..203u32 {
+        graph.insert(n, Vec::new());
+    }
+    Graph(graph)
+}
+
+#[tokio::test]
+async fn parallel_resolve_keeps_the_sequential_order() {
+    // Later pages answer first, so completion order differs from queue order.
+    let fake = Arc::new(FakeWorkshop::new(modpack(), |n| Duration::from_millis(u64::from(300 - n % 300) / 10)));
+    let resolver = WorkshopResolver::with_options(fake.clone(), options(4));
+
+    for max_depth in [0, 1, 2, 5] {
+        let result = resolver.resolve(&url(0), max_depth).await.expect("resolve");
+        assert_eq!(result.dependency_ids, fake.graph.sequential_order(0, max_depth), "depth {max_depth}");
+        assert!(result.errors.is_empty());
+        assert_eq!(result.scenarios, vec![format!("{{{}}}Missions/Coop.conf", id(0))]);
+    }
+    let max_in_flight = fake.max_in_flight.load(Ordering::SeqCst);
+    assert!((2..=4).contains(&max_in_flight), "max in flight {max_in_flight}");
+}
+
+#[tokio::test]
+async fn transient_errors_are_retried_with_backoff() {
+    let mut fake = FakeWorkshop::new(modpack(), |_| Duration::from_millis(1));
+    fake.failures.insert(3, (2, "request failed: status 503 Service Unavailable"));
+    fake.failures.insert(5, (10, "request failed: error sending request"));
+    fake.failures.insert(8, (1, "request failed: status 404 Not Found"));
+    let fake = Arc::new(fake);
+    let resolver = WorkshopResolver::with_options(fake.clone(), options(8));
+
+    let result = resolver.resolve(&url(0), 1).await.expect("resolve");
+
+    assert_eq!(fake.attempts(3), 3);
+    assert_eq!(fake.attempts(5), 4);
+    assert_eq!(fake.attempts(8), 1);
+    assert!(result.dependency_ids.contains(&id(3)));
+    assert!(!result.dependency_ids.contains(&id(5)));
+    assert!(!result.dependency_ids.contains(&id(8)));
+    assert_eq!(result.errors.len(), 2);
+    assert!(result.errors[0].contains(&id(5)) && result.errors[0].contains("error sending request"));
+    assert!(result.errors[1].contains(&id(8)) && result.errors[1].contains("404"));
+}
+
+#[tokio::test]
+async fn rate_limited_fetcher_spaces_requests() {
+    let fake = Arc::new(FakeWorkshop::new(modpack(), |_| Duration::ZERO));
+    let limited = Arc::new(RateLimitedFetcher::new(fake.clone(), 50));
+    let resolver = WorkshopResolver::with_options(limited, options(8));
+
+    let started = Instant::now();
+    let result = resolver.resolve(&url(0), 1).await.expect("resolve");
+
+    // Root, scenarios and twelve dependencies: thirteen gaps of 20 ms after the first.
+    assert_eq!(result.dependency_ids.len(), 12);
+    assert!(started.elapsed() >= Duration::from_millis(13 * 20 - 5), "took {:?}", started.elapsed());
+}
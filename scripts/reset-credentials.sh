@@ -1,21 +1,18 @@
 #!/usr/bin/env bash
 set -euo pipefail
 
-ARSSM_CONFIG_DIR="${XDG_CONFIG_HOME:-$HOME/.config}/arssm"
-CREDENTIALS_PATH="$ARSSM_CONFIG_DIR/credentials.json"
+# Generates a new username/password pair. Only the Argon2 hash is stored in
+# credentials.json, so the password below is shown this one time only.
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+BACKEND_DIR="$SCRIPT_DIR/../backend"
 
-mkdir -p "$ARSSM_CONFIG_DIR"
+if [[ -n "${ARSSM_BIN:-}" ]]; then
+  "$ARSSM_BIN" reset-credentials
+elif command -v cargo >/dev/null 2>&1; then
+  cargo run --quiet --release --manifest-path "$BACKEND_DIR/Cargo.toml" -- reset-credentials
+else
+  echo "cargo not found. Set ARSSM_BIN to the ARSSM binary and run this script again."
+  exit 1
+fi
 
-USERNAME="$(tr -dc 'a-zA-Z0-9' </dev/urandom | head -c 8 || true)"
-PASSWORD="$(tr -dc 'a-zA-Z0-9' </dev/urandom | head -c 20 || true)"
-
-cat > "$CREDENTIALS_PATH" <<EOF
-{
-  "username": "$USERNAME",
-  "password": "$PASSWORD"
-}
-EOF
-
-echo "New credentials:"
-echo "  Username: $USERNAME"
-echo "  Password: $PASSWORD"
+echo "Restart ARSSM to apply the new credentials."
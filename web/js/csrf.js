@@ -0,0 +1,38 @@
+// Sends the session's CSRF token (from the readable arssm_csrf cookie) with every
+// state-changing request: plain form posts, htmx requests and fetch() calls.
+(function () {
+  function csrfToken() {
+    const match = document.cookie.match(/(?:^|;\s*)arssm_csrf=([^;]*)/);
+    return match ? decodeURIComponent(match[1]) : '';
+  }
+
+  document.addEventListener('submit', (event) => {
+    const form = event.target;
+    if (!(form instanceof HTMLFormElement) || form.method.toLowerCase() !== 'post') {
+      return;
+    }
+    let input = form.querySelector('input[name="csrf_token"]');
+    if (!input) {
+      input = document.createElement('input');
+      input.type = 'hidden';
+      input.name = 'csrf_token';
+      form.appendChild(input);
+    }
+    input.value = csrfToken();
+  }, true);
+
+  document.addEventListener('htmx:configRequest', (event) => {
+    event.detail.headers['X-CSRF-Token'] = csrfToken();
+  });
+
+  const nativeFetch = window.fetch.bind(window);
+  window.fetch = (resource, init = {}) => {
+    const method = (init.method || 'GET').toUpperCase();
+    if (method !== 'GET' && method !== 'HEAD') {
+      const headers = new Headers(init.headers || {});
+      headers.set('X-CSRF-Token', csrfToken());
+      init = { ...init, headers };
+    }
+    return nativeFetch(resource, init);
+  };
+})();